@@ -9,6 +9,7 @@
 //!
 //! [`tackler-core`]: ../../tackler_core/index.html
 mod filter_definition;
+pub mod filter_expr;
 pub mod logic;
 pub mod posting;
 pub mod txn;
@@ -29,6 +30,7 @@ use txn::TxnFilterBBoxLatLonAlt;
 use txn::TxnFilterTxnCode;
 use txn::TxnFilterTxnComments;
 use txn::TxnFilterTxnDescription;
+use txn::TxnFilterTxnMetadata;
 use txn::TxnFilterTxnTSBegin;
 use txn::TxnFilterTxnTSEnd;
 use txn::TxnFilterTxnTags;
@@ -40,6 +42,7 @@ use posting::TxnFilterPostingAmountGreater;
 use posting::TxnFilterPostingAmountLess;
 use posting::TxnFilterPostingComment;
 use posting::TxnFilterPostingCommodity;
+use posting::TxnFilterPostingTags;
 
 /// fmt with prefix indent
 ///
@@ -87,6 +90,8 @@ pub enum TxnFilter {
     TxnFilterTxnTags(TxnFilterTxnTags),
     #[doc(hidden)]
     TxnFilterTxnComments(TxnFilterTxnComments),
+    #[doc(hidden)]
+    TxnFilterTxnMetadata(TxnFilterTxnMetadata),
 
     // TXN Postings
     #[doc(hidden)]
@@ -101,6 +106,8 @@ pub enum TxnFilter {
     TxnFilterPostingAmountGreater(TxnFilterPostingAmountGreater),
     #[doc(hidden)]
     TxnFilterPostingCommodity(TxnFilterPostingCommodity),
+    #[doc(hidden)]
+    TxnFilterPostingTags(TxnFilterPostingTags),
 }
 
 /*
@@ -132,6 +139,7 @@ impl IndentDisplay for TxnFilter {
             TxnFilter::TxnFilterBBoxLatLonAlt(tf) => tf.i_fmt(indent, tz, f),
             TxnFilter::TxnFilterTxnTags(tf) => tf.i_fmt(indent, tz, f),
             TxnFilter::TxnFilterTxnComments(tf) => tf.i_fmt(indent, tz, f),
+            TxnFilter::TxnFilterTxnMetadata(tf) => tf.i_fmt(indent, tz, f),
 
             // posting filters
             TxnFilter::TxnFilterPostingAccount(tf) => tf.i_fmt(indent, tz, f),
@@ -140,6 +148,7 @@ impl IndentDisplay for TxnFilter {
             TxnFilter::TxnFilterPostingAmountLess(tf) => tf.i_fmt(indent, tz, f),
             TxnFilter::TxnFilterPostingAmountGreater(tf) => tf.i_fmt(indent, tz, f),
             TxnFilter::TxnFilterPostingCommodity(tf) => tf.i_fmt(indent, tz, f),
+            TxnFilter::TxnFilterPostingTags(tf) => tf.i_fmt(indent, tz, f),
         }
     }
 }
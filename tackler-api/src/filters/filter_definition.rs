@@ -4,6 +4,7 @@
  */
 use crate::filters::IndentDisplay;
 use crate::filters::TxnFilter;
+use crate::filters::filter_expr;
 use crate::tackler;
 use base64::{Engine as _, engine::general_purpose};
 use jiff::tz::TimeZone;
@@ -139,6 +140,53 @@ impl FilterDefinition {
 
         FilterDefinition::from_json_str(from_utf8(&filt_json)?)
     }
+
+    /// Generate filter from Txn Filter expression
+    ///
+    /// Timestamps without offset are interpreted in timezone `tz`.
+    /// See [`filter_expr`](crate::filters::filter_expr) module for the syntax.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tackler_api::tackler;
+    /// # use tackler_api::filters::FilterDefinition;
+    /// # use tackler_api::filters::TxnFilter;
+    /// use jiff::tz::TimeZone;
+    ///
+    /// let tf = FilterDefinition::from_expr(r#"tag = "trip" and ts >= 2024-01-01"#, TimeZone::UTC)?;
+    ///
+    /// match tf.txn_filter {
+    ///      TxnFilter::TxnFilterAND(_) => (),
+    ///      _ => panic!(),
+    /// }
+    ///
+    /// # Ok::<(), tackler::Error>(())
+    /// ```
+    pub fn from_expr(filt_expr: &str, tz: TimeZone) -> Result<FilterDefinition, tackler::Error> {
+        filter_expr::parse(filt_expr, tz)
+    }
+
+    /// Get filter as Txn Filter expression
+    ///
+    /// Timestamps are printed with offset of timezone `tz`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tackler_api::tackler;
+    /// # use tackler_api::filters::FilterDefinition;
+    /// use jiff::tz::TimeZone;
+    ///
+    /// let filter_json_str = r#"{"txnFilter":{"TxnFilterTxnTags":{"regex":"trip"}}}"#;
+    ///
+    /// let tf = FilterDefinition::from_json_str(filter_json_str)?;
+    ///
+    /// assert_eq!(tf.to_expr(TimeZone::UTC), r#"tag ~ "trip""#);
+    /// # Ok::<(), tackler::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_expr(&self, tz: TimeZone) -> String {
+        filter_expr::to_expr(&self.txn_filter, &tz)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,743 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Txn Filter expressions
+//!
+//! Textual query language for transaction filters, which is compiled
+//! into [`FilterDefinition`]. For example:
+//!
+//! ```text
+//! ts >= 2024-01-01 and tag = "trip" and not account ~ "^Expenses:Tax.*"
+//! ```
+//!
+//! Logic operators are `not`, `and` and `or` (in order of precedence),
+//! and expressions can be grouped with parentheses.
+//!
+//! | Expression                       | Filter                          |
+//! |----------------------------------|---------------------------------|
+//! | `true`, `false`                  | `NullaryTRUE`, `NullaryFALSE`   |
+//! | `ts >= <ts>`                     | `TxnFilterTxnTSBegin`           |
+//! | `ts < <ts>`                      | `TxnFilterTxnTSEnd`             |
+//! | `code ~ "<regex>"`               | `TxnFilterTxnCode`              |
+//! | `desc ~ "<regex>"`               | `TxnFilterTxnDescription`       |
+//! | `uuid = <uuid>`                  | `TxnFilterTxnUUID`              |
+//! | `bbox(s, w, n, e)`               | `TxnFilterBBoxLatLon`           |
+//! | `bbox(s, w, depth, n, e, height)`| `TxnFilterBBoxLatLonAlt`        |
+//! | `tag ~ "<regex>"`                | `TxnFilterTxnTags`              |
+//! | `comment ~ "<regex>"`            | `TxnFilterTxnComments`          |
+//! | `meta("<regex>") ~ "<regex>"`    | `TxnFilterTxnMetadata`          |
+//! | `account ~ "<regex>"`            | `TxnFilterPostingAccount`       |
+//! | `posting.comment ~ "<regex>"`    | `TxnFilterPostingComment`       |
+//! | `posting.tag ~ "<regex>"`        | `TxnFilterPostingTags`          |
+//! | `commodity ~ "<regex>"`          | `TxnFilterPostingCommodity`     |
+//! | `amount("<regex>") = <amount>`   | `TxnFilterPostingAmountEqual`   |
+//! | `amount("<regex>") < <amount>`   | `TxnFilterPostingAmountLess`    |
+//! | `amount("<regex>") > <amount>`   | `TxnFilterPostingAmountGreater` |
+//!
+//! Regex operator `~` uses the string as full haystack regex, and
+//! operator `=` matches the string literally.
+//!
+//! Timestamp is either RFC-3339 timestamp with offset, or date or date-time
+//! without offset, which are interpreted in the given timezone.
+//!
+//! Strings are enclosed in double quotes. Inside strings, `\"` is a quote and
+//! `\\` is a backslash, other backslashes are kept as they are (e.g. `"\d+"`).
+use crate::filters::logic::{TxnFilterAND, TxnFilterNOT, TxnFilterOR};
+use crate::filters::posting::{
+    TxnFilterPostingAccount, TxnFilterPostingAmountEqual, TxnFilterPostingAmountGreater,
+    TxnFilterPostingAmountLess, TxnFilterPostingComment, TxnFilterPostingCommodity,
+    TxnFilterPostingTags,
+};
+use crate::filters::txn::{
+    TxnFilterBBoxLatLon, TxnFilterBBoxLatLonAlt, TxnFilterTxnCode, TxnFilterTxnComments,
+    TxnFilterTxnDescription, TxnFilterTxnMetadata, TxnFilterTxnTSBegin, TxnFilterTxnTSEnd,
+    TxnFilterTxnTags, TxnFilterTxnUUID,
+};
+use crate::filters::{FilterDefinition, NullaryFALSE, NullaryTRUE, TxnFilter};
+use crate::tackler;
+use crate::txn_ts::rfc_3339;
+use jiff::Timestamp;
+use jiff::tz::TimeZone;
+use regex::Regex;
+use rust_decimal::Decimal;
+use tackler_rs::regex::{new_full_haystack_regex, peeled_pattern};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    LParen,
+    RParen,
+    Comma,
+    Op(&'static str),
+    Str(String),
+    Word(String),
+    End,
+}
+
+impl Tok {
+    fn describe(&self) -> String {
+        match self {
+            Tok::LParen => "'('".to_string(),
+            Tok::RParen => "')'".to_string(),
+            Tok::Comma => "','".to_string(),
+            Tok::Op(op) => format!("'{op}'"),
+            Tok::Str(s) => format!("string \"{s}\""),
+            Tok::Word(w) => format!("'{w}'"),
+            Tok::End => "end of expression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    tok: Tok,
+    /// byte offset of the token
+    offset: usize,
+}
+
+const OPERATORS: [&str; 6] = [">=", "<=", "~", "=", "<", ">"];
+
+fn is_word_char(c: char) -> bool {
+    !(c.is_whitespace() || "()\",~=<>".contains(c))
+}
+
+struct FilterParser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    tz: TimeZone,
+}
+
+impl<'a> FilterParser<'a> {
+    fn error(input: &str, offset: usize, msg: &str) -> tackler::Error {
+        let column = input[..offset].chars().count();
+        let msg = format!(
+            "Txn Filter expression error at column {}: {msg}\n   {input}\n   {}^",
+            column + 1,
+            " ".repeat(column)
+        );
+        msg.into()
+    }
+
+    fn tokenize(input: &str) -> Result<Vec<Token>, tackler::Error> {
+        let mut tokens = Vec::new();
+        let mut chars = input.char_indices().peekable();
+
+        while let Some(&(offset, c)) = chars.peek() {
+            let tok = match c {
+                c if c.is_whitespace() => {
+                    chars.next();
+                    continue;
+                }
+                '(' => {
+                    chars.next();
+                    Tok::LParen
+                }
+                ')' => {
+                    chars.next();
+                    Tok::RParen
+                }
+                ',' => {
+                    chars.next();
+                    Tok::Comma
+                }
+                '"' => {
+                    chars.next();
+                    let mut s = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.peek() {
+                                Some(&(_, e @ ('"' | '\\'))) => {
+                                    s.push(e);
+                                    chars.next();
+                                }
+                                _ => s.push('\\'),
+                            },
+                            Some((_, c)) => s.push(c),
+                            None => {
+                                return Err(Self::error(input, offset, "unterminated string"));
+                            }
+                        }
+                    }
+                    Tok::Str(s)
+                }
+                _ => match OPERATORS.iter().find(|op| input[offset..].starts_with(*op)) {
+                    Some(op) => {
+                        chars.nth(op.len() - 1);
+                        Tok::Op(op)
+                    }
+                    None => {
+                        let mut w = String::new();
+                        while let Some(&(_, c)) = chars.peek() {
+                            if !is_word_char(c) {
+                                break;
+                            }
+                            w.push(c);
+                            chars.next();
+                        }
+                        Tok::Word(w)
+                    }
+                },
+            };
+            tokens.push(Token { tok, offset });
+        }
+        tokens.push(Token {
+            tok: Tok::End,
+            offset: input.len(),
+        });
+        Ok(tokens)
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> &Token {
+        let t = &self.tokens[self.pos];
+        if t.tok != Tok::End {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn err_at(&self, token: &Token, expected: &str) -> tackler::Error {
+        let msg = format!("expected {expected}, found {}", token.tok.describe());
+        Self::error(self.input, token.offset, &msg)
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        matches!(&self.peek().tok, Tok::Word(w) if w == kw)
+    }
+
+    fn expect(&mut self, tok: Tok) -> Result<(), tackler::Error> {
+        if self.peek().tok == tok {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.err_at(self.peek(), &tok.describe()))
+        }
+    }
+
+    fn expect_op(&mut self, ops: &[&str]) -> Result<&'static str, tackler::Error> {
+        match self.peek().tok {
+            Tok::Op(op) if ops.contains(&op) => {
+                self.next();
+                Ok(op)
+            }
+            _ => {
+                let expected = format!("operator {}", ops.join(" or "));
+                Err(self.err_at(self.peek(), &expected))
+            }
+        }
+    }
+
+    fn expect_str(&mut self) -> Result<(String, usize), tackler::Error> {
+        match &self.peek().tok {
+            Tok::Str(s) => {
+                let res = (s.clone(), self.peek().offset);
+                self.next();
+                Ok(res)
+            }
+            _ => Err(self.err_at(self.peek(), "string")),
+        }
+    }
+
+    /// Word or string value
+    fn expect_value(&mut self, expected: &str) -> Result<(String, usize), tackler::Error> {
+        match &self.peek().tok {
+            Tok::Str(s) | Tok::Word(s) => {
+                let res = (s.clone(), self.peek().offset);
+                self.next();
+                Ok(res)
+            }
+            _ => Err(self.err_at(self.peek(), expected)),
+        }
+    }
+
+    fn decimal(&mut self) -> Result<Decimal, tackler::Error> {
+        let (value, offset) = self.expect_value("number")?;
+        value.parse::<Decimal>().map_err(|err| {
+            let msg = format!("invalid number '{value}': {err}");
+            Self::error(self.input, offset, &msg)
+        })
+    }
+
+    fn timestamp(&mut self) -> Result<Timestamp, tackler::Error> {
+        let (value, offset) = self.expect_value("timestamp")?;
+        let ts = match value.parse::<Timestamp>() {
+            Ok(ts) => Ok(ts),
+            Err(_) => match value.parse::<jiff::civil::DateTime>() {
+                Ok(dt) => dt.to_zoned(self.tz.clone()).map(|z| z.timestamp()),
+                Err(err) => Err(err),
+            },
+        };
+        ts.map_err(|err| {
+            let msg = format!("invalid timestamp '{value}': {err}");
+            Self::error(self.input, offset, &msg)
+        })
+    }
+
+    /// Regex with operator: `~` (regex) or `=` (literal string)
+    fn regex(&mut self) -> Result<Regex, tackler::Error> {
+        let op = self.expect_op(&["~", "="])?;
+        let (s, offset) = self.expect_str()?;
+        let pattern = match op {
+            "=" => regex::escape(&s),
+            _ => s,
+        };
+        new_full_haystack_regex(&pattern).map_err(|err| {
+            let msg = format!("invalid regex: {err}");
+            Self::error(self.input, offset, &msg)
+        })
+    }
+
+    fn expr(&mut self) -> Result<TxnFilter, tackler::Error> {
+        let mut filters = vec![self.and_expr()?];
+        while self.is_keyword("or") {
+            self.next();
+            filters.push(self.and_expr()?);
+        }
+        if filters.len() == 1 {
+            Ok(filters.remove(0))
+        } else {
+            Ok(TxnFilter::TxnFilterOR(TxnFilterOR {
+                txn_filters: filters,
+            }))
+        }
+    }
+
+    fn and_expr(&mut self) -> Result<TxnFilter, tackler::Error> {
+        let mut filters = vec![self.unary_expr()?];
+        while self.is_keyword("and") {
+            self.next();
+            filters.push(self.unary_expr()?);
+        }
+        if filters.len() == 1 {
+            Ok(filters.remove(0))
+        } else {
+            Ok(TxnFilter::TxnFilterAND(TxnFilterAND {
+                txn_filters: filters,
+            }))
+        }
+    }
+
+    fn unary_expr(&mut self) -> Result<TxnFilter, tackler::Error> {
+        if self.is_keyword("not") {
+            self.next();
+            let tf = self.unary_expr()?;
+            return Ok(TxnFilter::TxnFilterNOT(TxnFilterNOT {
+                txn_filter: Box::new(tf),
+            }));
+        }
+        if self.peek().tok == Tok::LParen {
+            self.next();
+            let tf = self.expr()?;
+            self.expect(Tok::RParen)?;
+            return Ok(tf);
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Result<TxnFilter, tackler::Error> {
+        let token = self.peek();
+        let Tok::Word(name) = &token.tok else {
+            return Err(self.err_at(token, "filter"));
+        };
+        let (name, offset) = (name.clone(), token.offset);
+        self.next();
+
+        let tf = match name.as_str() {
+            "true" => TxnFilter::NullaryTRUE(NullaryTRUE {}),
+            "false" => TxnFilter::NullaryFALSE(NullaryFALSE {}),
+            "ts" => match self.expect_op(&[">=", "<"])? {
+                ">=" => TxnFilter::TxnFilterTxnTSBegin(TxnFilterTxnTSBegin {
+                    begin: self.timestamp()?,
+                }),
+                _ => TxnFilter::TxnFilterTxnTSEnd(TxnFilterTxnTSEnd {
+                    end: self.timestamp()?,
+                }),
+            },
+            "code" => TxnFilter::TxnFilterTxnCode(TxnFilterTxnCode {
+                regex: self.regex()?,
+            }),
+            "desc" => TxnFilter::TxnFilterTxnDescription(TxnFilterTxnDescription {
+                regex: self.regex()?,
+            }),
+            "uuid" => {
+                self.expect_op(&["="])?;
+                let (value, offset) = self.expect_value("uuid")?;
+                let uuid = Uuid::parse_str(&value).map_err(|err| {
+                    let msg = format!("invalid uuid '{value}': {err}");
+                    Self::error(self.input, offset, &msg)
+                })?;
+                TxnFilter::TxnFilterTxnUUID(TxnFilterTxnUUID { uuid })
+            }
+            "bbox" => {
+                self.expect(Tok::LParen)?;
+                let mut values = vec![self.decimal()?];
+                while self.peek().tok == Tok::Comma {
+                    self.next();
+                    values.push(self.decimal()?);
+                }
+                let rparen_offset = self.peek().offset;
+                match values[..] {
+                    [south, west, north, east] => {
+                        self.expect(Tok::RParen)?;
+                        TxnFilter::TxnFilterBBoxLatLon(TxnFilterBBoxLatLon {
+                            south,
+                            west,
+                            north,
+                            east,
+                        })
+                    }
+                    [south, west, depth, north, east, height] => {
+                        self.expect(Tok::RParen)?;
+                        TxnFilter::TxnFilterBBoxLatLonAlt(TxnFilterBBoxLatLonAlt {
+                            south,
+                            west,
+                            depth,
+                            north,
+                            east,
+                            height,
+                        })
+                    }
+                    _ => {
+                        let msg = format!(
+                            "bbox must have 4 (south, west, north, east) or 6 (south, west, depth, north, east, height) values, found {}",
+                            values.len()
+                        );
+                        return Err(Self::error(self.input, rparen_offset, &msg));
+                    }
+                }
+            }
+            "tag" => TxnFilter::TxnFilterTxnTags(TxnFilterTxnTags {
+                regex: self.regex()?,
+            }),
+            "comment" => TxnFilter::TxnFilterTxnComments(TxnFilterTxnComments {
+                regex: self.regex()?,
+            }),
+            "meta" => {
+                self.expect(Tok::LParen)?;
+                let (s, re_offset) = self.expect_str()?;
+                let key = new_full_haystack_regex(&s).map_err(|err| {
+                    let msg = format!("invalid regex: {err}");
+                    Self::error(self.input, re_offset, &msg)
+                })?;
+                self.expect(Tok::RParen)?;
+                TxnFilter::TxnFilterTxnMetadata(TxnFilterTxnMetadata {
+                    key,
+                    value: self.regex()?,
+                })
+            }
+            "account" => TxnFilter::TxnFilterPostingAccount(TxnFilterPostingAccount {
+                regex: self.regex()?,
+            }),
+            "posting.comment" => TxnFilter::TxnFilterPostingComment(TxnFilterPostingComment {
+                regex: self.regex()?,
+            }),
+            "posting.tag" => TxnFilter::TxnFilterPostingTags(TxnFilterPostingTags {
+                regex: self.regex()?,
+            }),
+            "commodity" => TxnFilter::TxnFilterPostingCommodity(TxnFilterPostingCommodity {
+                regex: self.regex()?,
+            }),
+            "amount" => {
+                self.expect(Tok::LParen)?;
+                let (s, re_offset) = self.expect_str()?;
+                let regex = new_full_haystack_regex(&s).map_err(|err| {
+                    let msg = format!("invalid regex: {err}");
+                    Self::error(self.input, re_offset, &msg)
+                })?;
+                self.expect(Tok::RParen)?;
+                match self.expect_op(&["=", "<", ">"])? {
+                    "=" => TxnFilter::TxnFilterPostingAmountEqual(TxnFilterPostingAmountEqual {
+                        regex,
+                        amount: self.decimal()?,
+                    }),
+                    "<" => TxnFilter::TxnFilterPostingAmountLess(TxnFilterPostingAmountLess {
+                        regex,
+                        amount: self.decimal()?,
+                    }),
+                    _ => TxnFilter::TxnFilterPostingAmountGreater(TxnFilterPostingAmountGreater {
+                        regex,
+                        amount: self.decimal()?,
+                    }),
+                }
+            }
+            _ => {
+                let msg = format!("unknown filter '{name}'");
+                return Err(Self::error(self.input, offset, &msg));
+            }
+        };
+        Ok(tf)
+    }
+}
+
+pub(crate) fn parse(expr: &str, tz: TimeZone) -> Result<FilterDefinition, tackler::Error> {
+    let mut parser = FilterParser {
+        input: expr,
+        tokens: FilterParser::tokenize(expr)?,
+        pos: 0,
+        tz,
+    };
+    let txn_filter = parser.expr()?;
+    if parser.peek().tok != Tok::End {
+        return Err(parser.err_at(parser.peek(), "'and', 'or' or end of expression"));
+    }
+    Ok(FilterDefinition { txn_filter })
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn re_expr(name: &str, regex: &Regex) -> String {
+    format!("{name} ~ {}", quote(peeled_pattern(regex)))
+}
+
+fn logic_expr(op: &str, filters: &[TxnFilter], tz: &TimeZone) -> String {
+    filters
+        .iter()
+        .map(|tf| operand_expr(tf, tz))
+        .collect::<Vec<_>>()
+        .join(op)
+}
+
+/// Logic operands are in parentheses, so that the structure of filter is kept as it is
+fn operand_expr(tf: &TxnFilter, tz: &TimeZone) -> String {
+    match tf {
+        TxnFilter::TxnFilterAND(TxnFilterAND { txn_filters })
+        | TxnFilter::TxnFilterOR(TxnFilterOR { txn_filters })
+            if txn_filters.len() > 1 =>
+        {
+            format!("({})", to_expr(tf, tz))
+        }
+        _ => to_expr(tf, tz),
+    }
+}
+
+pub(crate) fn to_expr(tf: &TxnFilter, tz: &TimeZone) -> String {
+    match tf {
+        TxnFilter::NullaryTRUE(_) => "true".to_string(),
+        TxnFilter::NullaryFALSE(_) => "false".to_string(),
+
+        // Empty AND selects all txns and empty OR selects none
+        TxnFilter::TxnFilterAND(tf) if tf.txn_filters.is_empty() => "true".to_string(),
+        TxnFilter::TxnFilterOR(tf) if tf.txn_filters.is_empty() => "false".to_string(),
+        TxnFilter::TxnFilterAND(tf) => logic_expr(" and ", &tf.txn_filters, tz),
+        TxnFilter::TxnFilterOR(tf) => logic_expr(" or ", &tf.txn_filters, tz),
+        TxnFilter::TxnFilterNOT(tf) => format!("not {}", operand_expr(&tf.txn_filter, tz)),
+
+        TxnFilter::TxnFilterTxnTSBegin(tf) => {
+            format!("ts >= {}", rfc_3339(&tf.begin.to_zoned(tz.clone())))
+        }
+        TxnFilter::TxnFilterTxnTSEnd(tf) => {
+            format!("ts < {}", rfc_3339(&tf.end.to_zoned(tz.clone())))
+        }
+        TxnFilter::TxnFilterTxnCode(tf) => re_expr("code", &tf.regex),
+        TxnFilter::TxnFilterTxnDescription(tf) => re_expr("desc", &tf.regex),
+        TxnFilter::TxnFilterTxnUUID(tf) => format!("uuid = {}", tf.uuid),
+        TxnFilter::TxnFilterBBoxLatLon(tf) => {
+            format!("bbox({}, {}, {}, {})", tf.south, tf.west, tf.north, tf.east)
+        }
+        TxnFilter::TxnFilterBBoxLatLonAlt(tf) => format!(
+            "bbox({}, {}, {}, {}, {}, {})",
+            tf.south, tf.west, tf.depth, tf.north, tf.east, tf.height
+        ),
+        TxnFilter::TxnFilterTxnTags(tf) => re_expr("tag", &tf.regex),
+        TxnFilter::TxnFilterTxnComments(tf) => re_expr("comment", &tf.regex),
+        TxnFilter::TxnFilterTxnMetadata(tf) => re_expr(
+            &format!("meta({})", quote(peeled_pattern(&tf.key))),
+            &tf.value,
+        ),
+
+        TxnFilter::TxnFilterPostingAccount(tf) => re_expr("account", &tf.regex),
+        TxnFilter::TxnFilterPostingComment(tf) => re_expr("posting.comment", &tf.regex),
+        TxnFilter::TxnFilterPostingAmountEqual(tf) => format!(
+            "amount({}) = {}",
+            quote(peeled_pattern(&tf.regex)),
+            tf.amount
+        ),
+        TxnFilter::TxnFilterPostingAmountLess(tf) => format!(
+            "amount({}) < {}",
+            quote(peeled_pattern(&tf.regex)),
+            tf.amount
+        ),
+        TxnFilter::TxnFilterPostingAmountGreater(tf) => format!(
+            "amount({}) > {}",
+            quote(peeled_pattern(&tf.regex)),
+            tf.amount
+        ),
+        TxnFilter::TxnFilterPostingCommodity(tf) => re_expr("commodity", &tf.regex),
+        TxnFilter::TxnFilterPostingTags(tf) => re_expr("posting.tag", &tf.regex),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(expr: &str) -> String {
+        let tf = parse(expr, TimeZone::UTC).unwrap(/*:test:*/);
+        serde_json::to_string(&tf).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn expr_example() {
+        let tf_json =
+            json(r#"ts >= 2024-01-01 and tag = "trip" and not account ~ "^Expenses:Tax.*""#);
+        assert_eq!(
+            tf_json,
+            r#"{"txnFilter":{"TxnFilterAND":{"txnFilters":[{"TxnFilterTxnTSBegin":{"begin":"2024-01-01T00:00:00Z"}},{"TxnFilterTxnTags":{"regex":"trip"}},{"TxnFilterNOT":{"txnFilter":{"TxnFilterPostingAccount":{"regex":"^Expenses:Tax.*"}}}}]}}}"#
+        );
+    }
+
+    #[test]
+    fn expr_precedence() {
+        assert_eq!(
+            json(r#"true or false and not true"#),
+            r#"{"txnFilter":{"TxnFilterOR":{"txnFilters":[{"NullaryTRUE":{}},{"TxnFilterAND":{"txnFilters":[{"NullaryFALSE":{}},{"TxnFilterNOT":{"txnFilter":{"NullaryTRUE":{}}}}]}}]}}}"#
+        );
+        assert_eq!(
+            json(r#"(true or false) and true"#),
+            r#"{"txnFilter":{"TxnFilterAND":{"txnFilters":[{"TxnFilterOR":{"txnFilters":[{"NullaryTRUE":{}},{"NullaryFALSE":{}}]}},{"NullaryTRUE":{}}]}}}"#
+        );
+    }
+
+    #[test]
+    fn expr_timestamps() {
+        let tz = TimeZone::get("Europe/Helsinki").unwrap(/*:test:*/);
+        let tf = parse("ts >= 2024-06-01 and ts < 2024-06-02T12:00:00", tz).unwrap(/*:test:*/);
+        assert_eq!(
+            serde_json::to_string(&tf).unwrap(/*:test:*/),
+            r#"{"txnFilter":{"TxnFilterAND":{"txnFilters":[{"TxnFilterTxnTSBegin":{"begin":"2024-05-31T21:00:00Z"}},{"TxnFilterTxnTSEnd":{"end":"2024-06-02T09:00:00Z"}}]}}}"#
+        );
+        assert_eq!(
+            json("ts < 2024-06-02T12:00:00.123+03:00"),
+            r#"{"txnFilter":{"TxnFilterTxnTSEnd":{"end":"2024-06-02T09:00:00.123Z"}}}"#
+        );
+    }
+
+    #[test]
+    fn expr_strings() {
+        assert_eq!(
+            json(r#"desc ~ "a\"b\\.\d+""#),
+            r#"{"txnFilter":{"TxnFilterTxnDescription":{"regex":"a\"b\\.\\d+"}}}"#
+        );
+        assert_eq!(
+            json(r#"code = "(#1)""#),
+            r#"{"txnFilter":{"TxnFilterTxnCode":{"regex":"\\(\\#1\\)"}}}"#
+        );
+    }
+
+    #[test]
+    fn expr_round_trip() {
+        let exprs = [
+            "true",
+            "false",
+            "not (true or false)",
+            "not not true",
+            "true and (false and true)",
+            "(true and false) or (false and true)",
+            "ts >= 2024-01-01T00:00:00+00:00 and ts < 2025-01-01T10:11:12.123456789+00:00",
+            r##"code ~ "#.*" or desc ~ "a\"b\\d" or comment ~ "c""##,
+            "uuid = 506a2d55-2375-4d51-af3a-cf5021f04de9",
+            "bbox(59.85, 24.0, 60.8, 27.5)",
+            "bbox(-1, -2, -3, 4, 5, 6.0)",
+            r#"tag ~ "trip" and not commodity ~ "EUR|USD""#,
+            r#"account ~ "^Expenses:.*" and posting.comment ~ ".*x""#,
+            r#"posting.tag ~ "vat:.*" or not posting.tag ~ "split""#,
+            r#"meta("invoice|receipt") ~ "2024-.*" and meta("a\"b") ~ "c""#,
+            r#"amount("e:.*") = 1.0 or amount("e:.*") < -2 or amount("e:.*") > 3.33"#,
+        ];
+        for expr in exprs {
+            let tf = parse(expr, TimeZone::UTC).unwrap(/*:test:*/);
+            assert_eq!(to_expr(&tf.txn_filter, &TimeZone::UTC), expr);
+        }
+    }
+
+    #[test]
+    fn expr_round_trip_empty_logic() {
+        let empty_and = TxnFilter::TxnFilterAND(TxnFilterAND {
+            txn_filters: vec![],
+        });
+        let empty_or = TxnFilter::TxnFilterOR(TxnFilterOR {
+            txn_filters: vec![],
+        });
+        let not_and = TxnFilter::TxnFilterNOT(TxnFilterNOT {
+            txn_filter: Box::new(empty_and.clone()),
+        });
+        let nested = TxnFilter::TxnFilterOR(TxnFilterOR {
+            txn_filters: vec![empty_and.clone(), empty_or.clone()],
+        });
+        let tests = [
+            (empty_and, "true", r#"{"txnFilter":{"NullaryTRUE":{}}}"#),
+            (empty_or, "false", r#"{"txnFilter":{"NullaryFALSE":{}}}"#),
+            (
+                not_and,
+                "not true",
+                r#"{"txnFilter":{"TxnFilterNOT":{"txnFilter":{"NullaryTRUE":{}}}}}"#,
+            ),
+            (
+                nested,
+                "true or false",
+                r#"{"txnFilter":{"TxnFilterOR":{"txnFilters":[{"NullaryTRUE":{}},{"NullaryFALSE":{}}]}}}"#,
+            ),
+        ];
+        for (tf, expr, tf_json) in tests {
+            assert_eq!(to_expr(&tf, &TimeZone::UTC), expr);
+            assert_eq!(json(expr), tf_json);
+        }
+    }
+
+    #[test]
+    fn expr_errors() {
+        let errors = [
+            ("", "column 1: expected filter, found end of expression"),
+            (
+                "true and",
+                "column 9: expected filter, found end of expression",
+            ),
+            (
+                "true false",
+                "column 6: expected 'and', 'or' or end of expression",
+            ),
+            ("(true", "column 6: expected ')', found end of expression"),
+            ("foo = 1", "column 1: unknown filter 'foo'"),
+            (
+                "tag trip",
+                "column 5: expected operator ~ or =, found 'trip'",
+            ),
+            ("tag ~ trip", "column 7: expected string, found 'trip'"),
+            (r#"tag ~ "trip"#, "column 7: unterminated string"),
+            (r#"tag ~ "(""#, "column 7: invalid regex"),
+            ("ts > 2024-01-01", "column 4: expected operator >= or <"),
+            (
+                "ts >= 2024-13-01",
+                "column 7: invalid timestamp '2024-13-01'",
+            ),
+            ("uuid = 1234", "column 8: invalid uuid '1234'"),
+            ("bbox(1, 2, 3)", "column 13: bbox must have 4"),
+            (r#"amount("a") > x"#, "column 15: invalid number 'x'"),
+        ];
+        for (expr, msg) in errors {
+            let err = parse(expr, TimeZone::UTC).err().unwrap(/*:test:*/).to_string();
+            assert!(err.contains(msg), "expr: [{expr}], error: [{err}]");
+        }
+    }
+
+    #[test]
+    fn expr_error_position() {
+        let err = parse(r#"tag = "ö" and ts >= x"#, TimeZone::UTC)
+            .err()
+            .unwrap(/*:test:*/)
+            .to_string();
+        assert_eq!(
+            err,
+            "Txn Filter expression error at column 21: invalid timestamp 'x': failed to parse year in date \"x\": expected four digit year (or leading sign for six digit year), but found end of input\n   tag = \"ö\" and ts >= x\n                       ^"
+        );
+    }
+}
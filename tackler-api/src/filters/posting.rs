@@ -11,6 +11,7 @@ pub use posting_amount_greater::TxnFilterPostingAmountGreater;
 pub use posting_amount_less::TxnFilterPostingAmountLess;
 pub use posting_comment::TxnFilterPostingComment;
 pub use posting_commodity::TxnFilterPostingCommodity;
+pub use posting_tags::TxnFilterPostingTags;
 
 mod posting_account;
 mod posting_amount_equal;
@@ -18,3 +19,4 @@ mod posting_amount_greater;
 mod posting_amount_less;
 mod posting_comment;
 mod posting_commodity;
+mod posting_tags;
@@ -0,0 +1,145 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use jiff::tz::TimeZone;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use tackler_rs::regex::peeled_pattern;
+use tackler_rs::regex::serde::full_haystack_matcher;
+
+use crate::filters::IndentDisplay;
+
+/// Txn Posting Tags filter
+///
+/// Select transaction, if any tag of its postings matches `regex`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TxnFilterPostingTags {
+    #[doc(hidden)]
+    #[serde(with = "full_haystack_matcher")]
+    pub regex: Regex,
+}
+
+impl IndentDisplay for TxnFilterPostingTags {
+    fn i_fmt(&self, indent: &str, _tz: TimeZone, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{indent}Posting Tags: \"{}\"",
+            peeled_pattern(&self.regex)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{
+        FilterDefZoned, FilterDefinition, NullaryTRUE, TxnFilter, logic::TxnFilterAND,
+    };
+    use indoc::indoc;
+    use jiff::tz;
+    use tackler_rs::IndocUtils;
+    use tackler_rs::regex::new_full_haystack_regex;
+
+    #[test]
+    // desc: PostingTags, full haystack match
+    fn posting_tags_full_haystack() {
+        let filter_json_str = r#"{"txnFilter":{"TxnFilterPostingTags":{"regex":"o.a"}}}"#;
+
+        let tf_res = serde_json::from_str::<FilterDefinition>(filter_json_str);
+        assert!(tf_res.is_ok());
+        let tf = tf_res.unwrap(/*:test:*/);
+
+        match &tf.txn_filter {
+            TxnFilter::TxnFilterPostingTags(f) => {
+                assert!(!f.regex.is_match("foobar"));
+                assert!(!f.regex.is_match("obar"));
+                assert!(!f.regex.is_match("ooba"));
+
+                assert!(f.regex.is_match("oba"));
+            }
+            _ => panic!(/*:test:*/),
+        }
+    }
+
+    #[test]
+    // desc: PostingTags, JSON
+    fn posting_tags_json() {
+        let filter_json_str = r#"{"txnFilter":{"TxnFilterPostingTags":{"regex":"vat:(.*)"}}}"#;
+
+        let filter_text_str = indoc! {
+        r#"|Filter
+           |  Posting Tags: "vat:(.*)"
+           |"#}
+        .strip_margin();
+
+        let tf_res = serde_json::from_str::<FilterDefinition>(filter_json_str);
+        assert!(tf_res.is_ok());
+        let tf = tf_res.unwrap(/*:test:*/);
+
+        match tf.txn_filter {
+            TxnFilter::TxnFilterPostingTags(_) => (),
+            _ => panic!(/*:test:*/),
+        }
+
+        assert_eq!(
+            format!(
+                "{}",
+                FilterDefZoned {
+                    filt_def: &tf,
+                    tz: tz::TimeZone::UTC
+                }
+            ),
+            filter_text_str
+        );
+        assert_eq!(
+            serde_json::to_string(&tf).unwrap(/*:test:*/),
+            filter_json_str
+        );
+    }
+
+    #[test]
+    // desc: PostingTags, Text
+    fn posting_tags_text() {
+        let filter_text_str = indoc! {
+        r#"|Filter
+           |  AND
+           |    Posting Tags: "vat:(.*)"
+           |    AND
+           |      Posting Tags: "receipt"
+           |      All pass
+           |"#}
+        .strip_margin();
+
+        let tf = FilterDefinition {
+            txn_filter: TxnFilter::TxnFilterAND(TxnFilterAND {
+                txn_filters: vec![
+                    TxnFilter::TxnFilterPostingTags(TxnFilterPostingTags {
+                        regex: new_full_haystack_regex("vat:(.*)").unwrap(/*:test:*/),
+                    }),
+                    TxnFilter::TxnFilterAND(TxnFilterAND {
+                        txn_filters: vec![
+                            TxnFilter::TxnFilterPostingTags(TxnFilterPostingTags {
+                                regex: new_full_haystack_regex("receipt").unwrap(/*:test:*/),
+                            }),
+                            TxnFilter::NullaryTRUE(NullaryTRUE {}),
+                        ],
+                    }),
+                ],
+            }),
+        };
+
+        assert_eq!(
+            format!(
+                "{}",
+                FilterDefZoned {
+                    filt_def: &tf,
+                    tz: tz::TimeZone::UTC
+                }
+            ),
+            filter_text_str
+        );
+    }
+}
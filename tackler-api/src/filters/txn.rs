@@ -10,6 +10,7 @@ pub use txn_bbox_lat_lon_alt::TxnFilterBBoxLatLonAlt;
 pub use txn_code::TxnFilterTxnCode;
 pub use txn_comments::TxnFilterTxnComments;
 pub use txn_description::TxnFilterTxnDescription;
+pub use txn_metadata::TxnFilterTxnMetadata;
 pub use txn_tags::TxnFilterTxnTags;
 pub use txn_ts_begin::TxnFilterTxnTSBegin;
 pub use txn_ts_end::TxnFilterTxnTSEnd;
@@ -20,6 +21,7 @@ mod txn_bbox_lat_lon_alt;
 mod txn_code;
 mod txn_comments;
 mod txn_description;
+mod txn_metadata;
 mod txn_tags;
 mod txn_ts_begin;
 mod txn_ts_end;
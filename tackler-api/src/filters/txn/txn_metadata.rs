@@ -0,0 +1,105 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use jiff::tz::TimeZone;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use tackler_rs::regex::peeled_pattern;
+use tackler_rs::regex::serde::full_haystack_matcher;
+
+use crate::filters::IndentDisplay;
+
+/// Txn Metadata filter
+///
+/// Select transaction, if any of its key-value metadata (`# key: value`)
+/// matches both `key` and `value` regex
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TxnFilterTxnMetadata {
+    #[doc(hidden)]
+    #[serde(with = "full_haystack_matcher")]
+    pub key: Regex,
+    #[doc(hidden)]
+    #[serde(with = "full_haystack_matcher")]
+    pub value: Regex,
+}
+
+impl IndentDisplay for TxnFilterTxnMetadata {
+    fn i_fmt(&self, indent: &str, _tz: TimeZone, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let my_indent = format!("{indent}  ");
+        writeln!(f, "{indent}Txn Metadata")?;
+        writeln!(f, "{my_indent}key: \"{}\"", peeled_pattern(&self.key))?;
+        writeln!(f, "{my_indent}value: \"{}\"", peeled_pattern(&self.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filters::{FilterDefZoned, FilterDefinition, TxnFilter};
+    use indoc::indoc;
+    use jiff::tz;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    // desc: TxnMetadata, full haystack match
+    fn txn_metadata_full_haystack() {
+        let filter_json_str =
+            r#"{"txnFilter":{"TxnFilterTxnMetadata":{"key":"inv","value":"o.a"}}}"#;
+
+        let tf_res = serde_json::from_str::<FilterDefinition>(filter_json_str);
+        assert!(tf_res.is_ok());
+        let tf = tf_res.unwrap(/*:test:*/);
+
+        match &tf.txn_filter {
+            TxnFilter::TxnFilterTxnMetadata(f) => {
+                assert!(!f.key.is_match("invoice"));
+                assert!(f.key.is_match("inv"));
+
+                assert!(!f.value.is_match("foobar"));
+                assert!(f.value.is_match("oba"));
+            }
+            _ => panic!(/*:test:*/),
+        }
+    }
+
+    #[test]
+    // desc: TxnMetadata, JSON and Text
+    fn txn_metadata_json() {
+        let filter_json_str =
+            r#"{"txnFilter":{"TxnFilterTxnMetadata":{"key":"invoice|receipt","value":"2024-.*"}}}"#;
+
+        let filter_text_str = indoc! {
+        r#"|Filter
+           |  Txn Metadata
+           |    key: "invoice|receipt"
+           |    value: "2024-.*"
+           |"#}
+        .strip_margin();
+
+        let tf_res = serde_json::from_str::<FilterDefinition>(filter_json_str);
+        assert!(tf_res.is_ok());
+        let tf = tf_res.unwrap(/*:test:*/);
+
+        match tf.txn_filter {
+            TxnFilter::TxnFilterTxnMetadata(_) => (),
+            _ => panic!(/*:test:*/),
+        }
+
+        assert_eq!(
+            format!(
+                "{}",
+                FilterDefZoned {
+                    filt_def: &tf,
+                    tz: tz::TimeZone::UTC
+                }
+            ),
+            filter_text_str
+        );
+        assert_eq!(
+            serde_json::to_string(&tf).unwrap(/*:test:*/),
+            filter_json_str
+        );
+    }
+}
@@ -7,10 +7,13 @@
 //!
 pub mod items;
 
+use crate::tackler;
+use items::Json;
 use items::MetadataItem;
 use items::MetadataItems;
 use items::Text;
 use jiff::tz::TimeZone;
+use serde_json::{Value, json};
 
 /// Metadata of Inputs, Txn Set, Reporting parameters, etc.
 ///
@@ -72,6 +75,19 @@ impl Metadata {
             .collect::<Vec<String>>();
         ts.join("\n")
     }
+
+    /// Get JSON representation of Metadata items
+    ///
+    /// Items are returned in the same order as they are with textual representation.
+    ///
+    /// # Errors
+    /// Returns `Err` if some of the items can't be serialized
+    pub fn json(&self, tz: TimeZone) -> Result<Vec<Value>, tackler::Error> {
+        self.items
+            .iter()
+            .map(|item| item.json(tz.clone()))
+            .collect()
+    }
 }
 
 /// Generic checksum value
@@ -82,3 +98,12 @@ pub struct Checksum {
     /// hexadecimal hash value
     pub value: String,
 }
+
+impl Checksum {
+    fn json(&self) -> Value {
+        json!({
+            "algorithm": self.algorithm,
+            "value": self.value,
+        })
+    }
+}
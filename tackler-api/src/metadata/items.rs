@@ -8,9 +8,11 @@
 
 use crate::filters::{FilterDefZoned, FilterDefinition};
 use crate::metadata::Checksum;
+use crate::tackler;
 use crate::txn_ts;
 use jiff::Zoned;
 use jiff::tz::TimeZone;
+use serde_json::{Value, json};
 
 #[doc(hidden)]
 pub type MetadataItems = Vec<MetadataItem>;
@@ -22,6 +24,18 @@ pub trait Text: std::fmt::Debug {
     fn text(&self, tz: TimeZone) -> Vec<String>;
 }
 
+#[doc(hidden)]
+pub trait Json: std::fmt::Debug {
+    /// Get metadata item as JSON value
+    ///
+    /// The value is an object with a single key (name of the item),
+    /// and the item content is the value of that key.
+    ///
+    /// # Errors
+    /// Returns `Err` if the item can't be serialized
+    fn json(&self, tz: TimeZone) -> Result<Value, tackler::Error>;
+}
+
 #[doc(hidden)]
 #[derive(Debug, Clone)]
 pub enum MetadataItem {
@@ -50,6 +64,17 @@ impl Text for MetadataItem {
     }
 }
 
+impl Json for MetadataItem {
+    fn json(&self, tz: TimeZone) -> Result<Value, tackler::Error> {
+        match self {
+            Self::GitInputReference(gif) => gif.json(tz),
+            Self::TxnSetChecksum(tscs) => tscs.json(tz),
+            Self::AccountSelectorChecksum(asc) => asc.json(tz),
+            Self::TxnFilterDescription(tfd) => tfd.json(tz),
+        }
+    }
+}
+
 /// Txn Set Checksum metadata item
 #[derive(Debug, Clone)]
 pub struct TxnSetChecksum {
@@ -69,6 +94,16 @@ impl Text for TxnSetChecksum {
         ]
     }
 }
+impl Json for TxnSetChecksum {
+    fn json(&self, _tz: TimeZone) -> Result<Value, tackler::Error> {
+        Ok(json!({
+            "TxnSetChecksum": {
+                "size": self.size,
+                "hash": self.hash.json(),
+            }
+        }))
+    }
+}
 
 /// Account Selector Checksum item
 #[derive(Debug, Clone)]
@@ -86,6 +121,15 @@ impl Text for AccountSelectorChecksum {
         ]
     }
 }
+impl Json for AccountSelectorChecksum {
+    fn json(&self, _tz: TimeZone) -> Result<Value, tackler::Error> {
+        Ok(json!({
+            "AccountSelectorChecksum": {
+                "hash": self.hash.json(),
+            }
+        }))
+    }
+}
 
 /// Report timezone item
 #[derive(Debug, Clone)]
@@ -102,6 +146,15 @@ impl Text for ReportTimezone {
         ]
     }
 }
+impl Json for ReportTimezone {
+    fn json(&self, _tz: TimeZone) -> Result<Value, tackler::Error> {
+        Ok(json!({
+            "ReportTimezone": {
+                "timezone": self.timezone,
+            }
+        }))
+    }
+}
 /// Metadata information about active Txn Filters
 ///
 #[derive(Debug, Clone)]
@@ -135,6 +188,15 @@ impl Text for TxnFilterDescription {
         .collect::<Vec<String>>()
     }
 }
+impl Json for TxnFilterDescription {
+    fn json(&self, _tz: TimeZone) -> Result<Value, tackler::Error> {
+        // Filter definition is serialized with its own (API) serde schema,
+        // so it could be used as it is with the `--api-filter-def` option.
+        Ok(json!({
+            "TxnFilterDescription": serde_json::to_value(&self.txn_filter_def)?,
+        }))
+    }
+}
 
 /// Metadata information about Git Txn input
 ///
@@ -171,6 +233,19 @@ impl Text for GitInputReference {
         ]
     }
 }
+impl Json for GitInputReference {
+    fn json(&self, _tz: TimeZone) -> Result<Value, tackler::Error> {
+        Ok(json!({
+            "GitInputReference": {
+                "commit": self.commit,
+                "reference": self.reference,
+                "dir": self.dir,
+                "suffix": self.suffix,
+                "message": self.message.trim(),
+            }
+        }))
+    }
+}
 
 /// Metadata item for one commodity conversion
 #[derive(Debug, Clone)]
@@ -205,6 +280,16 @@ impl Text for PriceRecord {
         ]
     }
 }
+impl PriceRecord {
+    fn json_value(&self, tz: TimeZone) -> Value {
+        json!({
+            "ts": self.ts.as_ref().map(|ts| txn_ts::as_tz_full(ts, tz)),
+            "source": self.source,
+            "rate": self.rate,
+            "target": self.target,
+        })
+    }
+}
 /// Metadata information of used commodity conversions
 #[derive(Debug, Clone)]
 pub struct PriceRecords {
@@ -231,3 +316,176 @@ impl Text for PriceRecords {
         txt
     }
 }
+impl Json for PriceRecords {
+    fn json(&self, tz: TimeZone) -> Result<Value, tackler::Error> {
+        Ok(json!({
+            "PriceRecords": {
+                "rates": self
+                    .rates
+                    .iter()
+                    .map(|pr| pr.json_value(tz.clone()))
+                    .collect::<Vec<Value>>(),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    fn checksum() -> Checksum {
+        Checksum {
+            algorithm: "SHA-256".to_string(),
+            value: "ef35a6cc".to_string(),
+        }
+    }
+
+    #[test]
+    fn txn_set_checksum_json() {
+        let tsc = TxnSetChecksum {
+            size: 3,
+            hash: checksum(),
+        };
+        assert_eq!(
+            tsc.json(TimeZone::UTC).unwrap(/*:test:*/),
+            json!({
+                "TxnSetChecksum": {
+                    "size": 3,
+                    "hash": {
+                        "algorithm": "SHA-256",
+                        "value": "ef35a6cc",
+                    },
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn account_selector_checksum_json() {
+        let asc = AccountSelectorChecksum { hash: checksum() };
+        assert_eq!(
+            asc.json(TimeZone::UTC).unwrap(/*:test:*/),
+            json!({
+                "AccountSelectorChecksum": {
+                    "hash": {
+                        "algorithm": "SHA-256",
+                        "value": "ef35a6cc",
+                    },
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn report_timezone_json() {
+        let rtz = ReportTimezone {
+            timezone: "Europe/Helsinki".to_string(),
+        };
+        assert_eq!(
+            rtz.json(TimeZone::UTC).unwrap(/*:test:*/),
+            json!({
+                "ReportTimezone": {
+                    "timezone": "Europe/Helsinki",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn txn_filter_description_json() {
+        let filt_str = r#"{"txnFilter":{"TxnFilterTxnDescription":{"regex":"^abc.*"}}}"#;
+        let tfd = TxnFilterDescription::from(
+            FilterDefinition::from_json_str(filt_str).unwrap(/*:test:*/),
+        );
+        let filt_json: Value = serde_json::from_str(filt_str).unwrap(/*:test:*/);
+
+        let json = tfd.json(TimeZone::UTC).unwrap(/*:test:*/);
+        assert_eq!(json, json!({ "TxnFilterDescription": filt_json }));
+
+        // filter definition could be used as it is with `--api-filter-def`
+        let filt_def =
+            FilterDefinition::from_json_str(json["TxnFilterDescription"].to_string().as_str());
+        assert!(filt_def.is_ok());
+    }
+
+    #[test]
+    fn git_input_reference_json() {
+        let gir = GitInputReference {
+            commit: "4aa4e9797501c1aefc92f32dff30ab462dae5545".to_string(),
+            reference: None,
+            dir: "txns/2016".to_string(),
+            suffix: "txn".to_string(),
+            message: "txns-1E1: 2016/12\n".to_string(),
+        };
+        assert_eq!(
+            gir.json(TimeZone::UTC).unwrap(/*:test:*/),
+            json!({
+                "GitInputReference": {
+                    "commit": "4aa4e9797501c1aefc92f32dff30ab462dae5545",
+                    "reference": null,
+                    "dir": "txns/2016",
+                    "suffix": "txn",
+                    "message": "txns-1E1: 2016/12",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn price_records_json() {
+        let ts = date(2024, 1, 2)
+            .at(3, 4, 5, 0)
+            .to_zoned(TimeZone::UTC)
+            .unwrap(/*:test:*/);
+        let prs = PriceRecords {
+            rates: vec![
+                PriceRecord {
+                    ts: Some(ts),
+                    source: "EUR".to_string(),
+                    rate: Some("1.25".to_string()),
+                    target: "USD".to_string(),
+                },
+                PriceRecord {
+                    ts: None,
+                    source: "SEK".to_string(),
+                    rate: None,
+                    target: "USD".to_string(),
+                },
+            ],
+        };
+        assert_eq!(
+            prs.json(TimeZone::UTC).unwrap(/*:test:*/),
+            json!({
+                "PriceRecords": {
+                    "rates": [
+                        {
+                            "ts": "2024-01-02 03:04:05",
+                            "source": "EUR",
+                            "rate": "1.25",
+                            "target": "USD",
+                        },
+                        {
+                            "ts": null,
+                            "source": "SEK",
+                            "rate": null,
+                            "target": "USD",
+                        },
+                    ],
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn metadata_item_json() {
+        let mdi =
+            MetadataItem::AccountSelectorChecksum(AccountSelectorChecksum { hash: checksum() });
+        let asc = AccountSelectorChecksum { hash: checksum() };
+        assert_eq!(
+            mdi.json(TimeZone::UTC).unwrap(/*:test:*/),
+            asc.json(TimeZone::UTC).unwrap(/*:test:*/)
+        );
+    }
+}
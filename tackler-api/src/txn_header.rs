@@ -21,6 +21,9 @@ pub type Tag = String;
 /// Collection of Txn comments
 pub type Comments = Vec<String>;
 
+/// Collection of Txn key-value metadata, in the order of journal
+pub type Metadata = Vec<(String, String)>;
+
 use crate::location::GeoPoint;
 
 /// Transaction Header Structure
@@ -39,6 +42,8 @@ pub struct TxnHeader {
     pub location: Option<GeoPoint>,
     /// Txn tags, if any
     pub tags: Option<Tags>,
+    /// Txn key-value metadata (`# key: value`), if any
+    pub meta: Option<Metadata>,
     /// Txn comments, if any
     pub comments: Option<Comments>,
 }
@@ -137,7 +142,7 @@ impl TxnHeader {
         tz: TimeZone,
     ) -> String {
         format!(
-            "{}{}{}\n{}{}{}{}",
+            "{}{}{}\n{}{}{}{}{}",
             // txn header line: ts, code, desc
             ts_formatter(&self.timestamp, tz),
             self.code
@@ -158,6 +163,13 @@ impl TxnHeader {
                 indent,
                 Self::t_to_s(tags)
             )),
+            self.meta.as_ref().map_or_else(String::new, |meta| {
+                meta.iter()
+                    .fold(String::with_capacity(128), |mut output, (key, value)| {
+                        let _ = writeln!(output, "{indent}# {key}: {value}");
+                        output
+                    })
+            }),
             // txn comments
             self.comments.as_ref().map_or_else(String::new, |comments| {
                 comments
@@ -231,6 +243,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -247,6 +260,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -263,6 +277,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -279,6 +294,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -295,6 +311,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc! {
@@ -311,6 +328,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -327,6 +345,7 @@ mod tests {
                     uuid: Some(uuid),
                     location: None,
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 formatdoc!(
@@ -344,6 +363,7 @@ mod tests {
                     uuid: None,
                     location: Some(geo.clone()),
                     tags: None,
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -361,6 +381,7 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: Some(txn_tags.clone()),
+                    meta: None,
                     comments: None,
                 },
                 indoc!(
@@ -378,6 +399,29 @@ mod tests {
                     uuid: None,
                     location: None,
                     tags: None,
+                    meta: Some(vec![
+                        ("invoice".to_string(), "2024-117".to_string()),
+                        ("receipt".to_string(), "receipts/2024/117.pdf".to_string()),
+                    ]),
+                    comments: None,
+                },
+                indoc!(
+                    "|2023-02-04T14:03:05.047974+02:00 'desc
+                     |   # invoice: 2024-117
+                     |   # receipt: receipts/2024/117.pdf
+                     |"
+                )
+                .strip_margin(),
+            ),
+            (
+                TxnHeader {
+                    timestamp: ts.clone(),
+                    code: None,
+                    description: Some("desc".to_string()),
+                    uuid: None,
+                    location: None,
+                    tags: None,
+                    meta: None,
                     comments: Some(comments.clone()),
                 },
                 indoc!(
@@ -397,6 +441,7 @@ mod tests {
                     uuid: Some(uuid),
                     location: Some(geo),
                     tags: Some(txn_tags),
+                    meta: None,
                     comments: Some(comments),
                 },
                 formatdoc!(
@@ -9,14 +9,25 @@ use std::path::PathBuf;
 use tackler_api::txn_ts;
 use tackler_core::config::PriceLookupType;
 use tackler_core::config::overlaps::{
-    AuditOverlap, OverlapConfig, PriceOverlap, ReportOverlap, StrictOverlap, TargetOverlap,
+    AuditOverlap, OverlapConfig, PriceOverlap, RecoveryOverlap, ReportOverlap, StrictOverlap,
+    TargetOverlap,
 };
+use tackler_core::convert::ConvertFormat;
+use tackler_core::import::ImportFormat;
 use tackler_core::kernel::Settings;
 use tackler_core::kernel::settings::{FileInput, FsInput, GitInput, InputSettings};
 use tackler_core::parser::GitInputSelector;
 use tackler_core::{config, tackler};
 
 pub(crate) const PRICE_BEFORE: &str = "price.before";
+
+/// Valid values of report formats (global and per report)
+const REPORT_FORMATS: [&str; 4] = [
+    config::ReportFormat::TXT,
+    config::ReportFormat::JSON,
+    config::ReportFormat::CSV,
+    config::ReportFormat::TSV,
+];
 //
 // Default subcommand setup:
 // https://github.com/clap-rs/clap/issues/975
@@ -135,10 +146,88 @@ pub(crate) enum Commands {
     New { name: String },
     /// Initialize existing bookkeeping setup
     Init {},
+    /// Format txn files into canonical layout
+    Fmt(FmtArgs),
+    /// Import bank statement as txns
+    Import(ImportArgs),
+    /// Convert ledger, hledger or beancount journal into txn shard tree
+    Convert(ConvertArgs),
     /// This is the default action: run specified reports and exports
     Report(DefaultModeArgs),
 }
 
+#[derive(Debug, Clone, clap::Args)]
+pub(crate) struct ImportArgs {
+    /// Config file, its journal is used to skip already imported entries
+    #[arg(long = "config", value_name = "path_to_config-file")]
+    pub(crate) conf_path: PathBuf,
+
+    /// Import profile (TOML)
+    #[arg(long = "profile", value_name = "path_to_profile")]
+    pub(crate) profile: PathBuf,
+
+    /// Output file for imported txns, default is stdout
+    #[arg(long = "output", value_name = "path_to_txn-file")]
+    pub(crate) output: Option<PathBuf>,
+
+    /// Format of bank statement
+    #[arg(long = "format", value_name = "format", default_value = ImportFormat::CSV,
+        value_parser([
+            PossibleValue::new(ImportFormat::CSV),
+            PossibleValue::new(ImportFormat::CAMT053),
+            PossibleValue::new(ImportFormat::MT940),
+        ])
+    )]
+    pub(crate) format: String,
+
+    /// Bank statement (CSV, camt.053 or MT940)
+    #[arg(value_name = "statement")]
+    pub(crate) statement: PathBuf,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub(crate) struct ConvertArgs {
+    /// Format of the journals
+    #[arg(long = "format", value_name = "format",
+        value_parser([
+            PossibleValue::new(ConvertFormat::LEDGER),
+            PossibleValue::new(ConvertFormat::HLEDGER),
+            PossibleValue::new(ConvertFormat::BEANCOUNT),
+        ])
+    )]
+    pub(crate) format: String,
+
+    /// Output directory, it must not exist
+    ///
+    /// Txns are written into shard tree ('txns/YYYY/MM/DD'), and
+    /// accounts, commodities and tags into 'accounts.toml',
+    /// 'commodities.toml' and 'tags.toml'
+    #[arg(long = "output", value_name = "path_to_dir")]
+    pub(crate) output: PathBuf,
+
+    /// Journal files, included files must be given separately
+    #[arg(value_name = "journal", required = true)]
+    pub(crate) paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub(crate) struct FmtArgs {
+    /// Optional config file, e.g. for timezone and strict mode settings
+    #[arg(long = "config", value_name = "path_to_config-file")]
+    pub(crate) conf_path: Option<PathBuf>,
+
+    /// Check mode
+    ///
+    /// Don't write files, but report files which are not formatted,
+    /// and exit with non-zero status if there is any
+    #[arg(long = "check", default_value_t = false)]
+    pub(crate) check: bool,
+
+    /// Txn files or directories (with `.txn` files) to format
+    #[arg(value_name = "path", required = true)]
+    pub(crate) paths: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, clap::Args)]
 pub(crate) struct DefaultModeArgs {
     #[arg(long = "config", value_name = "path_to_config-file")]
@@ -146,16 +235,28 @@ pub(crate) struct DefaultModeArgs {
 
     /// Strict txn data mode
     ///
-    /// Turn on strict validation of transactions (accounts, commodities and tags).
+    /// Turn on strict validation of transactions (accounts, commodities, tags and metadata).
     #[arg(long = "strict.mode", value_name = "true|false")]
     pub(crate) strict_mode: Option<bool>,
 
+    /// Error recovery mode
+    ///
+    /// Continue parsing at the next txn header after an error, and report all parse errors
+    #[arg(long = "recovery.mode", value_name = "true|false")]
+    pub(crate) recovery_mode: Option<bool>,
+
     /// Txn set audit mode
     ///
     /// Produce checksums for transaction data and account selectors
     #[arg(long = "audit.mode", value_name = "true|false")]
     pub(crate) audit_mode: Option<bool>,
 
+    /// Attachment check
+    ///
+    /// Verify that attachments of txns exist in the same commit (git input)
+    #[arg(long = "audit.attachments", value_name = "true|false")]
+    pub(crate) audit_attachments: Option<bool>,
+
     /// Path to output directory
     #[arg(
         long = "output.dir",
@@ -271,10 +372,69 @@ pub(crate) struct DefaultModeArgs {
             PossibleValue::new("register"),
             PossibleValue::new("balance"),
             PossibleValue::new("balance-group"),
+            PossibleValue::new("pnl"),
+            PossibleValue::new("income-statement"),
+            PossibleValue::new("balance-sheet"),
         ])
     )]
     pub(crate) reports: Option<Vec<String>>,
 
+    /// List of report output formats
+    ///
+    /// The list is space separated, and it is used for all reports
+    #[arg(long = "report.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_formats: Option<Vec<String>>,
+
+    /// List of output formats for Balance report
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.balance.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_balance_formats: Option<Vec<String>>,
+
+    /// List of output formats for Balance Group report
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.balance-group.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_balance_group_formats: Option<Vec<String>>,
+
+    /// List of output formats for Register report
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.register.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_register_formats: Option<Vec<String>>,
+
+    /// List of output formats for PnL report
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.pnl.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_pnl_formats: Option<Vec<String>>,
+
+    /// List of output formats for Income Statement
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.income-statement.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_income_statement_formats: Option<Vec<String>>,
+
+    /// List of output formats for Balance Sheet
+    ///
+    /// This overrides '--report.formats' for this report
+    #[arg(long = "report.balance-sheet.formats", value_name = "format", num_args(1..),
+        value_parser(REPORT_FORMATS)
+    )]
+    pub(crate) report_balance_sheet_formats: Option<Vec<String>>,
+
     /// Path to single PriceDB file
     #[arg(long = "pricedb", value_name = "path_to_pricedb-file")]
     pub(crate) pricedb_filename: Option<PathBuf>,
@@ -314,17 +474,22 @@ pub(crate) struct DefaultModeArgs {
         value_parser([
             PossibleValue::new("identity"),
             PossibleValue::new("equity"),
+            PossibleValue::new("ledger"),
+            PossibleValue::new("beancount"),
+            PossibleValue::new("price-db"),
         ])
     )]
     pub(crate) exports: Option<Vec<String>>,
 
-    /// Txn Filter definition in JSON
+    /// Txn Filter definition in JSON or as filter expression
     ///
-    /// This could be ascii armored with base64 encoding
+    /// JSON could be ascii armored with base64 encoding
     ///
     /// The ascii armor must have prefix 'base64:'
     ///
     /// e.g. "base64:eyJ0eG5GaWx0ZXIiOnsiTnVsbGFyeVRSVUUiOnt9fX0K"
+    ///
+    /// Filter expression, e.g. 'ts >= 2024-01-01 and tag = "trip"'
     #[arg(long = "api-filter-def", value_name = "txn_filter")]
     pub(crate) api_filter_def: Option<String>,
 }
@@ -334,10 +499,14 @@ impl DefaultModeArgs {
         OverlapConfig {
             audit: AuditOverlap {
                 mode: self.audit_mode,
+                attachments: self.audit_attachments,
             },
             strict: StrictOverlap {
                 mode: self.strict_mode,
             },
+            recovery: RecoveryOverlap {
+                mode: self.recovery_mode,
+            },
             price: PriceOverlap {
                 db_path: self.pricedb_filename.clone(),
                 lookup_type: self.price_lookup_type,
@@ -347,6 +516,13 @@ impl DefaultModeArgs {
                 commodity: self.report_commodity.clone(),
                 account_overlap: self.accounts.clone(),
                 group_by: self.group_by.clone(),
+                formats: self.report_formats.clone(),
+                balance_formats: self.report_balance_formats.clone(),
+                balance_group_formats: self.report_balance_group_formats.clone(),
+                register_formats: self.report_register_formats.clone(),
+                pnl_formats: self.report_pnl_formats.clone(),
+                income_statement_formats: self.report_income_statement_formats.clone(),
+                balance_sheet_formats: self.report_balance_sheet_formats.clone(),
             },
             target: TargetOverlap {
                 reports: self.reports.clone(),
@@ -403,15 +579,12 @@ impl DefaultModeArgs {
                 ext: String::from("txn"),
             };
             Ok(InputSettings::Git(i))
-        } else if self.input_git_repo.is_none() && git_selector.is_some() {
+        } else if let Some(git_ref) = git_selector {
             match settings.get_input_settings(
                 Some(&config::StorageType::STORAGE_GIT.to_string()),
                 Some(self.conf_path.as_ref().unwrap().as_path()),
             )? {
-                InputSettings::Git(git) => Ok(InputSettings::Git(GitInput {
-                    git_ref: git_selector.unwrap(/*:ok: is_some */),
-                    ..git
-                })),
+                InputSettings::Git(git) => Ok(InputSettings::Git(GitInput { git_ref, ..git })),
                 _ => {
                     let msg = "CLI Arg handling: Internal logic error";
                     Err(msg.into())
@@ -3,5 +3,8 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+pub(crate) mod convert;
+pub(crate) mod fmt;
+pub(crate) mod import;
 pub(crate) mod init;
 pub(crate) mod new;
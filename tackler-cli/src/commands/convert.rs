@@ -0,0 +1,54 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::cli_args::ConvertArgs;
+use std::fs;
+use tackler_core::convert::{Conversion, ConvertFormat, convert};
+use tackler_core::tackler;
+
+pub(crate) fn exec(args: &ConvertArgs) -> Result<Option<String>, tackler::Error> {
+    if fs::exists(&args.output)? {
+        let msg = format!(
+            "Output directory '{}' already exists",
+            args.output.display()
+        );
+        return Err(msg.into());
+    }
+
+    let format = ConvertFormat::from(&args.format)?;
+    let mut conversion = Conversion::default();
+    let mut unsupported = 0;
+    for path in &args.paths {
+        let journal = match fs::read_to_string(path) {
+            Ok(journal) => journal,
+            Err(err) => {
+                let msg = format!("Can't open file: '{}' - {}", path.display(), err);
+                return Err(msg.into());
+            }
+        };
+        if let Err(err) = convert(format, &journal, &mut conversion) {
+            let msg = format!("Journal '{}': {err}", path.display());
+            return Err(msg.into());
+        }
+        for u in &conversion.unsupported {
+            eprintln!("{}:{}: {}", path.display(), u.line, u.msg);
+        }
+        unsupported += conversion.unsupported.len();
+    }
+
+    for (path, content) in conversion.files() {
+        let path = args.output.join(path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, content)?;
+    }
+
+    Ok(Some(format!(
+        "Converted {} txns into '{}', {} unsupported constructs",
+        conversion.txn_count(),
+        args.output.display(),
+        unsupported
+    )))
+}
@@ -0,0 +1,80 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::cli_args::FmtArgs;
+use std::fs;
+use std::path::PathBuf;
+use tackler_core::config::Config;
+use tackler_core::config::overlaps::OverlapConfig;
+use tackler_core::kernel::Settings;
+use tackler_core::{parser, tackler};
+
+fn get_settings(args: &FmtArgs) -> Result<Settings, tackler::Error> {
+    match &args.conf_path {
+        Some(path) => {
+            let cfg = match Config::from(path) {
+                Ok(cfg) => cfg,
+                Err(err) => {
+                    let msg = format!("Configuration error with '{}': {err}", path.display());
+                    return Err(msg.into());
+                }
+            };
+            Settings::try_from(cfg, OverlapConfig::default())
+        }
+        None => Ok(Settings::default()),
+    }
+}
+
+fn get_paths(args: &FmtArgs) -> Result<Vec<PathBuf>, tackler::Error> {
+    let mut paths = Vec::new();
+    for path in &args.paths {
+        if path.is_dir() {
+            paths.extend(tackler_rs::get_paths_by_ext(path, "txn")?);
+        } else {
+            paths.push(path.clone());
+        }
+    }
+    Ok(paths)
+}
+
+pub(crate) fn exec(args: &FmtArgs) -> Result<Option<String>, tackler::Error> {
+    let mut settings = get_settings(args)?;
+
+    let mut unformatted = Vec::new();
+    for path in get_paths(args)? {
+        let txt = match fs::read_to_string(&path) {
+            Ok(txt) => txt,
+            Err(err) => {
+                let msg = format!("Can't open file: '{}' - {}", path.display(), err);
+                return Err(msg.into());
+            }
+        };
+        let formatted = match parser::format_journal(&txt, &mut settings) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                let msg = format!("Txn file '{}': {err}", path.display());
+                return Err(msg.into());
+            }
+        };
+        if formatted != txt {
+            if !args.check {
+                fs::write(&path, formatted)?;
+            }
+            unformatted.push(path);
+        }
+    }
+
+    if args.check && !unformatted.is_empty() {
+        let msg = format!(
+            "files are not formatted:\n{}",
+            unformatted
+                .iter()
+                .map(|p| format!("   {}", p.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        return Err(msg.into());
+    }
+    Ok(None)
+}
@@ -0,0 +1,83 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::cli_args::ImportArgs;
+use std::fs;
+use std::path::Path;
+use tackler_core::config::Config;
+use tackler_core::config::overlaps::{OverlapConfig, StrictOverlap};
+use tackler_core::import::{ImportFormat, ImportProfile, import_statement};
+use tackler_core::kernel::Settings;
+use tackler_core::{parser, tackler};
+
+fn get_config(path: &Path) -> Result<Config, tackler::Error> {
+    match Config::from(path) {
+        Ok(cfg) => Ok(cfg),
+        Err(err) => {
+            let msg = format!("Configuration error with '{}': {err}", path.display());
+            Err(msg.into())
+        }
+    }
+}
+
+pub(crate) fn exec(args: &ImportArgs) -> Result<Option<String>, tackler::Error> {
+    let conf_path = args.conf_path.as_path();
+
+    if let Some(output) = &args.output {
+        if fs::exists(output)? {
+            let msg = format!("Output file '{}' already exists", output.display());
+            return Err(msg.into());
+        }
+    }
+
+    let mut settings = Settings::try_from(get_config(conf_path)?, OverlapConfig::default())?;
+    let input = settings.get_input_settings(None, Some(conf_path))?;
+    let journal = match parser::input_to_txns(input, &mut settings) {
+        Ok(txn_data) => txn_data,
+        Err(err) if err.is::<parser::ParseDiagnostic>() || err.is::<parser::ParseErrors>() => {
+            return Err(err);
+        }
+        Err(err) => {
+            let msg = format!("Txn Data: {err}");
+            return Err(msg.into());
+        }
+    };
+
+    // Imported txns are always validated in strict mode
+    let strict_overlap = OverlapConfig {
+        strict: StrictOverlap { mode: Some(true) },
+        ..OverlapConfig::default()
+    };
+    let mut strict_settings = Settings::try_from(get_config(conf_path)?, strict_overlap)?;
+
+    let format = ImportFormat::from(&args.format)?;
+    let profile = ImportProfile::from(&args.profile)?;
+    let statement = match fs::read_to_string(&args.statement) {
+        Ok(s) => s,
+        Err(err) => {
+            let msg = format!("Can't open file: '{}' - {}", args.statement.display(), err);
+            return Err(msg.into());
+        }
+    };
+
+    let import = import_statement(format, &statement, &profile, &journal, &mut strict_settings)?;
+
+    let summary = format!(
+        "Imported {} txns, skipped {} already imported entries",
+        import.imported, import.skipped
+    );
+    match &args.output {
+        Some(output) => {
+            if import.imported > 0 {
+                fs::write(output, import.journal)?;
+            }
+            Ok(Some(summary))
+        }
+        None => {
+            print!("{}", import.journal);
+            eprintln!("{summary}");
+            Ok(None)
+        }
+    }
+}
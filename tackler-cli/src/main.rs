@@ -11,7 +11,8 @@ use log::error;
 use std::io;
 use tackler_core::export::write_exports;
 use tackler_core::kernel::settings::Settings;
-use tackler_core::report::write_txt_reports;
+use tackler_core::parser::{ParseDiagnostic, ParseErrors};
+use tackler_core::report::write_reports;
 use tackler_core::{parser, tackler};
 
 use clap::Parser;
@@ -19,7 +20,6 @@ use tackler_api::filters::FilterDefinition;
 use tackler_core::config::Config;
 
 use crate::cli_args::{Commands, DefaultModeArgs};
-use tackler_core::kernel::settings::InputSettings;
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
 
@@ -46,28 +46,14 @@ fn run(cli: DefaultModeArgs) -> Result<Option<String>, tackler::Error> {
 
     let input_type = cli.get_input_type(&settings)?;
 
-    #[rustfmt::skip]
-    let result = match input_type {
-        InputSettings::File(f) => {
-            parser::paths_to_txns(&[f.path], &mut settings)
-        },
-        InputSettings::Fs(fs) => {
-            let paths = tackler_rs::get_paths_by_ext(fs.dir.as_path(), fs.suffix.as_str())?;
-            parser::paths_to_txns(&paths, &mut settings)
-        }
-        InputSettings::Git(git) => {
-            parser::git_to_txns(
-                git.repo.as_path(),
-                git.dir.as_str(),
-                git.ext.as_str(),
-                git.git_ref,
-                &mut settings,
-            )
-        },
-    };
+    let result = parser::input_to_txns(input_type, &mut settings);
 
     let txn_data = match result {
         Ok(txn_data) => txn_data,
+        Err(err) if err.is::<ParseDiagnostic>() || err.is::<ParseErrors>() => {
+            error!("Txn Data: {err}");
+            return Err(err);
+        }
         Err(err) => {
             let msg = format!("Txn Data: {err}");
             error!("{}", msg);
@@ -79,8 +65,13 @@ fn run(cli: DefaultModeArgs) -> Result<Option<String>, tackler::Error> {
         Some(filt_str) => {
             if FilterDefinition::is_armored(&filt_str) {
                 Some(FilterDefinition::from_armor(&filt_str)?)
-            } else {
+            } else if filt_str.trim_start().starts_with('{') {
                 Some(FilterDefinition::from_json_str(&filt_str)?)
+            } else {
+                Some(FilterDefinition::from_expr(
+                    &filt_str,
+                    settings.get_timezone(),
+                )?)
             }
         }
         None => None,
@@ -106,7 +97,7 @@ fn run(cli: DefaultModeArgs) -> Result<Option<String>, tackler::Error> {
     let reports = settings.get_report_targets();
 
     if !reports.is_empty() {
-        write_txt_reports(
+        write_reports(
             &mut console_output,
             cli.output_directory.as_ref(),
             &cli.output_name,
@@ -118,19 +109,19 @@ fn run(cli: DefaultModeArgs) -> Result<Option<String>, tackler::Error> {
     }
 
     let exports = settings.get_export_targets();
-    if !exports.is_empty() && cli.output_directory.is_some() {
-        write_exports(
-            cli.output_directory
-                .as_ref()
-                .expect("IE: logic error with CLI arguments"),
-            cli.output_name
-                .expect("IE: logic error with CLI arguments")
-                .as_str(),
-            &exports,
-            &txn_set,
-            &mut settings,
-            &mut Some(Box::new(io::stdout())),
-        )?;
+    if let Some(output_dir) = &cli.output_directory {
+        if !exports.is_empty() {
+            write_exports(
+                output_dir,
+                cli.output_name
+                    .expect("IE: logic error with CLI arguments")
+                    .as_str(),
+                &exports,
+                &txn_set,
+                &mut settings,
+                &mut Some(Box::new(io::stdout())),
+            )?;
+        }
     }
     Ok(None)
 }
@@ -144,6 +135,9 @@ fn main() {
     let res = match command {
         Commands::New { name } => commands::new::exec(&exe_name, name.as_str()),
         Commands::Init {} => commands::init::exec(&exe_name, "."),
+        Commands::Fmt(args) => commands::fmt::exec(&args),
+        Commands::Import(args) => commands::import::exec(&args),
+        Commands::Convert(args) => commands::convert::exec(&args),
         Commands::Report(args) => run(args),
     };
 
@@ -155,6 +149,7 @@ fn main() {
             std::process::exit(0)
         }
         Err(err) => {
+            // parse errors are rendered by their core `Display`
             let msg = format!("Tackler error: {err}");
             error!("{msg}");
             eprintln!("{msg}");
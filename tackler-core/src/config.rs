@@ -2,17 +2,27 @@
  * Tackler-NG 2024-2025
  * SPDX-License-Identifier: Apache-2.0
  */
+pub use items::AccountInfo;
 pub(crate) use items::AccountSelectors;
+pub use items::AccountType;
+pub use items::CommodityFormat;
 pub use items::Config;
+pub use items::EquityMode;
 pub(crate) use items::Export;
 pub use items::ExportType;
 pub use items::Input;
 pub(crate) use items::Kernel;
+pub use items::LotMethod;
+pub(crate) use items::NumberFormat;
 pub use items::PriceLookupType;
 pub(crate) use items::Report;
+pub use items::ReportFormat;
+pub(crate) use items::ReportFormats;
 pub use items::ReportType;
-pub(crate) use items::Scale;
+pub use items::Rounding;
+pub use items::Scale;
 pub use items::StorageType;
+pub use items::SymbolPosition;
 
 use crate::tackler;
 pub use items::NONE_VALUE;
@@ -39,6 +49,27 @@ pub fn to_report_targets(targets: &[String]) -> Result<Vec<ReportType>, tackler:
     Ok(trgs)
 }
 
+pub fn to_report_formats(formats: &[String]) -> Result<Vec<ReportFormat>, tackler::Error> {
+    let fmts = formats
+        .iter()
+        .try_fold(
+            Vec::new(),
+            |mut fmts: Vec<ReportFormat>, fmt| match ReportFormat::from(fmt.as_str()) {
+                Ok(f) => {
+                    if !fmts.contains(&f) {
+                        fmts.push(f);
+                    }
+                    Ok::<Vec<ReportFormat>, tackler::Error>(fmts)
+                }
+                Err(e) => {
+                    let msg = format!("Invalid report format: {e}");
+                    Err(msg.into())
+                }
+            },
+        )?;
+    Ok(fmts)
+}
+
 pub fn to_export_targets(targets: &[String]) -> Result<Vec<ExportType>, tackler::Error> {
     let trgs =
         targets.iter().try_fold(
@@ -3,24 +3,27 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 use crate::config::raw_items::{
-    AccountsPathRaw, AccountsRaw, AuditRaw, BalanceGroupRaw, BalanceRaw, CommoditiesPathRaw,
-    CommoditiesRaw, ConfigRaw, EquityRaw, ExportRaw, FsRaw, GitRaw, InputRaw, KernelRaw, PriceRaw,
-    RegisterRaw, ReportRaw, ScaleRaw, TagsPathRaw, TagsRaw, TimestampRaw, TimezoneRaw,
-    TransactionRaw,
+    AccountInfoRaw, AccountsPathRaw, AccountsRaw, AttachmentsRaw, AuditRaw, BalanceGroupRaw,
+    BalanceRaw, CommoditiesPathRaw, CommoditiesRaw, CommodityFormatRaw, ConfigRaw, EquityRaw,
+    ExportRaw, FsRaw, GitRaw, InputRaw, KernelRaw, LotsRaw, MetadataPathRaw, MetadataRaw,
+    NumberFormatRaw, PnlRaw, PriceRaw, RegisterRaw, ReportRaw, ScaleRaw, StatementRaw, TagsPathRaw,
+    TagsRaw, TimestampRaw, TimezoneRaw, TransactionRaw,
 };
-use crate::config::{to_export_targets, to_report_targets};
+use crate::config::{to_export_targets, to_report_formats, to_report_targets};
 use crate::kernel::hash::Hash;
 use crate::model::Commodity;
 use crate::tackler;
 use jiff::fmt::strtime::BrokenDownTime;
 use jiff::tz::TimeZone;
-use rust_decimal::Decimal;
+use regex::Regex;
+use rust_decimal::{Decimal, RoundingStrategy};
 use std::fmt::{Debug, Display};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{cmp, fs};
 use tackler_api::txn_ts::{GroupBy, TimestampStyle};
 use tackler_rs::get_abs_path;
+use tackler_rs::regex::new_full_haystack_regex;
 
 /// UI/CFG key value for none
 pub const NONE_VALUE: &str = "none";
@@ -73,6 +76,208 @@ impl TryFrom<&str> for PriceLookupType {
     }
 }
 
+/// Lot matching method for closing postings
+///
+/// Closing posting with cost basis `{ ... }` is always matched
+/// with lots of that cost (specific lot), and with `Specific`
+/// method, the cost basis is mandatory for all closing postings.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LotMethod {
+    #[default]
+    Fifo,
+    Lifo,
+    Specific,
+}
+
+#[rustfmt::skip]
+impl LotMethod {
+    pub const FIFO:     &'static str = "fifo";
+    pub const LIFO:     &'static str = "lifo";
+    pub const SPECIFIC: &'static str = "specific";
+
+    pub fn from(method: &str) -> Result<LotMethod, tackler::Error> {
+        match method {
+            LotMethod::FIFO => Ok(LotMethod::Fifo),
+            LotMethod::LIFO => Ok(LotMethod::Lifo),
+            LotMethod::SPECIFIC => Ok(LotMethod::Specific),
+            _ => Err(format!("Unknown lot method: {}", method).into()),
+        }
+    }
+}
+
+/// Type of account or account subtree in Chart of Accounts
+///
+/// Assets and expenses have normal debit (positive) balance,
+/// and liabilities, equity and income have normal credit (negative) balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+#[rustfmt::skip]
+impl AccountType {
+    pub const ASSET:     &'static str = "asset";
+    pub const LIABILITY: &'static str = "liability";
+    pub const EQUITY:    &'static str = "equity";
+    pub const INCOME:    &'static str = "income";
+    pub const EXPENSE:   &'static str = "expense";
+
+    pub fn from(acc_type: &str) -> Result<AccountType, tackler::Error> {
+        match acc_type {
+            AccountType::ASSET => Ok(AccountType::Asset),
+            AccountType::LIABILITY => Ok(AccountType::Liability),
+            AccountType::EQUITY => Ok(AccountType::Equity),
+            AccountType::INCOME => Ok(AccountType::Income),
+            AccountType::EXPENSE => Ok(AccountType::Expense),
+            _ => Err(format!("Unknown account type: {}", acc_type).into()),
+        }
+    }
+
+    /// Normal balance of this account type is credit (negative)
+    pub fn is_credit(&self) -> bool {
+        matches!(
+            self,
+            AccountType::Liability | AccountType::Equity | AccountType::Income
+        )
+    }
+}
+
+impl Display for AccountType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Asset => f.write_str(AccountType::ASSET),
+            Self::Liability => f.write_str(AccountType::LIABILITY),
+            Self::Equity => f.write_str(AccountType::EQUITY),
+            Self::Income => f.write_str(AccountType::INCOME),
+            Self::Expense => f.write_str(AccountType::EXPENSE),
+        }
+    }
+}
+
+/// Mode of equity export
+///
+/// Balance mode makes equity txns of selected account balances,
+/// and closing mode makes closing txns of income and expense accounts
+/// into retained earnings account.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EquityMode {
+    #[default]
+    Balance,
+    Closing,
+}
+
+#[rustfmt::skip]
+impl EquityMode {
+    pub const BALANCE: &'static str = "balance";
+    pub const CLOSING: &'static str = "closing";
+
+    pub fn from(mode: &str) -> Result<EquityMode, tackler::Error> {
+        match mode {
+            EquityMode::BALANCE => Ok(EquityMode::Balance),
+            EquityMode::CLOSING => Ok(EquityMode::Closing),
+            _ => Err(format!("Unknown equity export mode: {}", mode).into()),
+        }
+    }
+}
+
+impl Display for EquityMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Balance => f.write_str(EquityMode::BALANCE),
+            Self::Closing => f.write_str(EquityMode::CLOSING),
+        }
+    }
+}
+
+/// Position of commodity symbol in reports
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SymbolPosition {
+    /// Symbol is before amount, e.g. `$12.30`
+    Prefix,
+    /// Symbol is after amount, in place of commodity name, e.g. `12.30 €`
+    #[default]
+    Suffix,
+}
+
+#[rustfmt::skip]
+impl SymbolPosition {
+    pub const PREFIX: &'static str = "prefix";
+    pub const SUFFIX: &'static str = "suffix";
+
+    pub fn from(pos: &str) -> Result<SymbolPosition, tackler::Error> {
+        match pos {
+            SymbolPosition::PREFIX => Ok(SymbolPosition::Prefix),
+            SymbolPosition::SUFFIX => Ok(SymbolPosition::Suffix),
+            _ => Err(format!("Unknown symbol position: {}", pos).into()),
+        }
+    }
+}
+
+impl Display for SymbolPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prefix => f.write_str(SymbolPosition::PREFIX),
+            Self::Suffix => f.write_str(SymbolPosition::SUFFIX),
+        }
+    }
+}
+
+/// Rounding strategy of amounts in reports
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Rounding {
+    /// Midpoint is rounded away from zero (1.5 -> 2, -1.5 -> -2)
+    #[default]
+    HalfUp,
+    /// Midpoint is rounded to nearest even number (1.5 -> 2, 2.5 -> 2)
+    HalfEven,
+    /// Towards zero, i.e. truncate (1.9 -> 1, -1.9 -> -1)
+    Down,
+    /// Away from zero (1.1 -> 2, -1.1 -> -2)
+    Up,
+}
+
+#[rustfmt::skip]
+impl Rounding {
+    pub const HALF_UP:   &'static str = "half-up";
+    pub const HALF_EVEN: &'static str = "half-even";
+    pub const DOWN:      &'static str = "down";
+    pub const UP:        &'static str = "up";
+
+    pub fn from(rounding: &str) -> Result<Rounding, tackler::Error> {
+        match rounding {
+            Rounding::HALF_UP => Ok(Rounding::HalfUp),
+            Rounding::HALF_EVEN => Ok(Rounding::HalfEven),
+            Rounding::DOWN => Ok(Rounding::Down),
+            Rounding::UP => Ok(Rounding::Up),
+            _ => Err(format!("Unknown rounding strategy: {}", rounding).into()),
+        }
+    }
+
+    pub(crate) fn strategy(&self) -> RoundingStrategy {
+        match self {
+            Rounding::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            Rounding::HalfEven => RoundingStrategy::MidpointNearestEven,
+            Rounding::Down => RoundingStrategy::ToZero,
+            Rounding::Up => RoundingStrategy::AwayFromZero,
+        }
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HalfUp => f.write_str(Rounding::HALF_UP),
+            Self::HalfEven => f.write_str(Rounding::HALF_EVEN),
+            Self::Down => f.write_str(Rounding::DOWN),
+            Self::Up => f.write_str(Rounding::UP),
+        }
+    }
+}
+
 #[rustfmt::skip]
 impl StorageType {
     pub const STORAGE_FS:   &'static str = "fs";
@@ -93,6 +298,9 @@ pub enum ReportType {
     Balance,
     BalanceGroup,
     Register,
+    Pnl,
+    IncomeStatement,
+    BalanceSheet,
 }
 impl ReportType {
     pub fn from(r: &str) -> Result<Self, tackler::Error> {
@@ -100,22 +308,67 @@ impl ReportType {
             "balance" => Ok(ReportType::Balance),
             "balance-group" => Ok(ReportType::BalanceGroup),
             "register" => Ok(ReportType::Register),
+            "pnl" => Ok(ReportType::Pnl),
+            "income-statement" => Ok(ReportType::IncomeStatement),
+            "balance-sheet" => Ok(ReportType::BalanceSheet),
             _ => Err(format!("Unknown report type {r}").into()),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Txt,
+    Json,
+    Csv,
+    Tsv,
+}
+#[rustfmt::skip]
+impl ReportFormat {
+    pub const TXT:  &'static str = "txt";
+    pub const JSON: &'static str = "json";
+    pub const CSV:  &'static str = "csv";
+    pub const TSV:  &'static str = "tsv";
+
+    pub fn from(f: &str) -> Result<Self, tackler::Error> {
+        match f {
+            ReportFormat::TXT => Ok(ReportFormat::Txt),
+            ReportFormat::JSON => Ok(ReportFormat::Json),
+            ReportFormat::CSV => Ok(ReportFormat::Csv),
+            ReportFormat::TSV => Ok(ReportFormat::Tsv),
+            _ => Err(format!("Unknown report format {f}").into()),
+        }
+    }
+
+    /// File name extension of report format
+    pub fn ext(&self) -> &'static str {
+        match self {
+            ReportFormat::Txt => ReportFormat::TXT,
+            ReportFormat::Json => ReportFormat::JSON,
+            ReportFormat::Csv => ReportFormat::CSV,
+            ReportFormat::Tsv => ReportFormat::TSV,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub enum ExportType {
     #[default]
     Equity,
     Identity,
+    Ledger,
+    Beancount,
+    PriceDb,
 }
 impl ExportType {
     pub fn from(r: &str) -> Result<Self, tackler::Error> {
         match r {
             "equity" => Ok(ExportType::Equity),
             "identity" => Ok(ExportType::Identity),
+            "ledger" => Ok(ExportType::Ledger),
+            "beancount" => Ok(ExportType::Beancount),
+            "price-db" => Ok(ExportType::PriceDb),
             _ => Err(format!("Unknown export type {r}").into()),
         }
     }
@@ -124,6 +377,7 @@ impl ExportType {
 enum Timezone {}
 
 pub(crate) type AccountSelectors = Vec<String>;
+pub(crate) type ReportFormats = Vec<ReportFormat>;
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -155,17 +409,24 @@ impl Config {
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Kernel {
     pub(crate) strict: bool,
+    pub(crate) recovery: bool,
     pub(crate) timestamp: Timestamp,
     pub(crate) audit: Audit,
     pub input: Input,
+    pub(crate) lot_method: LotMethod,
 }
 impl Kernel {
     fn from(k_raw: &KernelRaw) -> Result<Kernel, tackler::Error> {
         let k = Kernel {
             strict: k_raw.strict,
+            recovery: k_raw.recovery.unwrap_or(false),
             timestamp: Timestamp::from(&k_raw.timestamp)?,
             audit: Audit::from(&k_raw.audit)?,
             input: Input::from(&k_raw.input)?,
+            lot_method: match &k_raw.lots {
+                Some(LotsRaw { method }) => LotMethod::from(method.as_str())?,
+                None => LotMethod::default(),
+            },
         };
         Ok(k)
     }
@@ -230,17 +491,53 @@ impl Timezone {
 pub(crate) struct Audit {
     pub(crate) hash: Hash,
     pub(crate) mode: bool,
+    pub(crate) attachments: Attachments,
 }
 impl Audit {
     fn from(a_raw: &AuditRaw) -> Result<Audit, tackler::Error> {
         let a = Audit {
             hash: Hash::from(&a_raw.hash)?,
             mode: a_raw.mode,
+            attachments: match &a_raw.attachments {
+                Some(att_raw) => Attachments::from(att_raw)?,
+                None => Attachments::default(),
+            },
         };
         Ok(a)
     }
 }
 
+/// Attachments of txns
+///
+/// Values of these txn metadata keys are repository-relative paths
+/// of attachment files (e.g. receipts).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Attachments {
+    /// Verify that attachments exist (git input)
+    pub(crate) check: bool,
+    pub(crate) keys: Vec<String>,
+}
+impl Attachments {
+    fn from(att_raw: &AttachmentsRaw) -> Result<Attachments, tackler::Error> {
+        if att_raw.check && att_raw.keys.is_empty() {
+            let msg = "Attachment check is activated, but there are no attachment `keys`";
+            return Err(msg.into());
+        }
+        if let Some(key) = att_raw
+            .keys
+            .iter()
+            .find(|k| matches!(k.as_str(), "uuid" | "location" | "tags"))
+        {
+            let msg = format!("Invalid attachment key: '{key}' is reserved txn metadata key");
+            return Err(msg.into());
+        }
+        Ok(Attachments {
+            check: att_raw.check,
+            keys: att_raw.keys.clone(),
+        })
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Default)]
 pub struct Input {
@@ -316,6 +613,8 @@ impl Git {
 pub(crate) struct Price {
     pub(crate) db_path: PathBuf,
     pub(crate) lookup_type: PriceLookupType,
+    /// Use prices of closing positions (`@`, `=`) as implicit price entries
+    pub(crate) implicit_prices: bool,
 }
 impl Price {
     fn try_from<P: AsRef<Path>>(
@@ -324,18 +623,30 @@ impl Price {
     ) -> Result<Price, tackler::Error> {
         let db_path_str = price_raw.db_path.as_str();
         let lookup_type = PriceLookupType::try_from(price_raw.lookup_type.as_str())?;
+        let implicit_prices = price_raw.implicit_prices.unwrap_or(false);
 
         match db_path_str {
             NONE_VALUE => match lookup_type {
-                PriceLookupType::None => Ok(Price::default()),
+                PriceLookupType::None => Ok(Price {
+                    implicit_prices,
+                    ..Price::default()
+                }),
+                // all prices are implicit prices of txns
+                _ if implicit_prices => Ok(Price {
+                    lookup_type,
+                    implicit_prices,
+                    ..Price::default()
+                }),
                 _ => {
-                    let msg = "Price database path is 'none' but lookup type is not 'none'";
+                    let msg = "Price database path is 'none' but lookup type is not 'none' \
+                               (and implicit prices are not activated)";
                     Err(msg.into())
                 }
             },
             _ => Ok(Price {
                 db_path: get_abs_path(base_path, db_path_str)?,
                 lookup_type,
+                implicit_prices,
             }),
         }
     }
@@ -346,6 +657,7 @@ pub(crate) struct Transaction {
     pub(crate) accounts: Accounts,
     pub(crate) commodities: Commodities,
     pub(crate) tags: Tags,
+    pub(crate) metadata: Option<Metadata>,
 }
 
 impl Transaction {
@@ -357,6 +669,12 @@ impl Transaction {
             accounts: Accounts::from(&path, &txn_raw.accounts)?,
             commodities: Commodities::from(&path, &txn_raw.commodities)?,
             tags: Tags::from(&path, &txn_raw.tags)?,
+            metadata: txn_raw
+                .metadata
+                .as_ref()
+                .map(|md_path_raw| Metadata::from(&path, md_path_raw))
+                .transpose()?
+                .flatten(),
         })
     }
 }
@@ -364,6 +682,34 @@ impl Transaction {
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Accounts {
     pub names: Vec<String>,
+    pub infos: Vec<AccountInfo>,
+    /// Account types which are checked for normal balance in strict mode
+    pub normal_balance_types: Vec<AccountType>,
+}
+
+/// Account type, description and code of account or account subtree
+#[derive(Debug, Clone)]
+pub struct AccountInfo {
+    pub name: String,
+    /// Account type of this account and all of its sub-accounts
+    pub acc_type: Option<AccountType>,
+    pub description: Option<String>,
+    pub code: Option<String>,
+}
+
+impl AccountInfo {
+    fn from(info_raw: &AccountInfoRaw) -> Result<AccountInfo, tackler::Error> {
+        Ok(AccountInfo {
+            name: info_raw.name.clone(),
+            acc_type: info_raw
+                .acc_type
+                .as_ref()
+                .map(|t| AccountType::from(t.as_str()))
+                .transpose()?,
+            description: info_raw.description.clone(),
+            code: info_raw.code.clone(),
+        })
+    }
 }
 impl Accounts {
     fn from<P: AsRef<Path>>(
@@ -384,8 +730,24 @@ impl Accounts {
                         return Err(msg.into());
                     }
                 };
+                let infos = acc_raw
+                    .infos
+                    .iter()
+                    .flatten()
+                    .map(AccountInfo::from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("Accounts configuration error: {err}"))?;
+                let normal_balance_types = acc_raw
+                    .strict_normal_balance
+                    .iter()
+                    .flatten()
+                    .map(|t| AccountType::from(t.as_str()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("Accounts configuration error: {err}"))?;
                 Ok(Accounts {
                     names: acc_raw.names,
+                    infos,
+                    normal_balance_types,
                 })
             }
         }
@@ -397,7 +759,51 @@ pub(crate) struct Commodities {
     pub(crate) permit_empty_commodity: Option<bool>,
 
     pub(crate) names: Vec<String>,
+
+    pub(crate) formats: Vec<CommodityFormat>,
+}
+
+/// Display precision, symbol and rounding of commodity
+#[derive(Debug, Clone, Default)]
+pub struct CommodityFormat {
+    pub name: String,
+    /// Decimal places of amounts, this overrides `report.scale`
+    pub decimals: Option<u32>,
+    pub symbol: Option<String>,
+    pub symbol_position: SymbolPosition,
+    pub rounding: Rounding,
 }
+
+impl CommodityFormat {
+    fn from(fmt_raw: &CommodityFormatRaw) -> Result<CommodityFormat, tackler::Error> {
+        let max_scale = 28;
+        if fmt_raw.decimals.is_some_and(|d| d > max_scale) {
+            let msg = format!(
+                "too large decimals for '{}' - maximum value is {max_scale}",
+                fmt_raw.name
+            );
+            return Err(msg.into());
+        }
+        Ok(CommodityFormat {
+            name: fmt_raw.name.clone(),
+            decimals: fmt_raw.decimals,
+            symbol: fmt_raw.symbol.clone(),
+            symbol_position: fmt_raw
+                .symbol_position
+                .as_ref()
+                .map(|p| SymbolPosition::from(p.as_str()))
+                .transpose()?
+                .unwrap_or_default(),
+            rounding: fmt_raw
+                .rounding
+                .as_ref()
+                .map(|r| Rounding::from(r.as_str()))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
 impl Commodities {
     fn from<P: AsRef<Path>>(
         path: P,
@@ -408,6 +814,7 @@ impl Commodities {
             NONE_VALUE => Ok(Commodities {
                 permit_empty_commodity: Some(true),
                 names: Vec::new(),
+                formats: Vec::new(),
             }),
             _ => {
                 let comm_path = get_abs_path(&path, comm_path_str)?;
@@ -420,9 +827,17 @@ impl Commodities {
                         return Err(msg.into());
                     }
                 };
+                let formats = comm_raw
+                    .formats
+                    .iter()
+                    .flatten()
+                    .map(CommodityFormat::from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("Commodities configuration error: {err}"))?;
                 Ok(Commodities {
                     permit_empty_commodity: comm_raw.permit_empty_commodity,
                     names: comm_raw.names,
+                    formats,
                 })
             }
         }
@@ -459,15 +874,81 @@ impl Tags {
     }
 }
 
+/// Declared key of txn and posting metadata (`# key: value`)
+#[derive(Debug, Clone)]
+pub(crate) struct MetadataKey {
+    pub(crate) name: String,
+    /// Full haystack regex of the value, if any
+    pub(crate) regex: Option<Regex>,
+}
+
+/// Chart of Metadata: declared keys of key-value metadata
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Metadata {
+    pub(crate) keys: Vec<MetadataKey>,
+}
+
+impl Metadata {
+    /// Chart of Metadata, or none if it is disabled
+    fn from<P: AsRef<Path>>(
+        path: P,
+        md_path_raw: &MetadataPathRaw,
+    ) -> Result<Option<Metadata>, tackler::Error> {
+        let md_path_str = md_path_raw.path.as_str();
+        if md_path_str == NONE_VALUE {
+            return Ok(None);
+        }
+        let md_path = get_abs_path(&path, md_path_str)?;
+        let md_raw: MetadataRaw = match fs::read_to_string(&md_path) {
+            Ok(s) => toml::from_str(s.as_str())?,
+            Err(err) => {
+                let msg = format!(
+                    "Metadata configuration error while reading file '{md_path_str}': {err}"
+                );
+                return Err(msg.into());
+            }
+        };
+        let keys = md_raw
+            .keys
+            .into_iter()
+            .map(|key| {
+                let regex = key
+                    .regex
+                    .map(|re| {
+                        new_full_haystack_regex(&re).map_err(|err| {
+                            let msg = format!(
+                                "Metadata configuration error: invalid regex of key '{}': {err}",
+                                key.name
+                            );
+                            tackler::Error::from(msg)
+                        })
+                    })
+                    .transpose()?;
+                Ok(MetadataKey {
+                    name: key.name,
+                    regex,
+                })
+            })
+            .collect::<Result<Vec<_>, tackler::Error>>()?;
+        Ok(Some(Metadata { keys }))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Report {
     pub report_tz: TimeZone,
     pub targets: Vec<ReportType>,
     pub scale: Scale,
+    pub number_format: NumberFormat,
     pub commodity: Option<Arc<Commodity>>,
     pub register: Register,
     pub balance_group: BalanceGroup,
     pub balance: Balance,
+    pub pnl: Pnl,
+    pub income_statement: Statement,
+    pub balance_sheet: Statement,
+    /// Show balances of accounts with normal credit balance as positive
+    pub natural_signs: bool,
 }
 
 impl Default for Report {
@@ -476,10 +957,15 @@ impl Default for Report {
             report_tz: jiff::tz::TimeZone::UTC,
             targets: Vec::new(),
             scale: Scale::default(),
+            number_format: NumberFormat::default(),
             commodity: None,
             register: Register::default(),
             balance_group: BalanceGroup::default(),
             balance: Balance::default(),
+            pnl: Pnl::default(),
+            income_statement: Statement::default_income_statement(),
+            balance_sheet: Statement::default_balance_sheet(),
+            natural_signs: false,
         }
     }
 }
@@ -491,6 +977,12 @@ impl Report {
             report_tz: TimeZone::get(report_raw.report_tz.as_str())?,
             targets: trgs,
             scale: Scale::from(&report_raw.scale)?,
+            number_format: report_raw
+                .number_format
+                .as_ref()
+                .map(NumberFormat::from)
+                .transpose()?
+                .unwrap_or_default(),
             commodity: match &report_raw.commodity {
                 Some(c) => Some(Arc::new(Commodity::from(c.clone())?)),
                 None => None,
@@ -498,6 +990,18 @@ impl Report {
             register: Register::from(&report_raw.register, report_raw)?,
             balance_group: BalanceGroup::from(&report_raw.balance_group, report_raw)?,
             balance: Balance::from(&report_raw.balance, report_raw)?,
+            pnl: Pnl::from(&report_raw.pnl, report_raw)?,
+            income_statement: Statement::from(
+                &report_raw.income_statement,
+                Statement::INCOME_STATEMENT_TITLE,
+                report_raw,
+            )?,
+            balance_sheet: Statement::from(
+                &report_raw.balance_sheet,
+                Statement::BALANCE_SHEET_TITLE,
+                report_raw,
+            )?,
+            natural_signs: report_raw.natural_signs.unwrap_or(false),
         })
     }
 }
@@ -530,12 +1034,73 @@ impl Scale {
     }
 }
 
+/// Decimal and digit grouping separators of text reports
+#[derive(Debug, Clone)]
+pub(crate) struct NumberFormat {
+    pub(crate) decimal_sep: char,
+    pub(crate) grouping_sep: Option<char>,
+}
+
+impl NumberFormat {
+    fn from(nf_raw: &NumberFormatRaw) -> Result<NumberFormat, tackler::Error> {
+        fn to_char(sep: &str, name: &str) -> Result<char, tackler::Error> {
+            let mut chars = sep.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_ascii_digit() && c != '-' => Ok(c),
+                _ => {
+                    let msg = format!(
+                        "number format error: {name} must be a single non-digit character, got '{sep}'"
+                    );
+                    Err(msg.into())
+                }
+            }
+        }
+        let decimal_sep = to_char(&nf_raw.decimal_sep, "decimal-separator")?;
+        let grouping_sep = nf_raw
+            .grouping_sep
+            .as_ref()
+            .filter(|s| !s.is_empty())
+            .map(|s| to_char(s, "grouping-separator"))
+            .transpose()?;
+        if grouping_sep == Some(decimal_sep) {
+            let msg = "number format error: decimal and grouping separators must be different";
+            return Err(msg.into());
+        }
+        Ok(NumberFormat {
+            decimal_sep,
+            grouping_sep,
+        })
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimal_sep: '.',
+            grouping_sep: None,
+        }
+    }
+}
+
 impl Default for Scale {
     fn default() -> Self {
         Scale { min: 2, max: 7 }
     }
 }
 
+fn get_report_formats(
+    formats: &Option<Vec<String>>,
+    report: &ReportRaw,
+) -> Result<ReportFormats, tackler::Error> {
+    match formats {
+        Some(fmts) => to_report_formats(fmts),
+        None => match &report.formats {
+            Some(fmts) => to_report_formats(fmts),
+            None => Ok(vec![ReportFormat::Txt]),
+        },
+    }
+}
+
 fn get_account_selector(
     acc_sel: &Option<AccountSelectors>,
     report: &ReportRaw,
@@ -554,6 +1119,7 @@ pub(crate) struct Register {
     pub title: String,
     pub timestamp_style: TimestampStyle,
     pub acc_sel: AccountSelectors,
+    pub formats: ReportFormats,
 }
 
 impl Register {
@@ -565,6 +1131,7 @@ impl Register {
                 None => TimestampStyle::Date,
             },
             acc_sel: get_account_selector(&reg_raw.acc_sel, report),
+            formats: get_report_formats(&reg_raw.formats, report)?,
         })
     }
 }
@@ -574,6 +1141,7 @@ pub(crate) struct BalanceGroup {
     pub title: String,
     pub group_by: GroupBy,
     pub acc_sel: AccountSelectors,
+    pub formats: ReportFormats,
 }
 
 impl BalanceGroup {
@@ -585,6 +1153,7 @@ impl BalanceGroup {
             title: balgrp_raw.title.clone(),
             group_by: GroupBy::from(balgrp_raw.group_by.as_str())?,
             acc_sel: get_account_selector(&balgrp_raw.acc_sel, report),
+            formats: get_report_formats(&balgrp_raw.formats, report)?,
         })
     }
 }
@@ -593,6 +1162,7 @@ impl BalanceGroup {
 pub(crate) struct Balance {
     pub title: String,
     pub acc_sel: AccountSelectors,
+    pub formats: ReportFormats,
 }
 
 impl Balance {
@@ -600,10 +1170,103 @@ impl Balance {
         Ok(Balance {
             title: bal_raw.title.clone(),
             acc_sel: get_account_selector(&bal_raw.acc_sel, report),
+            formats: get_report_formats(&bal_raw.formats, report)?,
         })
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct Pnl {
+    pub title: String,
+    pub acc_sel: AccountSelectors,
+    pub formats: ReportFormats,
+}
+
+impl Default for Pnl {
+    fn default() -> Self {
+        Pnl {
+            title: Pnl::DEFAULT_TITLE.to_string(),
+            acc_sel: AccountSelectors::default(),
+            formats: vec![ReportFormat::Txt],
+        }
+    }
+}
+
+impl Pnl {
+    const DEFAULT_TITLE: &'static str = "PnL Report";
+
+    fn from(pnl_raw: &Option<PnlRaw>, report: &ReportRaw) -> Result<Pnl, tackler::Error> {
+        match pnl_raw {
+            Some(pnl_raw) => Ok(Pnl {
+                title: pnl_raw
+                    .title
+                    .clone()
+                    .unwrap_or(Pnl::DEFAULT_TITLE.to_string()),
+                acc_sel: get_account_selector(&pnl_raw.acc_sel, report),
+                formats: get_report_formats(&pnl_raw.formats, report)?,
+            }),
+            None => Ok(Pnl {
+                title: Pnl::DEFAULT_TITLE.to_string(),
+                acc_sel: get_account_selector(&None, report),
+                formats: get_report_formats(&None, report)?,
+            }),
+        }
+    }
+}
+
+/// Income statement or balance sheet
+#[derive(Debug, Clone)]
+pub(crate) struct Statement {
+    pub title: String,
+    /// Statement for each period, or for the whole txn set
+    pub group_by: Option<GroupBy>,
+    pub formats: ReportFormats,
+}
+
+impl Statement {
+    const INCOME_STATEMENT_TITLE: &'static str = "Income Statement";
+    const BALANCE_SHEET_TITLE: &'static str = "Balance Sheet";
+
+    fn default_income_statement() -> Statement {
+        Statement {
+            title: Statement::INCOME_STATEMENT_TITLE.to_string(),
+            group_by: None,
+            formats: vec![ReportFormat::Txt],
+        }
+    }
+
+    fn default_balance_sheet() -> Statement {
+        Statement {
+            title: Statement::BALANCE_SHEET_TITLE.to_string(),
+            group_by: None,
+            formats: vec![ReportFormat::Txt],
+        }
+    }
+
+    fn from(
+        stmt_raw: &Option<StatementRaw>,
+        default_title: &str,
+        report: &ReportRaw,
+    ) -> Result<Statement, tackler::Error> {
+        match stmt_raw {
+            Some(stmt_raw) => Ok(Statement {
+                title: stmt_raw.title.clone().unwrap_or(default_title.to_string()),
+                group_by: stmt_raw
+                    .group_by
+                    .as_ref()
+                    .map(|g| GroupBy::from(g.as_str()))
+                    .transpose()?,
+                formats: get_report_formats(&stmt_raw.formats, report)?,
+            }),
+            None => Ok(Statement {
+                title: default_title.to_string(),
+                group_by: None,
+                formats: get_report_formats(&None, report)?,
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Export {
     pub targets: Vec<ExportType>,
@@ -623,13 +1286,28 @@ impl Export {
 pub(crate) struct Equity {
     pub(crate) equity_account: String,
     pub(crate) acc_sel: AccountSelectors,
+    pub(crate) mode: EquityMode,
+    pub(crate) retained_earnings: Option<String>,
+    /// Cutoff timestamp of closing mode, this is parsed with kernel settings
+    pub(crate) cutoff: Option<String>,
 }
 
 impl Equity {
     fn from(eq_raw: &EquityRaw, report: &ReportRaw) -> Result<Equity, tackler::Error> {
+        let mode = match &eq_raw.mode {
+            Some(mode) => EquityMode::from(mode)?,
+            None => EquityMode::default(),
+        };
+        if mode == EquityMode::Closing && eq_raw.retained_earnings.is_none() {
+            let msg = "Equity export: `retained-earnings` account is mandatory with closing mode";
+            return Err(msg.into());
+        }
         Ok(Equity {
             equity_account: eq_raw.equity_account.clone(),
             acc_sel: get_account_selector(&eq_raw.acc_sel, report),
+            mode,
+            retained_earnings: eq_raw.retained_earnings.clone(),
+            cutoff: eq_raw.cutoff.clone(),
         })
     }
 }
@@ -16,6 +16,8 @@ pub struct OverlapConfig {
     pub audit: AuditOverlap,
     /// Strict mode related overlaps
     pub strict: StrictOverlap,
+    /// Error recovery mode related overlaps
+    pub recovery: RecoveryOverlap,
     /// Price DB and conversion related overlaps
     pub price: PriceOverlap,
     /// Reporting related overlaps
@@ -29,6 +31,8 @@ pub struct OverlapConfig {
 pub struct AuditOverlap {
     /// Audit-mode
     pub mode: Option<bool>,
+    /// Attachment check
+    pub attachments: Option<bool>,
 }
 
 /// Strict mode related overlaps
@@ -38,6 +42,13 @@ pub struct StrictOverlap {
     pub mode: Option<bool>,
 }
 
+/// Error recovery mode related overlaps
+#[derive(Debug, Default, Clone)]
+pub struct RecoveryOverlap {
+    /// Recovery-mode
+    pub mode: Option<bool>,
+}
+
 /// Price overlap configuration
 #[derive(Debug, Default, Clone)]
 pub struct PriceOverlap {
@@ -58,6 +69,20 @@ pub struct ReportOverlap {
     pub account_overlap: Option<Vec<String>>,
     /// Group-By operator
     pub group_by: Option<String>,
+    /// Report output formats (for all reports)
+    pub formats: Option<Vec<String>>,
+    /// Output formats of Balance report
+    pub balance_formats: Option<Vec<String>>,
+    /// Output formats of Balance Group report
+    pub balance_group_formats: Option<Vec<String>>,
+    /// Output formats of Register report
+    pub register_formats: Option<Vec<String>>,
+    /// Output formats of PnL report
+    pub pnl_formats: Option<Vec<String>>,
+    /// Output formats of Income Statement
+    pub income_statement_formats: Option<Vec<String>>,
+    /// Output formats of Balance Sheet
+    pub balance_sheet_formats: Option<Vec<String>>,
 }
 
 /// Target (reports, exports) overlap configuration
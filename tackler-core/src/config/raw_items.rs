@@ -18,9 +18,16 @@ pub(super) struct ConfigRaw {
 #[derive(Debug, Clone, Deserialize)]
 pub(super) struct KernelRaw {
     pub(super) strict: bool,
+    pub(super) recovery: Option<bool>,
     pub(super) timestamp: TimestampRaw,
     pub(super) audit: AuditRaw,
     pub(super) input: InputRaw,
+    pub(super) lots: Option<LotsRaw>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct LotsRaw {
+    pub(super) method: String,
 }
 
 #[allow(dead_code)]
@@ -42,6 +49,13 @@ pub(super) struct TimezoneRaw {
 pub(super) struct AuditRaw {
     pub(super) hash: String,
     pub(super) mode: bool,
+    pub(super) attachments: Option<AttachmentsRaw>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct AttachmentsRaw {
+    pub(super) check: bool,
+    pub(super) keys: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -78,6 +92,8 @@ pub(super) struct PriceRaw {
     pub(super) db_path: String,
     #[serde(rename = "lookup-type")]
     pub(super) lookup_type: String,
+    #[serde(rename = "implicit-prices")]
+    pub(super) implicit_prices: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -85,6 +101,7 @@ pub(super) struct TransactionRaw {
     pub(super) accounts: AccountsPathRaw,
     pub(super) commodities: CommoditiesPathRaw,
     pub(super) tags: TagsPathRaw,
+    pub(super) metadata: Option<MetadataPathRaw>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -102,10 +119,28 @@ pub(super) struct TagsPathRaw {
     pub(super) path: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct MetadataPathRaw {
+    pub(super) path: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(super) struct AccountsRaw {
     #[serde(rename = "accounts")]
     pub(super) names: Vec<String>,
+    #[serde(rename = "account")]
+    pub(super) infos: Option<Vec<AccountInfoRaw>>,
+    #[serde(rename = "strict-normal-balance")]
+    pub(super) strict_normal_balance: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct AccountInfoRaw {
+    pub(super) name: String,
+    #[serde(rename = "type")]
+    pub(super) acc_type: Option<String>,
+    pub(super) description: Option<String>,
+    pub(super) code: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -115,6 +150,19 @@ pub(super) struct CommoditiesRaw {
 
     #[serde(rename = "commodities")]
     pub(crate) names: Vec<String>,
+
+    #[serde(rename = "commodity")]
+    pub(crate) formats: Option<Vec<CommodityFormatRaw>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct CommodityFormatRaw {
+    pub(super) name: String,
+    pub(super) decimals: Option<u32>,
+    pub(super) symbol: Option<String>,
+    #[serde(rename = "symbol-position")]
+    pub(super) symbol_position: Option<String>,
+    pub(super) rounding: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -123,18 +171,40 @@ pub(super) struct TagsRaw {
     pub(crate) names: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct MetadataRaw {
+    #[serde(rename = "key")]
+    pub(super) keys: Vec<MetadataKeyRaw>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct MetadataKeyRaw {
+    pub(super) name: String,
+    pub(super) regex: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(super) struct ReportRaw {
     #[serde(rename = "report-timezone")]
     pub(super) report_tz: String,
     pub(super) targets: Vec<String>,
     pub(super) accounts: Option<Vec<String>>,
+    pub(super) formats: Option<Vec<String>>,
     pub(super) scale: ScaleRaw,
+    #[serde(rename = "number-format")]
+    pub(super) number_format: Option<NumberFormatRaw>,
     pub(super) commodity: Option<String>,
     pub(super) register: RegisterRaw,
     #[serde(rename = "balance-group")]
     pub(super) balance_group: BalanceGroupRaw,
     pub(super) balance: BalanceRaw,
+    pub(super) pnl: Option<PnlRaw>,
+    #[serde(rename = "income-statement")]
+    pub(super) income_statement: Option<StatementRaw>,
+    #[serde(rename = "balance-sheet")]
+    pub(super) balance_sheet: Option<StatementRaw>,
+    #[serde(rename = "natural-signs")]
+    pub(super) natural_signs: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -143,11 +213,20 @@ pub(super) struct ScaleRaw {
     pub(super) max: u32,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct NumberFormatRaw {
+    #[serde(rename = "decimal-separator")]
+    pub(super) decimal_sep: String,
+    #[serde(rename = "grouping-separator")]
+    pub(super) grouping_sep: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(super) struct BalanceRaw {
     pub(super) title: String,
     #[serde(rename = "accounts")]
     pub(super) acc_sel: Option<AccountSelectors>,
+    pub(super) formats: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -157,6 +236,7 @@ pub(super) struct BalanceGroupRaw {
     pub(super) group_by: String,
     #[serde(rename = "accounts")]
     pub(super) acc_sel: Option<AccountSelectors>,
+    pub(super) formats: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -166,6 +246,23 @@ pub(super) struct RegisterRaw {
     pub(super) timestamp_style: Option<String>,
     #[serde(rename = "accounts")]
     pub(super) acc_sel: Option<AccountSelectors>,
+    pub(super) formats: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct PnlRaw {
+    pub(super) title: Option<String>,
+    #[serde(rename = "accounts")]
+    pub(super) acc_sel: Option<AccountSelectors>,
+    pub(super) formats: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct StatementRaw {
+    pub(super) title: Option<String>,
+    #[serde(rename = "group-by")]
+    pub(super) group_by: Option<String>,
+    pub(super) formats: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -181,4 +278,8 @@ pub(super) struct EquityRaw {
     pub(super) equity_account: String,
     #[serde(rename = "accounts")]
     pub(super) acc_sel: Option<AccountSelectors>,
+    pub(super) mode: Option<String>,
+    #[serde(rename = "retained-earnings")]
+    pub(super) retained_earnings: Option<String>,
+    pub(super) cutoff: Option<String>,
 }
@@ -0,0 +1,650 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Conversion of ledger, hledger and beancount journals into Tackler journal
+//!
+//! Converted txns are written into a txn shard tree (`txns/YYYY/MM/DD/`),
+//! one txn per file, together with the chart of accounts (`accounts.toml`),
+//! commodities (`commodities.toml`), tags (`tags.toml`) and price database (`price.db`).
+//!
+//! Account, commodity and tag names are converted into valid Tackler names:
+//! whitespace is replaced by `·` and other invalid characters by `_`.
+//!
+//! Constructs which can't be converted are reported with their line numbers,
+//! and txns which contain such constructs are skipped.
+use crate::kernel::Settings;
+use crate::parser;
+use crate::parser::parts::identifier::{id_char, id_start_char};
+use crate::parser::parts::txn_header_code::valid_code_char;
+use crate::tackler;
+use itertools::Itertools;
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+mod beancount;
+mod ledger;
+
+/// Format of converted journal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConvertFormat {
+    /// Ledger and hledger journal
+    Ledger,
+    /// Beancount journal
+    Beancount,
+}
+#[rustfmt::skip]
+impl ConvertFormat {
+    pub const LEDGER:    &'static str = "ledger";
+    pub const HLEDGER:   &'static str = "hledger";
+    pub const BEANCOUNT: &'static str = "beancount";
+
+    pub fn from(f: &str) -> Result<Self, tackler::Error> {
+        match f {
+            ConvertFormat::LEDGER | ConvertFormat::HLEDGER => Ok(ConvertFormat::Ledger),
+            ConvertFormat::BEANCOUNT => Ok(ConvertFormat::Beancount),
+            _ => Err(format!("Unknown convert format {f}").into()),
+        }
+    }
+}
+
+/// Construct of the source journal which is not converted
+#[derive(Debug, Clone)]
+pub struct Unsupported {
+    /// Line number in the source journal
+    pub line: usize,
+    pub msg: String,
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConvAmount {
+    pub(crate) num: Decimal,
+    pub(crate) comm: String,
+}
+
+impl Display for ConvAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.comm.is_empty() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{} {}", self.num, self.comm)
+        }
+    }
+}
+
+/// Posting value: amount, cost, price (with total price flag) and balance assertion
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PostingValue {
+    pub(crate) amount: Option<ConvAmount>,
+    pub(crate) cost: Option<ConvAmount>,
+    pub(crate) price: Option<(ConvAmount, bool)>,
+    pub(crate) assertion: Option<ConvAmount>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ConvPosting {
+    pub(crate) account: String,
+    pub(crate) value: PostingValue,
+    pub(crate) comment: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ConvTxn {
+    pub(crate) line: usize,
+    pub(crate) date: Date,
+    pub(crate) code: Option<String>,
+    pub(crate) description: String,
+    pub(crate) uuid: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) comments: Vec<String>,
+    pub(crate) posts: Vec<ConvPosting>,
+}
+
+impl ConvTxn {
+    pub(crate) fn new(line: usize, date: Date) -> ConvTxn {
+        ConvTxn {
+            line,
+            date,
+            code: None,
+            description: String::new(),
+            uuid: None,
+            tags: Vec::new(),
+            comments: Vec::new(),
+            posts: Vec::new(),
+        }
+    }
+}
+
+/// Tackler name: whitespace is replaced by `·` and invalid characters by `_`
+pub(crate) fn tackler_name(name: &str) -> String {
+    name.trim()
+        .split(':')
+        .map(|part| {
+            part.chars()
+                .map(|c| match c {
+                    c if id_char(c) => c,
+                    c if c.is_whitespace() => '·',
+                    _ => '_',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Tackler commodity name, commodity can't have sub-parts
+pub(crate) fn tackler_commodity(name: &str) -> String {
+    tackler_name(&name.replace(':', "_"))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.starts_with(id_start_char) && !name.split(':').any(str::is_empty)
+}
+
+/// Date as `YYYY-MM-DD`, `YYYY/MM/DD` or `YYYY.MM.DD`
+pub(crate) fn parse_date(date: &str) -> Option<Date> {
+    let parts: Vec<_> = date.split(['-', '/', '.']).collect();
+    match parts[..] {
+        [y, m, d] => Date::new(y.parse().ok()?, m.parse().ok()?, d.parse().ok()?).ok(),
+        _ => None,
+    }
+}
+
+/// Decimal mark of the number, when it's not set by the journal
+///
+/// If there are both `.` and `,`, the last one is the decimal mark. Repeated
+/// separator is a thousands separator, and single `.` is a decimal mark.
+/// Single `,` followed by three digits (e.g. `10,500`) is ambiguous (`None`).
+fn guess_decimal_mark(num: &str) -> Option<char> {
+    match (num.rfind('.'), num.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+        (None, Some(comma)) => {
+            let (int, frac) = (&num[..comma], &num[comma + 1..]);
+            if int.contains(',') {
+                Some('.')
+            } else if frac.len() == 3 && (1..=3).contains(&int.len()) && !int.starts_with('0') {
+                None
+            } else {
+                Some(',')
+            }
+        }
+        (Some(dot), None) if num[..dot].contains('.') => Some(','),
+        _ => Some('.'),
+    }
+}
+
+/// Number with optional thousands separators
+///
+/// Separators other than the decimal mark are thousands separators,
+/// and those are not allowed after the decimal mark.
+fn parse_number(num: &str, decimal_mark: char) -> Option<Decimal> {
+    let num = match num.split_once(decimal_mark) {
+        Some((_, frac)) if frac.contains(['.', ',']) => return None,
+        Some((int, frac)) => format!("{}.{frac}", int.replace(['.', ','], "")),
+        None => num.replace(['.', ','], ""),
+    };
+    Decimal::from_str(&num).ok()
+}
+
+/// Take commodity in front of the number (e.g. `$10`) or after the number (e.g. `10 EUR`)
+fn take_commodity(s: &str, is_prefix: bool) -> (String, &str) {
+    if let Some(quoted) = s.strip_prefix('"') {
+        return match quoted.split_once('"') {
+            Some((comm, rest)) => (comm.to_string(), rest),
+            None => (String::new(), s),
+        };
+    }
+    let len = s
+        .find(|c: char| {
+            c.is_whitespace() || (is_prefix && (c.is_ascii_digit() || matches!(c, '-' | '.' | ',')))
+        })
+        .unwrap_or(s.len());
+    (s[..len].to_string(), &s[len..])
+}
+
+/// Amount with commodity, e.g. `10 EUR`, `EUR 10`, `-$10`, `$-10` or `10 "ACME 1"`
+///
+/// Decimal mark is guessed by the number, if it's not set by the journal.
+pub(crate) fn parse_amount(s: &str, decimal_mark: Option<char>) -> Result<ConvAmount, String> {
+    let err = || format!("unsupported amount '{}'", s.trim());
+
+    let mut rest = s.trim();
+    let mut is_neg = false;
+    if let Some(r) = rest.strip_prefix('-') {
+        is_neg = true;
+        rest = r.trim_start();
+    }
+    let (mut comm, r) = take_commodity(rest, true);
+    rest = r.trim_start();
+    if let Some(r) = rest.strip_prefix('-') {
+        is_neg = !is_neg;
+        rest = r;
+    }
+
+    let num_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(rest.len());
+    let num = &rest[..num_len];
+    if num.is_empty() {
+        return Err(err());
+    }
+    let Some(decimal_mark) = decimal_mark.or_else(|| guess_decimal_mark(num)) else {
+        return Err(format!(
+            "ambiguous decimal mark of amount '{}', set it with 'decimal-mark' directive",
+            s.trim()
+        ));
+    };
+    let num = parse_number(num, decimal_mark).ok_or_else(err)?;
+
+    rest = rest[num_len..].trim();
+    if !rest.is_empty() {
+        let (c, r) = take_commodity(rest, false);
+        if !comm.is_empty() || c.is_empty() || !r.trim().is_empty() {
+            return Err(err());
+        }
+        comm = c;
+    }
+
+    Ok(ConvAmount {
+        num: if is_neg { -num } else { num },
+        comm: tackler_commodity(&comm),
+    })
+}
+
+/// Posting value: `amount [{cost}] [@ price | @@ total price] [= balance assertion]`
+///
+/// Cost could have also lot date and label (beancount), those are ignored.
+pub(crate) fn parse_posting_value(
+    value: &str,
+    decimal_mark: Option<char>,
+) -> Result<PostingValue, String> {
+    let (value, assertion) = match value.split_once('=') {
+        Some((v, a)) => (v, Some(parse_amount(a, decimal_mark)?)),
+        None => (value, None),
+    };
+    let (value, price) = if let Some((v, p)) = value.split_once("@@") {
+        (v, Some((parse_amount(p, decimal_mark)?, true)))
+    } else if let Some((v, p)) = value.split_once('@') {
+        (v, Some((parse_amount(p, decimal_mark)?, false)))
+    } else {
+        (value, None)
+    };
+    let (value, cost) = match value.split_once('{') {
+        Some((v, c)) => {
+            let cost = match c.trim_end().strip_suffix('}') {
+                Some(cost) if !cost.starts_with('{') => cost,
+                _ => return Err(format!("unsupported cost '{{{c}'")),
+            };
+            let cost = cost
+                .split(',')
+                .map(str::trim)
+                .filter(|c| parse_date(c).is_none() && !c.starts_with('"'))
+                .collect::<Vec<_>>()
+                .join(",");
+            if cost.is_empty() {
+                return Err("cost without amount is not supported".to_string());
+            }
+            (v, Some(parse_amount(&cost, decimal_mark)?))
+        }
+        None => (value, None),
+    };
+
+    let amount = match value.trim() {
+        "" if cost.is_some() || price.is_some() => {
+            return Err("price or cost without amount".to_string());
+        }
+        "" if assertion.is_some() => {
+            return Err("balance assignment is not supported".to_string());
+        }
+        "" => None,
+        v => Some(parse_amount(v, decimal_mark)?),
+    };
+
+    Ok(PostingValue {
+        amount,
+        cost,
+        price,
+        assertion,
+    })
+}
+
+/// Result of the conversion
+#[derive(Default)]
+pub struct Conversion {
+    settings: Settings,
+    txns: Vec<(Date, String)>,
+    accounts: BTreeSet<String>,
+    commodities: BTreeSet<String>,
+    tags: BTreeSet<String>,
+    prices: Vec<String>,
+    has_empty_commodity: bool,
+    /// Unsupported constructs of the latest converted journal
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl Conversion {
+    /// Count of converted txns
+    pub fn txn_count(&self) -> usize {
+        self.txns.len()
+    }
+
+    pub(crate) fn report(&mut self, line: usize, msg: impl Into<String>) {
+        self.unsupported.push(Unsupported {
+            line,
+            msg: msg.into(),
+        });
+    }
+
+    pub(crate) fn add_account(&mut self, line: usize, account: &str) {
+        let account = tackler_name(account);
+        if is_valid_name(&account) {
+            self.accounts.insert(account);
+        } else {
+            self.report(line, format!("invalid account name '{account}'"));
+        }
+    }
+
+    pub(crate) fn add_commodity(&mut self, line: usize, commodity: &str) {
+        let commodity = tackler_commodity(commodity);
+        if is_valid_name(&commodity) {
+            self.commodities.insert(commodity);
+        } else {
+            self.report(line, format!("invalid commodity name '{commodity}'"));
+        }
+    }
+
+    pub(crate) fn add_price(
+        &mut self,
+        line: usize,
+        date: Date,
+        commodity: &str,
+        price: ConvAmount,
+    ) {
+        let commodity = tackler_commodity(commodity);
+        if !is_valid_name(&commodity) || !is_valid_name(&price.comm) {
+            self.report(line, "price without valid commodities is not supported");
+            return;
+        }
+        self.prices.push(format!("P {date} {commodity} {price}"));
+        self.commodities.insert(commodity);
+        self.commodities.insert(price.comm);
+    }
+
+    fn render(txn: &ConvTxn) -> String {
+        let mut lines = vec![format!(
+            "{}{}{}",
+            txn.date,
+            txn.code
+                .as_ref()
+                .map(|c| c
+                    .chars()
+                    .filter(|c| valid_code_char(*c))
+                    .collect::<String>())
+                .filter(|c| !c.trim().is_empty())
+                .map(|c| format!(" ({})", c.trim()))
+                .unwrap_or_default(),
+            match txn.description.trim() {
+                "" => String::new(),
+                desc => format!(" '{desc}"),
+            }
+        )];
+        if let Some(uuid) = &txn.uuid {
+            lines.push(format!(" # uuid: {uuid}"));
+        }
+        if !txn.tags.is_empty() {
+            lines.push(format!(" # tags: {}", txn.tags.join(", ")));
+        }
+        for c in &txn.comments {
+            lines.push(format!(" ; {c}"));
+        }
+        for p in &txn.posts {
+            let v = &p.value;
+            lines.push(format!(
+                " {}{}{}{}{}{}",
+                p.account,
+                v.amount
+                    .as_ref()
+                    .map(|a| format!("  {a}"))
+                    .unwrap_or_default(),
+                v.cost
+                    .as_ref()
+                    .map(|c| format!(" {{{c}}}"))
+                    .unwrap_or_default(),
+                match (&v.price, &v.amount) {
+                    (Some((price, true)), Some(amount)) => format!(
+                        " = {}",
+                        ConvAmount {
+                            num: if amount.num.is_sign_negative() {
+                                -price.num.abs()
+                            } else {
+                                price.num.abs()
+                            },
+                            comm: price.comm.clone(),
+                        }
+                    ),
+                    (Some((price, false)), _) => format!(" @ {price}"),
+                    // posting with cost and without price is valued at cost
+                    (None, Some(_)) => v
+                        .cost
+                        .as_ref()
+                        .map(|c| format!(" @ {c}"))
+                        .unwrap_or_default(),
+                    _ => String::new(),
+                },
+                v.assertion
+                    .as_ref()
+                    .map(|a| format!(" == {a}"))
+                    .unwrap_or_default(),
+                p.comment
+                    .as_ref()
+                    .map(|c| format!(" ; {c}"))
+                    .unwrap_or_default(),
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Convert txn into Tackler txn, and validate it
+    pub(crate) fn add_txn(&mut self, mut txn: ConvTxn) {
+        txn.tags = txn.tags.iter().map(|t| tackler_name(t)).unique().collect();
+        for p in &mut txn.posts {
+            p.account = tackler_name(&p.account);
+        }
+
+        match parser::format_journal(&Self::render(&txn), &mut self.settings) {
+            Ok(formatted) => {
+                for p in &txn.posts {
+                    self.accounts.insert(p.account.clone());
+                    let v = &p.value;
+                    let amounts = [&v.amount, &v.cost, &v.assertion]
+                        .into_iter()
+                        .flatten()
+                        .chain(v.price.as_ref().map(|(p, _)| p));
+                    for a in amounts {
+                        match a.comm.is_empty() {
+                            true => self.has_empty_commodity = true,
+                            false => {
+                                self.commodities.insert(a.comm.clone());
+                            }
+                        }
+                    }
+                }
+                self.tags.extend(txn.tags.iter().cloned());
+                self.txns.push((txn.date, formatted));
+            }
+            Err(err) => {
+                let err = err.to_string().replace('\n', "\n   ");
+                self.report(txn.line, format!("txn can't be converted: {err}"));
+            }
+        }
+    }
+
+    fn names_toml(key: &str, names: &BTreeSet<String>) -> String {
+        format!(
+            "{key} = [\n{}]\n",
+            names
+                .iter()
+                .map(|n| format!("    \"{n}\",\n"))
+                .collect::<String>()
+        )
+    }
+
+    /// Converted files, path is relative to the output directory
+    pub fn files(&self) -> Vec<(PathBuf, String)> {
+        let mut files = Vec::new();
+
+        let mut prev_date = None;
+        let mut n = 0;
+        let mut txns: Vec<_> = self.txns.iter().collect();
+        txns.sort_by_key(|(date, _)| *date);
+        for (date, txn) in txns {
+            n = if prev_date == Some(date) { n + 1 } else { 1 };
+            prev_date = Some(date);
+            let path = PathBuf::from(format!(
+                "txns/{}/{:02}/{:02}/{}-{n:04}.txn",
+                date.year(),
+                date.month(),
+                date.day(),
+                date.strftime("%Y%m%d")
+            ));
+            files.push((path, txn.clone()));
+        }
+
+        files.push((
+            PathBuf::from("accounts.toml"),
+            Self::names_toml("accounts", &self.accounts),
+        ));
+        files.push((
+            PathBuf::from("commodities.toml"),
+            format!(
+                "permit-empty-commodity = {}\n\n{}",
+                self.has_empty_commodity,
+                Self::names_toml("commodities", &self.commodities)
+            ),
+        ));
+        files.push((
+            PathBuf::from("tags.toml"),
+            Self::names_toml("tags", &self.tags),
+        ));
+        if !self.prices.is_empty() {
+            files.push((PathBuf::from("price.db"), self.prices.join("\n") + "\n"));
+        }
+        files
+    }
+}
+
+/// Convert journal, and add its txns, accounts, commodities, tags and prices into conversion
+///
+/// Unsupported constructs of the journal are in `conversion.unsupported`.
+pub fn convert(
+    format: ConvertFormat,
+    input: &str,
+    conversion: &mut Conversion,
+) -> Result<(), tackler::Error> {
+    conversion.unsupported.clear();
+    match format {
+        ConvertFormat::Ledger => ledger::convert(input, conversion),
+        ConvertFormat::Beancount => beancount::convert(input, conversion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn amount(num: Decimal, comm: &str) -> ConvAmount {
+        ConvAmount {
+            num,
+            comm: comm.to_string(),
+        }
+    }
+
+    #[test]
+    fn convert_amounts() {
+        let parse = |s| parse_amount(s, None);
+        assert_eq!(parse("$-1,000.50"), Ok(amount(dec!(-1000.50), "$")));
+        assert_eq!(parse("-$ 10"), Ok(amount(dec!(-10), "$")));
+        assert_eq!(parse("10,5 EUR"), Ok(amount(dec!(10.5), "EUR")));
+        assert_eq!(parse("EUR 1,000,000"), Ok(amount(dec!(1000000), "EUR")));
+        assert_eq!(parse("2 HOOL2"), Ok(amount(dec!(2), "HOOL2")));
+        assert_eq!(parse("3 \"ACME 1.A\""), Ok(amount(dec!(3), "ACME·1_A")));
+        assert_eq!(parse("12"), Ok(amount(dec!(12), "")));
+        assert!(parse("(1 + 2) EUR").is_err());
+        assert!(parse("1 EUR x").is_err());
+    }
+
+    #[test]
+    fn convert_amounts_decimal_mark() {
+        let parse = |s| parse_amount(s, None);
+        assert_eq!(parse("1.234,56 EUR"), Ok(amount(dec!(1234.56), "EUR")));
+        assert_eq!(parse("1,234.56 EUR"), Ok(amount(dec!(1234.56), "EUR")));
+        assert_eq!(parse("1.234.567 EUR"), Ok(amount(dec!(1234567), "EUR")));
+        assert_eq!(parse("0,125 EUR"), Ok(amount(dec!(0.125), "EUR")));
+        assert_eq!(parse("1.500 EUR"), Ok(amount(dec!(1.500), "EUR")));
+        assert!(
+            parse("10,500 EUR")
+                .unwrap_err()
+                .starts_with("ambiguous decimal mark of amount '10,500 EUR'")
+        );
+        assert!(parse("1,234.5,6 EUR").is_err());
+
+        // decimal mark of the journal
+        assert_eq!(
+            parse_amount("10,500 EUR", Some(',')),
+            Ok(amount(dec!(10.500), "EUR"))
+        );
+        assert_eq!(
+            parse_amount("10,500 EUR", Some('.')),
+            Ok(amount(dec!(10500), "EUR"))
+        );
+        assert_eq!(
+            parse_amount("1.234,56 EUR", Some(',')),
+            Ok(amount(dec!(1234.56), "EUR"))
+        );
+        assert!(parse_amount("1.234,56 EUR", Some('.')).is_err());
+    }
+
+    #[test]
+    fn convert_posting_values() {
+        let v = parse_posting_value(
+            "10 ACME {4.5 EUR, 2024-01-01, \"lot\"} @@ 50 EUR = 20 ACME",
+            None,
+        )
+            .unwrap(/*:test:*/);
+        assert_eq!(v.amount, Some(amount(dec!(10), "ACME")));
+        assert_eq!(v.cost, Some(amount(dec!(4.5), "EUR")));
+        assert_eq!(v.price, Some((amount(dec!(50), "EUR"), true)));
+        assert_eq!(v.assertion, Some(amount(dec!(20), "ACME")));
+
+        assert_eq!(
+            parse_posting_value("  ", None).unwrap(/*:test:*/),
+            PostingValue::default()
+        );
+        assert!(parse_posting_value("-10 ACME {}", None).is_err());
+        assert!(parse_posting_value("10 ACME {{45 EUR}}", None).is_err());
+        assert!(parse_posting_value(" = 10 EUR", None).is_err());
+    }
+
+    #[test]
+    fn convert_names() {
+        assert_eq!(
+            tackler_name("Assets:Bank Account:Jane's"),
+            "Assets:Bank·Account:Jane_s"
+        );
+        assert!(is_valid_name("Assets:Bank"));
+        assert!(!is_valid_name("1Assets"));
+        assert!(!is_valid_name("Assets::Bank"));
+    }
+}
@@ -0,0 +1,321 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Beancount journals
+//!
+//! Supported constructs are txns (with payee, narration, tags, links and metadata),
+//! postings (with cost, price and comment), `open`, `commodity`, `price`,
+//! `pushtag` and `poptag` directives. Flags are dropped, and links and metadata
+//! (except `uuid`) are converted into txn comments.
+use crate::convert::{
+    ConvPosting, ConvTxn, Conversion, parse_amount, parse_date, parse_posting_value,
+};
+use crate::tackler;
+use jiff::civil::Date;
+
+/// Token of line, with flag of quoted string
+type Token = (String, bool);
+
+/// Split line into tokens and comment (`; ...`)
+fn tokens(text: &str) -> (Vec<Token>, Option<String>) {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' => {
+                let comment = chars.collect::<String>().trim().to_string();
+                return (tokens, Some(comment).filter(|c| !c.is_empty()));
+            }
+            '"' => {
+                let mut s = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => s.extend(chars.next()),
+                        '"' => break,
+                        c => s.push(c),
+                    }
+                }
+                tokens.push((s, true));
+            }
+            c => {
+                let mut s = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    s.push(c);
+                }
+                tokens.push((s, false));
+            }
+        }
+    }
+    (tokens, None)
+}
+
+fn is_flag(token: &str) -> bool {
+    matches!(
+        token,
+        "txn" | "*" | "!" | "&" | "#" | "?" | "%" | "P" | "S" | "T" | "C" | "U" | "R" | "M"
+    )
+}
+
+/// Metadata `key: value`
+fn metadata(text: &str) -> Option<(&str, String)> {
+    let (key, value) = text.split_once(':')?;
+    let is_key = key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    is_key.then(|| (key, value.trim().trim_matches('"').to_string()))
+}
+
+/// Txn header: `DATE FLAG ["PAYEE"] ["NARRATION"] [#tag ...] [^link ...]`
+fn parse_header(line: usize, date: Date, toks: &[Token], comment: Option<String>) -> ConvTxn {
+    let mut txn = ConvTxn::new(line, date);
+
+    let mut strings = Vec::new();
+    for (token, is_quoted) in toks {
+        if *is_quoted {
+            strings.push(token.as_str());
+        } else if let Some(tag) = token.strip_prefix('#') {
+            txn.tags.push(tag.to_string());
+        } else if let Some(link) = token.strip_prefix('^') {
+            txn.comments.push(format!("link: {link}"));
+        }
+    }
+    txn.description = strings.join(" - ");
+    txn.comments.extend(comment);
+    txn
+}
+
+/// Beancount uses always `.` as decimal mark, and `,` as thousands separator
+const DECIMAL_MARK: Option<char> = Some('.');
+
+/// Posting: `[FLAG] ACCOUNT [VALUE] [; comment]`
+fn parse_posting(text: &str) -> Result<ConvPosting, String> {
+    let text = match text.split_once(char::is_whitespace) {
+        Some((flag, rest)) if is_flag(flag) => rest.trim_start(),
+        _ => text,
+    };
+    let (text, comment) = match text.split_once(';') {
+        Some((t, c)) => (t, Some(c.trim().to_string()).filter(|c| !c.is_empty())),
+        None => (text, None),
+    };
+    let (account, value) = text
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((text.trim(), ""));
+
+    Ok(ConvPosting {
+        account: account.to_string(),
+        value: parse_posting_value(value, DECIMAL_MARK)?,
+        comment,
+    })
+}
+
+#[derive(PartialEq)]
+enum State {
+    /// Top level of the journal
+    Top,
+    /// Inside of the txn
+    Txn,
+    /// Inside of the invalid txn
+    InvalidTxn,
+    /// Metadata of other directives
+    Directive,
+}
+
+fn finish_txn(txn: &mut Option<ConvTxn>, conv: &mut Conversion) {
+    if let Some(txn) = txn.take() {
+        conv.add_txn(txn);
+    }
+}
+
+fn directive(line: usize, date: Date, dir: &str, args: &[Token], conv: &mut Conversion) {
+    match (dir, args) {
+        ("open", [(account, _), rest @ ..]) => {
+            conv.add_account(line, account);
+            for (comms, is_quoted) in rest {
+                // currency constraints, but not booking method
+                if !is_quoted {
+                    for comm in comms.split(',').filter(|c| !c.is_empty()) {
+                        conv.add_commodity(line, comm);
+                    }
+                }
+            }
+        }
+        ("commodity", [(commodity, _), ..]) => conv.add_commodity(line, commodity),
+        ("price", [(commodity, _), price @ ..]) => {
+            let price = price.iter().map(|(t, _)| t.as_str()).collect::<Vec<_>>();
+            match parse_amount(&price.join(" "), DECIMAL_MARK) {
+                Ok(price) => conv.add_price(line, date, commodity, price),
+                Err(err) => conv.report(line, format!("{err} in price directive")),
+            }
+        }
+        _ => conv.report(line, format!("directive '{dir}' is not supported")),
+    }
+}
+
+pub(crate) fn convert(input: &str, conv: &mut Conversion) -> Result<(), tackler::Error> {
+    let mut state = State::Top;
+    let mut txn: Option<ConvTxn> = None;
+    let mut pushed_tags: Vec<String> = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line_nro = idx + 1;
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            finish_txn(&mut txn, conv);
+            state = State::Top;
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let text = line.trim_start();
+            match (&state, txn.as_mut()) {
+                (State::Txn, Some(t)) => {
+                    if let Some(comment) = text.strip_prefix(';') {
+                        t.comments.push(comment.trim().to_string());
+                    } else if let Some((key, value)) = metadata(text) {
+                        match (key, t.posts.is_empty()) {
+                            ("uuid", true) => t.uuid = Some(value),
+                            (_, true) => t.comments.push(format!("{key}: {value}")),
+                            (_, false) => {
+                                let msg = format!("posting metadata '{key}' is dropped");
+                                conv.report(line_nro, msg);
+                            }
+                        }
+                    } else {
+                        match parse_posting(text) {
+                            Ok(p) => t.posts.push(p),
+                            Err(err) => {
+                                conv.report(line_nro, format!("{err}, txn is skipped"));
+                                txn = None;
+                                state = State::InvalidTxn;
+                            }
+                        }
+                    }
+                }
+                (State::Top, _) if text.starts_with(';') => {}
+                (State::Top, _) => {
+                    let msg = format!("unexpected indented line '{text}'");
+                    return Err(format!("line {line_nro}: {msg}").into());
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        finish_txn(&mut txn, conv);
+        state = State::Top;
+        if line.starts_with([';', '*', '#']) {
+            continue;
+        }
+        let (toks, comment) = tokens(line);
+        let words: Vec<_> = toks.iter().map(|(t, _)| t.as_str()).collect();
+        let date = words.first().and_then(|d| parse_date(d));
+        match (date, &words[..]) {
+            (_, ["pushtag", tag, ..]) => pushed_tags.push(tag.trim_start_matches('#').to_string()),
+            (_, ["poptag", tag, ..]) => {
+                let tag = tag.trim_start_matches('#');
+                if let Some(pos) = pushed_tags.iter().rposition(|t| t == tag) {
+                    pushed_tags.remove(pos);
+                }
+            }
+            (Some(date), [_, flag, ..]) if is_flag(flag) => {
+                let mut t = parse_header(line_nro, date, &toks[2..], comment);
+                t.tags.splice(0..0, pushed_tags.iter().cloned());
+                txn = Some(t);
+                state = State::Txn;
+            }
+            (Some(date), [_, dir, ..]) => {
+                directive(line_nro, date, dir, &toks[2..], conv);
+                state = State::Directive;
+            }
+            (_, [keyword, ..]) => {
+                conv.report(line_nro, format!("'{keyword}' is not supported"));
+                state = State::Directive;
+            }
+            (_, []) => {}
+        }
+    }
+    finish_txn(&mut txn, conv);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::{Conversion, ConvertFormat, convert};
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn beancount_convert() {
+        let journal = indoc!(
+            r#"option "title" "Example"
+            * Accounts
+            2020-01-01 open Assets:Cash USD,EUR
+            2020-01-01 open Assets:Broker "FIFO"
+              note: "broker"
+            2020-01-01 open Expenses:Food
+            2020-01-01 commodity HOOL
+            2024-01-01 price HOOL 510.50 USD
+
+            pushtag #trip
+            2024-01-02 * "Cafe" "Coffee \"large\"" #food ^receipt-1 ; paid
+              uuid: "8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1"
+              category: "drinks"
+              Expenses:Food   4.50 USD ; with milk
+              Assets:Cash
+            poptag #trip
+
+            2024-01-03 txn "Buy"
+              Assets:Broker   10 HOOL {500.00 USD, 2024-01-03}
+                lot: "1"
+              Assets:Cash  -5,000.00 USD
+
+            2024-01-04 * "Sell"
+              Assets:Broker  -10 HOOL {}
+              Assets:Cash
+
+            2024-01-05 balance Assets:Cash  -5004.50 USD
+            "#
+        );
+        let mut conv = Conversion::default();
+        convert(ConvertFormat::Beancount, journal, &mut conv).unwrap(/*:test:*/);
+
+        let unsupported: Vec<_> = conv.unsupported.iter().map(|u| u.line).collect();
+        assert_eq!(unsupported, vec![1, 20, 24, 27]);
+        assert_eq!(conv.txn_count(), 2);
+
+        let files = conv.files();
+        assert_eq!(
+            files[0].1,
+            indoc!(
+                "|2024-01-02 'Cafe - Coffee \"large\"
+                 |   # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+                 |   # tags: trip, food
+                 |   ; link: receipt-1
+                 |   ; paid
+                 |   ; category: drinks
+                 |   Expenses:Food  4.50 USD ; with milk
+                 |   Assets:Cash
+                 |"
+            )
+            .strip_margin()
+        );
+        assert!(
+            files[1]
+                .1
+                .contains("Assets:Broker     10    HOOL {500.00 USD}")
+        );
+        assert_eq!(
+            files[3].1,
+            "permit-empty-commodity = false\n\ncommodities = [\n    \"EUR\",\n    \"HOOL\",\n    \"USD\",\n]\n"
+        );
+        assert_eq!(files[4].1, "tags = [\n    \"food\",\n    \"trip\",\n]\n");
+        assert_eq!(files[5].1, "P 2024-01-01 HOOL 510.50 USD\n");
+    }
+}
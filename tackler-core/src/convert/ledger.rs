@@ -0,0 +1,409 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Ledger and hledger journals
+//!
+//! Supported constructs are txns (with code, comments, metadata and tags),
+//! postings (with cost, price and balance assertion), and `account`, `commodity`,
+//! `decimal-mark` and `P` directives. Status marks and secondary dates are dropped.
+use crate::convert::{
+    ConvPosting, ConvTxn, Conversion, parse_amount, parse_date, parse_posting_value,
+};
+use crate::tackler;
+
+/// Split text and its comment (`; ...`)
+fn split_comment(text: &str) -> (&str, Option<&str>) {
+    match text.split_once(';') {
+        Some((t, c)) => (t, Some(c.trim()).filter(|c| !c.is_empty())),
+        None => (text, None),
+    }
+}
+
+/// Txn comment: tags (`:tag1:tag2:` or `tag:`), uuid (`uuid: ...`) or plain comment
+fn txn_note(txn: &mut ConvTxn, note: &str) {
+    let note = note.trim();
+    if note.len() > 2
+        && note.starts_with(':')
+        && note.ends_with(':')
+        && !note.contains(char::is_whitespace)
+    {
+        txn.tags.extend(
+            note.split(':')
+                .filter(|t| !t.is_empty())
+                .map(str::to_string),
+        );
+        return;
+    }
+    match note.split_once(':') {
+        Some((key, value)) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+            match (key, value.trim()) {
+                ("uuid", uuid) => txn.uuid = Some(uuid.to_string()),
+                (tag, "") => txn.tags.push(tag.to_string()),
+                _ => txn.comments.push(note.to_string()),
+            }
+        }
+        _ if note.is_empty() => {}
+        _ => txn.comments.push(note.to_string()),
+    }
+}
+
+fn strip_status(text: &str) -> &str {
+    text.strip_prefix(['*', '!']).unwrap_or(text).trim_start()
+}
+
+/// Txn header: `DATE[=DATE2] [*|!] [(CODE)] DESCRIPTION [; comment]`
+fn parse_header(line: usize, text: &str, conv: &mut Conversion) -> Option<ConvTxn> {
+    let (date_str, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let date_str = match date_str.split_once('=') {
+        Some((date, _)) => {
+            conv.report(line, "secondary date is not supported, it's dropped");
+            date
+        }
+        None => date_str,
+    };
+    let Some(date) = parse_date(date_str) else {
+        conv.report(
+            line,
+            format!("unsupported date '{date_str}', txn is skipped"),
+        );
+        return None;
+    };
+
+    let mut txn = ConvTxn::new(line, date);
+    let (rest, comment) = split_comment(strip_status(rest.trim_start()));
+    let rest = match rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+        Some((code, desc)) => {
+            txn.code = Some(code.to_string());
+            desc
+        }
+        None => rest,
+    };
+    txn.description = rest.trim().to_string();
+    if let Some(c) = comment {
+        txn_note(&mut txn, c);
+    }
+    Some(txn)
+}
+
+/// Posting: `[*|!] ACCOUNT  [VALUE] [; comment]`
+///
+/// Returns `None` for virtual postings, which are dropped.
+fn parse_posting(
+    line: usize,
+    text: &str,
+    decimal_mark: Option<char>,
+    conv: &mut Conversion,
+) -> Result<Option<ConvPosting>, String> {
+    let (text, comment) = split_comment(strip_status(text));
+    let (account, value) = match text.find("  ").or_else(|| text.find('\t')) {
+        Some(idx) => (text[..idx].trim(), &text[idx..]),
+        None => (text.trim(), ""),
+    };
+    if account.starts_with(['(', '[']) {
+        conv.report(line, format!("virtual posting '{account}' is dropped"));
+        return Ok(None);
+    }
+
+    Ok(Some(ConvPosting {
+        account: account.to_string(),
+        value: parse_posting_value(value, decimal_mark)?,
+        comment: comment.map(str::to_string),
+    }))
+}
+
+/// Price: `P DATE [TIME] COMMODITY AMOUNT`
+fn parse_price(line: usize, text: &str, decimal_mark: Option<char>, conv: &mut Conversion) {
+    let mut parts = text.split_whitespace().skip(1).peekable();
+    let date = parts.next().and_then(parse_date);
+    if parts.peek().is_some_and(|t| t.contains(':')) {
+        // time
+        parts.next();
+    }
+    let commodity = parts.next();
+    let price = parse_amount(&parts.collect::<Vec<_>>().join(" "), decimal_mark);
+    match (date, commodity, price) {
+        (Some(date), Some(commodity), Ok(price)) => {
+            conv.add_price(line, date, commodity.trim_matches('"'), price)
+        }
+        _ => conv.report(line, format!("unsupported price directive '{text}'")),
+    }
+}
+
+#[derive(PartialEq)]
+enum State {
+    /// Top level of the journal
+    Top,
+    /// Inside of the txn
+    Txn,
+    /// Inside of the invalid txn
+    InvalidTxn,
+    /// Indented sub-directives of other directives
+    Directive,
+    /// Inside of the comment block
+    Comment,
+}
+
+fn finish_txn(txn: &mut Option<ConvTxn>, conv: &mut Conversion) {
+    if let Some(txn) = txn.take() {
+        conv.add_txn(txn);
+    }
+}
+
+pub(crate) fn convert(input: &str, conv: &mut Conversion) -> Result<(), tackler::Error> {
+    let mut state = State::Top;
+    let mut txn: Option<ConvTxn> = None;
+    // decimal mark of amounts is guessed, until it's set by `decimal-mark` directive
+    let mut decimal_mark = None;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line_nro = idx + 1;
+        let line = line.trim_end();
+
+        if state == State::Comment {
+            if matches!(line.trim(), "end comment" | "end test") {
+                state = State::Top;
+            }
+            continue;
+        }
+        if line.is_empty() {
+            finish_txn(&mut txn, conv);
+            state = State::Top;
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let text = line.trim_start();
+            match (&state, txn.as_mut()) {
+                (State::Txn, Some(t)) => match text.strip_prefix(';') {
+                    Some(note) => match t.posts.last_mut() {
+                        Some(p) => {
+                            let note = note.trim();
+                            p.comment = Some(match &p.comment {
+                                Some(c) => format!("{c}; {note}"),
+                                None => note.to_string(),
+                            });
+                        }
+                        None => txn_note(t, note),
+                    },
+                    None => match parse_posting(line_nro, text, decimal_mark, conv) {
+                        Ok(Some(p)) => t.posts.push(p),
+                        Ok(None) => {}
+                        Err(err) => {
+                            conv.report(line_nro, format!("{err}, txn is skipped"));
+                            txn = None;
+                            state = State::InvalidTxn;
+                        }
+                    },
+                },
+                (State::Top, _) if text.starts_with(';') => {}
+                (State::Top, _) => {
+                    let msg = format!("unexpected indented line '{text}'");
+                    return Err(format!("line {line_nro}: {msg}").into());
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        finish_txn(&mut txn, conv);
+        state = State::Top;
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match keyword {
+            _ if line.starts_with([';', '#', '%', '|', '*']) => {}
+            _ if line.starts_with(|c: char| c.is_ascii_digit()) => {
+                match parse_header(line_nro, line, conv) {
+                    Some(t) => {
+                        txn = Some(t);
+                        state = State::Txn;
+                    }
+                    None => state = State::InvalidTxn,
+                }
+            }
+            "P" => parse_price(line_nro, line, decimal_mark, conv),
+            "account" => {
+                conv.add_account(line_nro, split_comment(rest).0.trim());
+                state = State::Directive;
+            }
+            "commodity" => {
+                let comm = split_comment(rest).0.trim();
+                match parse_amount(comm, decimal_mark) {
+                    // commodity with format, e.g. `commodity $1,000.00`
+                    Ok(amount) if !amount.comm.is_empty() => {
+                        conv.add_commodity(line_nro, &amount.comm)
+                    }
+                    _ => conv.add_commodity(line_nro, comm.trim_matches('"')),
+                }
+                state = State::Directive;
+            }
+            "decimal-mark" => match split_comment(rest).0.trim() {
+                mark @ ("." | ",") => decimal_mark = mark.chars().next(),
+                mark => conv.report(line_nro, format!("unsupported decimal mark '{mark}'")),
+            },
+            "comment" | "test" => state = State::Comment,
+            "~" => {
+                conv.report(line_nro, "periodic txn is not supported");
+                state = State::Directive;
+            }
+            "=" => {
+                conv.report(line_nro, "automated txn is not supported");
+                state = State::Directive;
+            }
+            "include" => {
+                let msg = format!("include is not supported, convert '{rest}' separately");
+                conv.report(line_nro, msg);
+            }
+            _ => {
+                conv.report(line_nro, format!("directive '{keyword}' is not supported"));
+                state = State::Directive;
+            }
+        }
+    }
+    finish_txn(&mut txn, conv);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::{Conversion, ConvertFormat, convert};
+    use indoc::indoc;
+    use std::path::PathBuf;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn ledger_convert() {
+        let journal = indoc!(
+            r#"; ledger journal
+            account Assets:Bank Account
+            commodity $1,000.00
+                format $1,000.00
+
+            P 2024/01/01 12:00:00 AAPL $180.50
+
+            2024/01/02=2024/01/03 * (42) Groceries  ; :food:home:
+                ; uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+                ; Payee: K-Market
+                Expenses:Food         $12.30  ; receipt
+                ; second line
+                (Budget:Food)         $-12.30
+                Assets:Bank Account
+
+            2024-01-05 ! Shares
+                Assets:Broker    10 AAPL {$180.50} @ $181 = 10 AAPL
+                Assets:Bank Account
+
+            ~ monthly
+                Expenses:Rent  $500
+                Assets:Bank Account
+
+            comment
+            this is not a txn
+            end comment
+
+            2024-01-06 Exchange
+                Assets:Cash  10 EUR
+                Assets:Bank Account  -11 USD
+
+            2024-01-07 Expression
+                Assets:Cash  ($10 * 2)
+                Assets:Bank Account
+
+            2024-01-08 Sell
+                Assets:Broker  -5 AAPL @@ $1,000.00
+                Assets:Bank Account
+
+            2024-01-09 Ambiguous
+                Assets:Cash  10,500 EUR
+                Assets:Bank Account
+            "#
+        );
+        let mut conv = Conversion::default();
+        convert(ConvertFormat::Ledger, journal, &mut conv).unwrap(/*:test:*/);
+
+        let unsupported: Vec<_> = conv.unsupported.iter().map(|u| u.line).collect();
+        assert_eq!(unsupported, vec![8, 13, 20, 28, 33, 41]);
+        assert_eq!(conv.txn_count(), 3);
+
+        let files = conv.files();
+        let (path, txn) = &files[0];
+        assert_eq!(path, &PathBuf::from("txns/2024/01/02/20240102-0001.txn"));
+        assert_eq!(
+            *txn,
+            indoc!(
+                "|2024-01-02 (42) 'Groceries
+                 |   # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+                 |   # tags: food, home
+                 |   ; Payee: K-Market
+                 |   Expenses:Food        12.30 $ ; receipt; second line
+                 |   Assets:Bank·Account
+                 |"
+            )
+            .strip_margin()
+        );
+        assert!(
+            files[1]
+                .1
+                .contains("Assets:Broker        10 AAPL {180.50 $} @ 181 $ == 10 AAPL")
+        );
+        assert!(
+            files[2]
+                .1
+                .contains("Assets:Broker        -5 AAPL = -1000.00 $")
+        );
+
+        let names: Vec<_> = files[3..].iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(
+            names,
+            ["accounts.toml", "commodities.toml", "tags.toml", "price.db"].map(PathBuf::from)
+        );
+        assert_eq!(
+            files[3].1,
+            "accounts = [\n    \"Assets:Bank·Account\",\n    \"Assets:Broker\",\n    \"Expenses:Food\",\n]\n"
+        );
+        assert_eq!(files[6].1, "P 2024-01-01 AAPL 180.50 $\n");
+    }
+    #[test]
+    fn ledger_convert_decimal_mark() {
+        let journal = indoc!(
+            "|2024-01-01 Guessed
+             |    Assets:Cash  1.234,56 EUR
+             |    Assets:Bank  -1,234.56 EUR
+             |
+             |decimal-mark ,
+             |
+             |2024-01-02 Decimal comma
+             |    Assets:Cash  10,500 EUR
+             |    Assets:Bank  -10,5 EUR
+             |"
+        )
+        .strip_margin();
+        let mut conv = Conversion::default();
+        convert(ConvertFormat::Ledger, &journal, &mut conv).unwrap(/*:test:*/);
+
+        assert!(conv.unsupported.is_empty(), "{:?}", conv.unsupported);
+        let files = conv.files();
+        assert_eq!(
+            files[0].1,
+            indoc!(
+                "|2024-01-01 'Guessed
+                 |   Assets:Cash   1234.56 EUR
+                 |   Assets:Bank  -1234.56 EUR
+                 |"
+            )
+            .strip_margin()
+        );
+        assert_eq!(
+            files[1].1,
+            indoc!(
+                "|2024-01-02 'Decimal comma
+                 |   Assets:Cash   10.500 EUR
+                 |   Assets:Bank  -10.5   EUR
+                 |"
+            )
+            .strip_margin()
+        );
+    }
+}
@@ -4,6 +4,7 @@
  */
 use crate::kernel::Settings;
 use crate::model::TxnSet;
+pub use beancount_exporter::BeancountExporter;
 pub use equity_exporter::EquityExporter;
 pub use equity_exporter::EquitySettings;
 use std::io;
@@ -12,10 +13,15 @@ use std::path::Path;
 use crate::config::ExportType;
 use crate::tackler;
 pub use identity_exporter::IdentityExporter;
+pub use ledger_exporter::LedgerExporter;
+pub use price_db_exporter::PriceDbExporter;
 use tackler_rs::create_output_file;
 
+mod beancount_exporter;
 mod equity_exporter;
 mod identity_exporter;
+mod ledger_exporter;
+mod price_db_exporter;
 
 pub trait Export {
     fn write_export<W: io::Write + ?Sized>(
@@ -58,6 +64,36 @@ pub fn write_exports<ProgW: io::Write + ?Sized>(
                     writeln!(p, "{:>21} : {}", "Identity Export", path)?;
                 }
             }
+            ExportType::Ledger => {
+                let ledger_exporter = LedgerExporter {};
+
+                let (mut out_writer, path) =
+                    create_output_file(output_dir, output_name, "ledger", "journal")?;
+                ledger_exporter.write_export(settings, &mut out_writer, txn_set)?;
+                if let Some(p) = prog_writer.as_mut() {
+                    writeln!(p, "{:>21} : {}", "Ledger Export", path)?;
+                }
+            }
+            ExportType::Beancount => {
+                let beancount_exporter = BeancountExporter {};
+
+                let (mut out_writer, path) =
+                    create_output_file(output_dir, output_name, "beancount", "beancount")?;
+                beancount_exporter.write_export(settings, &mut out_writer, txn_set)?;
+                if let Some(p) = prog_writer.as_mut() {
+                    writeln!(p, "{:>21} : {}", "Beancount Export", path)?;
+                }
+            }
+            ExportType::PriceDb => {
+                let price_db_exporter = PriceDbExporter {};
+
+                let (mut out_writer, path) =
+                    create_output_file(output_dir, output_name, "price", "db")?;
+                price_db_exporter.write_export(settings, &mut out_writer, txn_set)?;
+                if let Some(p) = prog_writer.as_mut() {
+                    writeln!(p, "{:>21} : {}", "Price DB Export", path)?;
+                }
+            }
         }
     }
 
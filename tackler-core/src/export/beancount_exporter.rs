@@ -0,0 +1,548 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::config::AccountType;
+use crate::export::Export;
+use crate::kernel::Settings;
+use crate::kernel::amount_format::AmountFormat;
+use crate::model::{Posting, Transaction, TxnAccount, TxnSet};
+use crate::tackler;
+use jiff::civil::Date;
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use tackler_api::txn_ts;
+
+/// Beancount journal
+///
+/// Journal has `open` directive for every used account and `commodity` directive
+/// for every used commodity (at the date of first use), `price` directives from
+/// the price database, and all txns.
+///
+/// Txn metadata is written as beancount metadata and tags:
+/// - `# uuid:` and `# location:` are `uuid: "..."` and `location: "..."`
+/// - txn code is `code: "..."`
+/// - key-value metadata `# key: value` is `key: "value"`
+/// - timestamp is `timestamp: "..."`, if the txn is not at the default time of the date
+/// - each tag is a beancount tag (`:` inside tag name is replaced by `.`)
+///
+/// Account and commodity names are converted to beancount syntax, and if the name
+/// has been changed, then the original name is kept as `name` metadata of
+/// the `open` or `commodity` directive. Beancount requires that top-level accounts
+/// are `Assets`, `Liabilities`, `Equity`, `Income` or `Expenses`, so accounts
+/// with account type are placed under the top-level account of their type
+/// (e.g. `Bank:Checking` with type `asset` is `Assets:Bank:Checking`).
+/// It's an error, if an account has other top-level account and no account type.
+///
+/// Closing postings of accounts with cost basis lots are lot reductions `{}`.
+///
+/// Beancount balance assertions include sub-accounts, so posting balance assertions
+/// (`==`) are written as posting metadata `assertion: "..."`.
+///
+/// Posting metadata is written as beancount posting metadata: posting tags are
+/// `tags: "..."`, posting date is `date: YYYY-MM-DD`, and other metadata
+/// is `key: "value"`.
+#[derive(Debug, Clone)]
+pub struct BeancountExporter {}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Account name components must start with capital letter or digit,
+/// and they can contain only letters, digits and dashes
+fn beancount_name(name: &str) -> String {
+    name.split(':')
+        .map(|component| {
+            component
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| match c {
+                    c if idx == 0 && c.is_lowercase() => c.to_uppercase().next().unwrap_or(c),
+                    c if idx == 0 && !c.is_alphanumeric() => 'X',
+                    c if c.is_alphanumeric() => c,
+                    _ => '-',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn beancount_root(acc_type: AccountType) -> &'static str {
+    match acc_type {
+        AccountType::Asset => "Assets",
+        AccountType::Liability => "Liabilities",
+        AccountType::Equity => "Equity",
+        AccountType::Income => "Income",
+        AccountType::Expense => "Expenses",
+    }
+}
+
+/// Beancount account name, under the top-level account of its account type
+fn beancount_account(name: &str, cfg: &Settings) -> Result<String, tackler::Error> {
+    const ROOTS: [&str; 5] = ["Assets", "Liabilities", "Equity", "Income", "Expenses"];
+
+    let account = beancount_name(name);
+    let top = account.split(':').next().unwrap_or_default();
+    let root = match cfg.get_account_type(name) {
+        Some(acc_type) => beancount_root(acc_type),
+        None if ROOTS.contains(&top) => top,
+        None => {
+            let msg = format!(
+                "Beancount export: account '{name}' has no account type, \
+                 and its top-level account is not one of: {}",
+                ROOTS.join(", ")
+            );
+            return Err(msg.into());
+        }
+    };
+    if top == root {
+        Ok(account)
+    } else {
+        Ok(format!("{root}:{account}"))
+    }
+}
+
+/// Commodities are capital letters, digits and `'._-`, starting with a letter
+/// and ending with a letter or a digit. Common currency symbols are converted
+/// to their ISO 4217 codes.
+fn beancount_commodity(name: &str) -> String {
+    match name {
+        "$" => return "USD".to_string(),
+        "€" => return "EUR".to_string(),
+        "£" => return "GBP".to_string(),
+        "¥" => return "JPY".to_string(),
+        _ => {}
+    }
+    let comm: String = name
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            c @ ('A'..='Z' | '0'..='9' | '\'' | '.' | '_' | '-') => c,
+            _ => '_',
+        })
+        .collect();
+    let comm = match comm.starts_with(|c: char| c.is_ascii_uppercase()) {
+        true => comm,
+        false => format!("C{comm}"),
+    };
+    match comm.ends_with(|c: char| c.is_ascii_alphanumeric()) && comm.len() > 1 {
+        true => comm,
+        false => format!("{comm}X"),
+    }
+}
+
+fn beancount_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| match c {
+            ':' => '.',
+            c if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '.') => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+/// Metadata keys are lowercase letter, and then letters, digits, dashes and underscores
+fn beancount_key(key: &str) -> String {
+    key.chars()
+        .enumerate()
+        .map(|(idx, c)| match c {
+            c if idx == 0 && c.is_ascii_uppercase() => c.to_ascii_lowercase(),
+            c if idx == 0 && !c.is_ascii_lowercase() => 'x',
+            c if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+fn beancount_posting_meta(indent: &str, p: &Posting) -> String {
+    let Some(meta) = &p.meta else {
+        return String::new();
+    };
+    let mut lines = String::new();
+    if let Some(tags) = &meta.tags {
+        let tags = tags.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        lines.push_str(&format!(
+            "\n{indent}{indent}tags: {}",
+            quote(&tags.join(", "))
+        ));
+    }
+    if let Some(date) = &meta.date {
+        lines.push_str(&format!("\n{indent}{indent}date: {date}"));
+    }
+    for (key, value) in &meta.values {
+        lines.push_str(&format!(
+            "\n{indent}{indent}{}: {}",
+            beancount_key(key),
+            quote(value)
+        ));
+    }
+    lines
+}
+
+fn beancount_amount(amount: impl std::fmt::Display, commodity: &str) -> String {
+    format!("{} {}", amount, beancount_commodity(commodity))
+}
+
+/// Accounts (and commodities) which have postings with cost basis
+type LotAccounts<'a> = HashSet<&'a TxnAccount>;
+
+fn beancount_posting(
+    indent: &str,
+    p: &Posting,
+    lot_accounts: &LotAccounts<'_>,
+    cfg: &Settings,
+    amount_fmt: &AmountFormat,
+) -> Result<String, tackler::Error> {
+    let comment = p
+        .comment
+        .as_ref()
+        .map(|c| format!(" ; {c}"))
+        .unwrap_or_default();
+    let account = beancount_account(&p.acctn.atn.account, cfg)?;
+    if p.is_inferred {
+        return Ok(format!(
+            "{indent}{account}{comment}{}",
+            beancount_posting_meta(indent, p)
+        ));
+    }
+
+    let comm = &p.acctn.comm.name;
+    if comm.is_empty() {
+        let msg = format!(
+            "Beancount export: commodity is required, account '{}' has a posting without commodity",
+            p.acctn.atn.account
+        );
+        return Err(msg.into());
+    }
+    let cost = match &p.cost_basis {
+        Some(cb) => format!(
+            " {{{}}}",
+            beancount_amount(cb.unit_cost, &cb.commodity.name)
+        ),
+        None if p.amount.is_sign_negative() && lot_accounts.contains(&p.acctn) => " {}".to_string(),
+        None => String::new(),
+    };
+    let mut posting = format!(
+        "{indent}{account}  {}{cost}{}{comment}",
+        beancount_amount(amount_fmt.exact_amount(&p.amount, comm), comm),
+        p.closing_price()
+            .map(|price| match p.is_total_amount {
+                true => format!(
+                    " @@ {}",
+                    beancount_amount(price.abs(), &p.txn_commodity.name)
+                ),
+                false => format!(" @ {}", beancount_amount(price, &p.txn_commodity.name)),
+            })
+            .unwrap_or_default(),
+    );
+    if let Some(ba) = p.balance_assertion {
+        posting.push_str(&format!(
+            "\n{indent}{indent}assertion: {}",
+            quote(&beancount_amount(amount_fmt.exact_amount(&ba, comm), comm))
+        ));
+    }
+    posting.push_str(&beancount_posting_meta(indent, p));
+    Ok(posting)
+}
+
+fn beancount_txn(
+    txn: &Transaction,
+    lot_accounts: &LotAccounts<'_>,
+    cfg: &Settings,
+    amount_fmt: &AmountFormat,
+) -> Result<String, tackler::Error> {
+    let indent = "  ";
+    let header = &txn.header;
+    let ts = &header.timestamp;
+
+    let mut lines = Vec::new();
+    lines.push(format!(
+        "{} * {}{}",
+        ts.date(),
+        quote(header.description.as_deref().unwrap_or_default()),
+        header
+            .tags
+            .iter()
+            .flatten()
+            .map(|t| format!(" #{}", beancount_tag(t)))
+            .collect::<String>(),
+    ));
+
+    if let Some(uuid) = &header.uuid {
+        lines.push(format!("{indent}uuid: {}", quote(&uuid.to_string())));
+    }
+    if let Some(geo) = &header.location {
+        lines.push(format!("{indent}location: {}", quote(&geo.to_string())));
+    }
+    if let Some(code) = &header.code {
+        lines.push(format!("{indent}code: {}", quote(code)));
+    }
+    for (key, value) in header.meta.iter().flatten() {
+        lines.push(format!("{indent}{}: {}", beancount_key(key), quote(value)));
+    }
+    let date_ts = cfg.get_offset_date(ts.date())?;
+    if date_ts.timestamp() != ts.timestamp() || date_ts.offset() != ts.offset() {
+        lines.push(format!(
+            "{indent}timestamp: {}",
+            quote(&txn_ts::rfc_3339(ts))
+        ));
+    }
+    for comment in header.comments.iter().flatten() {
+        lines.push(format!("{indent}; {comment}"));
+    }
+    for p in &txn.posts {
+        lines.push(beancount_posting(indent, p, lot_accounts, cfg, amount_fmt)?);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Beancount name with the date of first use and the original name
+type FirstUse<'a> = BTreeMap<String, (Date, &'a str)>;
+
+/// Different names could be converted into the same beancount name
+/// (e.g. `€` and `EUR`), so the changed original name is kept.
+fn first_use<'a>(map: &mut FirstUse<'a>, bc_name: String, name: &'a str, date: Date) {
+    let (first, orig) = map.entry(bc_name.clone()).or_insert((date, name));
+    *first = (*first).min(date);
+    if name != bc_name {
+        *orig = name;
+    }
+}
+
+/// Directive with the original name as metadata, if the name is changed
+fn name_directive(date: Date, directive: &str, name: &str, bc_name: &str) -> String {
+    match name == bc_name {
+        true => format!("{date} {directive} {bc_name}"),
+        false => format!("{date} {directive} {bc_name}\n  name: {}", quote(name)),
+    }
+}
+
+impl Export for BeancountExporter {
+    fn write_export<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let mut accounts = FirstUse::new();
+        let mut commodities = FirstUse::new();
+        let mut lot_accounts = LotAccounts::new();
+        for txn in &txn_data.txns {
+            let date = txn.header.timestamp.date();
+            for p in &txn.posts {
+                let account = p.acctn.atn.account.as_str();
+                first_use(
+                    &mut accounts,
+                    beancount_account(account, cfg)?,
+                    account,
+                    date,
+                );
+                if p.cost_basis.is_some() {
+                    lot_accounts.insert(&p.acctn);
+                }
+                for comm in [&p.acctn.comm, &p.txn_commodity]
+                    .into_iter()
+                    .chain(p.cost_basis.as_ref().map(|cb| &cb.commodity))
+                    .filter(|c| c.is_any())
+                {
+                    let bc_comm = beancount_commodity(&comm.name);
+                    first_use(&mut commodities, bc_comm, &comm.name, date);
+                }
+            }
+        }
+        let price_db = cfg.price.get_price_db(&txn_data.all_txns);
+        for price in price_db.iter() {
+            let date = price.timestamp.date();
+            for comm in [&price.base_commodity, &price.eq_commodity] {
+                let bc_comm = beancount_commodity(&comm.name);
+                first_use(&mut commodities, bc_comm, &comm.name, date);
+            }
+        }
+
+        for (account, (date, name)) in &accounts {
+            writeln!(writer, "{}", name_directive(*date, "open", name, account))?;
+        }
+        writeln!(writer)?;
+        for (comm, (date, name)) in &commodities {
+            writeln!(writer, "{}", name_directive(*date, "commodity", name, comm))?;
+        }
+        writeln!(writer)?;
+        if !price_db.is_empty() {
+            for price in price_db.iter() {
+                writeln!(
+                    writer,
+                    "{} price {} {}",
+                    price.timestamp.date(),
+                    beancount_commodity(&price.base_commodity.name),
+                    beancount_amount(price.eq_amount, &price.eq_commodity.name)
+                )?;
+            }
+            writeln!(writer)?;
+        }
+        let amount_fmt = cfg.get_amount_format();
+        for txn in &txn_data.txns {
+            writeln!(
+                writer,
+                "{}\n",
+                beancount_txn(txn, &lot_accounts, cfg, &amount_fmt)?
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn beancount_names() {
+        assert_eq!(beancount_name("Assets:bank_a:€"), "Assets:Bank-a:X");
+        assert_eq!(beancount_commodity("$"), "USD");
+        assert_eq!(beancount_commodity("acme_a"), "ACME_A");
+        assert_eq!(beancount_commodity("h2o"), "H2O");
+        assert_eq!(beancount_commodity("°C"), "C_C");
+        assert_eq!(beancount_commodity("x"), "XX");
+    }
+
+    #[test]
+    fn beancount_export() {
+        let journal = indoc!(
+            "|2024-01-02 (#42) 'Shares \"A\"
+             | # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+             | # location: geo:60.17,24.94
+             | # tags: invest:stocks, trip
+             | # Invoice: 2024-117
+             | ; bought
+             | Assets:Broker  10 ACME_A {4.5 EUR} @ 4.6 EUR ; lot 1
+             | Assets:Cash
+             |
+             |2024-01-03T10:00:00+02:00 'Sell
+             | Assets:Cash  40 EUR
+             | Assets:Broker  -10 ACME_A = -40 EUR
+             |
+             |2024-01-04 'Balance
+             | Expenses:food  5 € == 5 €
+             |  # tags: vat:deductible
+             |  # receipt: R-1
+             | Assets:Cash  -5 €
+             |  # date: 2024-01-05
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut out = Vec::new();
+        BeancountExporter {}
+            .write_export(&settings, &mut out, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let expected = indoc!(
+            r##"|2024-01-02 open Assets:Broker
+               |2024-01-02 open Assets:Cash
+               |2024-01-04 open Expenses:Food
+               |  name: "Expenses:food"
+               |
+               |2024-01-02 commodity ACME_A
+               |2024-01-02 commodity EUR
+               |  name: "€"
+               |
+               |2024-01-02 * "Shares \"A\"" #invest.stocks #trip
+               |  uuid: "8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1"
+               |  location: "geo:60.17,24.94"
+               |  code: "#42"
+               |  invoice: "2024-117"
+               |  ; bought
+               |  Assets:Broker  10 ACME_A {4.5 EUR} @ 4.6 EUR ; lot 1
+               |  Assets:Cash
+               |
+               |2024-01-03 * "Sell"
+               |  timestamp: "2024-01-03T10:00:00+02:00"
+               |  Assets:Cash  40 EUR
+               |  Assets:Broker  -10 ACME_A {} @@ 40 EUR
+               |
+               |2024-01-04 * "Balance"
+               |  Expenses:Food  5 EUR
+               |    assertion: "5 EUR"
+               |    tags: "vat:deductible"
+               |    receipt: "R-1"
+               |  Assets:Cash  -5 EUR
+               |    date: 2024-01-05
+               |
+               |"##
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(out).unwrap(/*:test:*/), expected);
+    }
+
+    #[test]
+    fn beancount_account_types() {
+        let journal = indoc!(
+            "|2024-01-02 'Salary
+             | Bank:Checking  100 EUR
+             | Job:Salary
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        settings.set_account_types(&[("Bank", AccountType::Asset), ("Job", AccountType::Income)]);
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut out = Vec::new();
+        BeancountExporter {}
+            .write_export(&settings, &mut out, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let expected = indoc!(
+            r#"|2024-01-02 open Assets:Bank:Checking
+               |  name: "Bank:Checking"
+               |2024-01-02 open Income:Job:Salary
+               |  name: "Job:Salary"
+               |
+               |2024-01-02 commodity EUR
+               |
+               |2024-01-02 * "Salary"
+               |  Assets:Bank:Checking  100 EUR
+               |  Income:Job:Salary
+               |
+               |"#
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(out).unwrap(/*:test:*/), expected);
+    }
+
+    #[test]
+    fn beancount_account_without_type() {
+        let journal = indoc!(
+            "|2024-01-02 'Salary
+             | Bank:Checking  100 EUR
+             | Income:Salary
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut out = Vec::new();
+        let res = BeancountExporter {}.write_export(&settings, &mut out, &txn_set);
+        assert!(res.is_err());
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .contains("account 'Bank:Checking' has no account type")
+        );
+    }
+}
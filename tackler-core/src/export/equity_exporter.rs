@@ -3,19 +3,23 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use crate::config::{AccountType, EquityMode};
 use crate::export::Export;
 use crate::kernel::Settings;
 use crate::kernel::balance::Balance;
+use crate::kernel::hash::{Hash, fingerprint_uuid};
 use crate::kernel::price_lookup::PriceLookupCtx;
 use crate::kernel::report_item_selector::{
     BalanceNonZeroByAccountSelector, BalanceNonZeroSelector, BalanceSelector,
 };
-use crate::model::{Transaction, TxnSet};
+use crate::model::txn_data::calc_txn_checksum;
+use crate::model::{Transaction, TxnRefs, TxnSet};
 use crate::tackler;
 use itertools::Itertools;
+use jiff::Zoned;
 use rust_decimal::Decimal;
 use std::io;
-use tackler_api::metadata::items::{AccountSelectorChecksum, Text};
+use tackler_api::metadata::items::{AccountSelectorChecksum, MetadataItem, Text, TxnSetChecksum};
 use tackler_api::txn_ts::rfc_3339;
 use uuid::Uuid;
 
@@ -23,6 +27,11 @@ use uuid::Uuid;
 pub struct EquitySettings {
     pub eqa: Option<String>,
     pub ras: Vec<String>,
+    pub mode: EquityMode,
+    /// Target account of closing txns
+    pub retained_earnings: Option<String>,
+    /// Closing txns are made with txns up to and including the cutoff
+    pub cutoff: Option<Zoned>,
 }
 
 impl EquitySettings {
@@ -30,6 +39,9 @@ impl EquitySettings {
         let bs = EquitySettings {
             eqa: Some(settings.export.equity.equity_account.clone()),
             ras: settings.get_equity_ras(),
+            mode: settings.export.equity.mode,
+            retained_earnings: settings.export.equity.retained_earnings.clone(),
+            cutoff: settings.get_equity_cutoff(),
         };
         Ok(bs)
     }
@@ -54,8 +66,8 @@ impl EquityExporter {
     }
 }
 
-impl Export for EquityExporter {
-    fn write_export<W: io::Write + ?Sized>(
+impl EquityExporter {
+    fn write_balance_txns<W: io::Write + ?Sized>(
         &self,
         cfg: &Settings,
         writer: &mut W,
@@ -78,6 +90,7 @@ impl Export for EquityExporter {
 
         let eq_txn_indent = "   ";
         let equity_account = "Equity:Default·Account".to_string();
+        let amount_fmt = cfg.get_amount_format();
 
         let hdr_str = |last_txn: Option<&&Transaction>, c: &String| -> String {
             let comm_str = || -> String {
@@ -127,9 +140,9 @@ impl Export for EquityExporter {
                 let dsum: Decimal = btns.clone().into_iter().map(|b| b.account_sum).sum();
                 let bal_posting = {
                     let value = if c.is_empty() {
-                        format!("{}", -dsum)
+                        amount_fmt.exact_amount(&-dsum, c)
                     } else {
-                        format!("{} {}", -dsum, c)
+                        format!("{} {}", amount_fmt.exact_amount(&-dsum, c), c)
                     };
                     let ea = match &self.export_settings.eqa {
                         Some(eqa) => eqa,
@@ -148,7 +161,7 @@ impl Export for EquityExporter {
                             "{}{}  {}{}",
                             eq_txn_indent,
                             b.acctn.atn.account,
-                            b.account_sum,
+                            amount_fmt.exact_amount(&b.account_sum, &comm.name),
                             match comm.is_any() {
                                 true => { format!(" {}", comm.name) },
                                 false => String::new(),
@@ -199,3 +212,318 @@ impl Export for EquityExporter {
         Ok(())
     }
 }
+
+impl EquityExporter {
+    /// Closing txns of income and expense accounts
+    ///
+    /// There is one closing txn per commodity, and it zeroes out balances
+    /// of income and expense accounts into retained earnings account.
+    /// Closing txns are deterministic: the UUID of txn is derived
+    /// from the content of txn and the checksum of closed txns.
+    /// The checksum is calculated from the content of closed txns,
+    /// so it's the same with and without audit mode, and txns don't need UUIDs.
+    fn write_closing_txns<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let Some(re_account) = &self.export_settings.retained_earnings else {
+            let msg = "Equity export: there is no retained earnings account for closing txns";
+            return Err(msg.into());
+        };
+
+        let closed_txns: TxnRefs<'_> = match &self.export_settings.cutoff {
+            Some(cutoff) => txn_data
+                .txns
+                .iter()
+                .filter(|txn| txn.header.timestamp <= *cutoff)
+                .copied()
+                .collect(),
+            None => txn_data.txns.clone(),
+        };
+        let Some(last_txn) = closed_txns.last() else {
+            return Ok(());
+        };
+        let ts = match &self.export_settings.cutoff {
+            Some(cutoff) => cutoff.clone(),
+            None => last_txn.header.timestamp.clone(),
+        };
+
+        let bal_acc_sel = self.get_acc_selector()?;
+        let bal = Balance::from_iter(
+            "",
+            &closed_txns,
+            &PriceLookupCtx::default(),
+            bal_acc_sel.as_ref(),
+            cfg,
+        )?;
+
+        let btns: Vec<_> = bal
+            .bal
+            .iter()
+            .filter(|btn| !btn.account_sum.is_zero())
+            .filter(|btn| {
+                matches!(
+                    cfg.get_account_type(&btn.acctn.atn.account),
+                    Some(AccountType::Income | AccountType::Expense)
+                )
+            })
+            .collect();
+        if btns.is_empty() {
+            if !bal.is_empty() {
+                let msg = "Equity export: there are no income or expense accounts to close \
+                           (account types are defined in Chart of Accounts)";
+                return Err(msg.into());
+            }
+            return Ok(());
+        }
+
+        let closed_checksum = Hash::default().checksum(
+            &closed_txns
+                .iter()
+                .map(|txn| txn.to_string())
+                .collect::<Vec<_>>(),
+            b"\n",
+        )?;
+        let txn_set_checksum = match cfg.get_hash() {
+            Some(hash) => Some(TxnSetChecksum {
+                size: closed_txns.len(),
+                hash: calc_txn_checksum(&closed_txns, None, &hash)?,
+            }),
+            None => None,
+        };
+        let acc_sel_checksum = match cfg.get_hash() {
+            Some(hash) => Some(AccountSelectorChecksum {
+                hash: bal_acc_sel.checksum(hash)?,
+            }),
+            None => None,
+        };
+
+        let indent = "   ";
+        let tz = cfg.report.report_tz.clone();
+        let amount_fmt = cfg.get_amount_format();
+
+        let mut metadata = Vec::<String>::new();
+        if let Some(md) = &txn_data.metadata {
+            // txn set checksum is replaced with the checksum of closed txns
+            for mdi in md
+                .items
+                .iter()
+                .filter(|mdi| !matches!(mdi, MetadataItem::TxnSetChecksum(_)))
+            {
+                metadata.extend(mdi.text(tz.clone()));
+                metadata.push(String::default());
+            }
+        }
+        if let Some(tsc) = &txn_set_checksum {
+            metadata.extend(tsc.text(tz.clone()));
+            metadata.push(String::default());
+        }
+        if let Some(asc) = &acc_sel_checksum {
+            metadata.extend(asc.text(tz.clone()));
+            metadata.push(String::default());
+        }
+
+        for (c, bs) in &btns.into_iter().chunk_by(|btn| &btn.acctn.comm) {
+            let btns: Vec<_> = bs.collect();
+            let comm_str = |value: Decimal| -> String {
+                let value = amount_fmt.exact_amount(&value, &c.name);
+                match c.is_any() {
+                    true => format!("{} {}", value, c.name),
+                    false => value,
+                }
+            };
+
+            let mut postings: Vec<String> = btns
+                .iter()
+                .map(|b| {
+                    format!(
+                        "{}{}  {}",
+                        indent,
+                        b.acctn.atn.account,
+                        comm_str(-b.account_sum)
+                    )
+                })
+                .collect();
+            let dsum: Decimal = btns.iter().map(|b| b.account_sum).sum();
+            if !dsum.is_zero() {
+                postings.push(format!("{}{}  {}", indent, re_account, comm_str(dsum)));
+            }
+
+            let fingerprint = format!(
+                "closing|{}|{}|{}|{}",
+                rfc_3339(&ts),
+                c.name,
+                postings.join("|"),
+                closed_checksum.value
+            );
+            let uuid = fingerprint_uuid(&fingerprint);
+
+            writeln!(
+                writer,
+                "{} 'Closing{}",
+                rfc_3339(&ts),
+                match c.is_any() {
+                    true => format!(" for {}", c.name),
+                    false => String::new(),
+                }
+            )?;
+            writeln!(writer, "{}# uuid: {}", indent, uuid)?;
+            for m in &metadata {
+                writeln!(writer, "{}; {}", indent, m)?;
+            }
+            for p in postings {
+                writeln!(writer, "{}", p)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Export for EquityExporter {
+    fn write_export<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        match self.export_settings.mode {
+            EquityMode::Balance => self.write_balance_txns(cfg, writer, txn_data),
+            EquityMode::Closing => self.write_closing_txns(cfg, writer, txn_data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn closing_txns() {
+        let journal = indoc!(
+            "|2024-06-01 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-06-02 'rent
+             | Expenses:Rent  1200 EUR
+             | Assets:Bank
+             |
+             |2025-01-15 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        settings.set_account_types(&[
+            ("Income", AccountType::Income),
+            ("Expenses", AccountType::Expense),
+        ]);
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let exporter = EquityExporter {
+            export_settings: EquitySettings {
+                eqa: None,
+                ras: vec![],
+                mode: EquityMode::Closing,
+                retained_earnings: Some("Equity:Retained".to_string()),
+                cutoff: Some(settings.parse_timestamp("2024-12-31T23:59:59Z").unwrap(/*:test:*/)),
+            },
+        };
+        let export = |exporter: &EquityExporter| {
+            let mut buf = Vec::new();
+            exporter
+                .write_export(&settings, &mut buf, &txn_set)
+                .unwrap(/*:test:*/);
+            String::from_utf8(buf).unwrap(/*:test:*/)
+        };
+
+        let closing = export(&exporter);
+        let lines: Vec<_> = closing.lines().collect();
+        assert_eq!(lines[0], "2024-12-31T23:59:59+00:00 'Closing for EUR");
+        assert!(lines[1].starts_with("   # uuid: "));
+        assert_eq!(
+            lines[2..],
+            [
+                "   Expenses:Rent  -1200 EUR",
+                "   Income:Salary  3000 EUR",
+                "   Equity:Retained  -1800 EUR",
+                "",
+            ]
+        );
+        // closing txns are deterministic
+        assert_eq!(closing, export(&exporter));
+        // and valid txns
+        parser::string_to_txns(&mut closing.as_str(), &mut settings).unwrap(/*:test:*/);
+    }
+
+    #[test]
+    fn closing_txns_uuid() {
+        let journal = indoc!(
+            "|2024-06-01 'salary
+             | # uuid: 2a9a7e4e-7a0f-4b7f-8d2a-3f7e5b4a6c01
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-06-02 'rent
+             | # uuid: 2a9a7e4e-7a0f-4b7f-8d2a-3f7e5b4a6c02
+             | Expenses:Rent  1200 EUR
+             | Assets:Bank
+             |"
+        )
+        .strip_margin();
+        let exporter = EquityExporter {
+            export_settings: EquitySettings {
+                eqa: None,
+                ras: vec![],
+                mode: EquityMode::Closing,
+                retained_earnings: Some("Equity:Retained".to_string()),
+                cutoff: None,
+            },
+        };
+        let export = |mut settings: Settings, journal: &str| {
+            settings.set_account_types(&[
+                ("Income", AccountType::Income),
+                ("Expenses", AccountType::Expense),
+            ]);
+            let txn_data = parser::string_to_txns(&mut &*journal, &mut settings).unwrap(/*:test:*/);
+            let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+            let mut buf = Vec::new();
+            exporter
+                .write_export(&settings, &mut buf, &txn_set)
+                .unwrap(/*:test:*/);
+            String::from_utf8(buf).unwrap(/*:test:*/)
+        };
+        let uuid_of = |closing: &str| {
+            closing
+                .lines()
+                .find(|l| l.starts_with("   # uuid: "))
+                .unwrap(/*:test:*/)
+                .to_string()
+        };
+
+        let closing = export(Settings::default(), &journal);
+        assert!(!closing.contains("Txn Set Checksum"));
+
+        let closing_audit = export(Settings::default_audit(), &journal);
+        assert!(closing_audit.contains("Txn Set Checksum"));
+
+        // uuid is the same with and without audit mode
+        assert_eq!(uuid_of(&closing), uuid_of(&closing_audit));
+
+        // uuid is by closed txns, not only by closing postings
+        let other = journal.replace("'rent", "'rent of June");
+        assert_ne!(
+            uuid_of(&closing),
+            uuid_of(&export(Settings::default(), &other))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::export::Export;
+use crate::kernel::Settings;
+use crate::kernel::amount_format::AmountFormat;
+use crate::model::{Posting, Transaction, TxnSet};
+use crate::tackler;
+use itertools::Itertools;
+use std::io;
+use tackler_api::txn_header::Tags;
+use tackler_api::txn_ts;
+
+/// Ledger and hledger compatible journal
+///
+/// Txn metadata is written as comments and tags:
+/// - `# uuid:` and `# location:` are `; uuid: ...` and `; location: ...`
+/// - key-value metadata `# key: value` is `; key: value`
+/// - tags are a tag comment `; :tag1:tag2:` (`:` inside tag name is replaced by `.`)
+/// - timestamp is `; timestamp: ...`, if the txn is not at the default time of the date
+///
+/// Closing position with unit price (`@`) is ledger unit price (`@`),
+/// and with total price (`=`) it is ledger total price (`@@`).
+/// Balance assertions (`==`) are ledger balance assertions (`=`).
+///
+/// Posting metadata is written as posting comments: tags are a tag comment,
+/// posting date is ledger posting date `; [YYYY-MM-DD]`, and other metadata
+/// is `; key: value`.
+#[derive(Debug, Clone)]
+pub struct LedgerExporter {}
+
+/// Commodity names with other than alphabetic or currency symbols must be quoted
+fn ledger_commodity(name: &str) -> String {
+    if name
+        .chars()
+        .all(|c| c.is_alphabetic() || (!c.is_ascii() && !c.is_whitespace() && !c.is_numeric()))
+    {
+        name.to_string()
+    } else {
+        format!("\"{name}\"")
+    }
+}
+
+fn ledger_amount(amount: impl std::fmt::Display, commodity: &str) -> String {
+    if commodity.is_empty() {
+        amount.to_string()
+    } else {
+        format!("{} {}", amount, ledger_commodity(commodity))
+    }
+}
+
+/// Tag comment `; :tag1:tag2:`
+fn ledger_tags(tags: &Tags) -> String {
+    format!(
+        "; :{}:",
+        tags.iter().map(|tag| tag.replace(':', ".")).join(":")
+    )
+}
+
+fn ledger_posting_meta(indent: &str, p: &Posting) -> String {
+    let Some(meta) = &p.meta else {
+        return String::new();
+    };
+    let mut lines = String::new();
+    if let Some(tags) = meta.tags.as_ref().filter(|t| !t.is_empty()) {
+        lines.push_str(&format!("\n{indent}{indent}{}", ledger_tags(tags)));
+    }
+    if let Some(date) = &meta.date {
+        lines.push_str(&format!("\n{indent}{indent}; [{date}]"));
+    }
+    for (key, value) in &meta.values {
+        lines.push_str(&format!("\n{indent}{indent}; {key}: {value}"));
+    }
+    lines
+}
+
+fn ledger_posting(indent: &str, p: &Posting, amount_fmt: &AmountFormat) -> String {
+    let comment = p
+        .comment
+        .as_ref()
+        .map(|c| format!("  ; {c}"))
+        .unwrap_or_default();
+    let meta = ledger_posting_meta(indent, p);
+    if p.is_inferred {
+        return format!("{indent}{}{comment}{meta}", p.acctn.atn);
+    }
+
+    let comm = &p.acctn.comm.name;
+    format!(
+        "{indent}{}  {}{}{}{}{comment}{meta}",
+        p.acctn.atn,
+        ledger_amount(amount_fmt.exact_amount(&p.amount, comm), comm),
+        p.cost_basis
+            .as_ref()
+            .map(|cb| format!(" {{{}}}", ledger_amount(cb.unit_cost, &cb.commodity.name)))
+            .unwrap_or_default(),
+        p.closing_price()
+            .map(|price| match p.is_total_amount {
+                true => format!(" @@ {}", ledger_amount(price.abs(), &p.txn_commodity.name)),
+                false => format!(" @ {}", ledger_amount(price, &p.txn_commodity.name)),
+            })
+            .unwrap_or_default(),
+        p.balance_assertion
+            .map(|ba| {
+                let ba = amount_fmt.exact_amount(&ba, comm);
+                format!(" = {}", ledger_amount(ba, comm))
+            })
+            .unwrap_or_default(),
+    )
+}
+
+fn ledger_txn(
+    txn: &Transaction,
+    cfg: &Settings,
+    amount_fmt: &AmountFormat,
+) -> Result<String, tackler::Error> {
+    let indent = "    ";
+    let header = &txn.header;
+    let ts = &header.timestamp;
+
+    let mut lines = Vec::new();
+    lines.push(format!(
+        "{}{}{}",
+        ts.date(),
+        header
+            .code
+            .as_ref()
+            .map(|c| format!(" ({c})"))
+            .unwrap_or_default(),
+        header
+            .description
+            .as_ref()
+            .map(|d| format!(" {d}"))
+            .unwrap_or_default(),
+    ));
+
+    let date_ts = cfg.get_offset_date(ts.date())?;
+    if date_ts.timestamp() != ts.timestamp() || date_ts.offset() != ts.offset() {
+        lines.push(format!("{indent}; timestamp: {}", txn_ts::rfc_3339(ts)));
+    }
+    if let Some(uuid) = &header.uuid {
+        lines.push(format!("{indent}; uuid: {uuid}"));
+    }
+    if let Some(geo) = &header.location {
+        lines.push(format!("{indent}; location: {geo}"));
+    }
+    if let Some(tags) = header.tags.as_ref().filter(|t| !t.is_empty()) {
+        lines.push(format!("{indent}{}", ledger_tags(tags)));
+    }
+    for (key, value) in header.meta.iter().flatten() {
+        lines.push(format!("{indent}; {key}: {value}"));
+    }
+    for comment in header.comments.iter().flatten() {
+        lines.push(format!("{indent}; {comment}"));
+    }
+    for p in &txn.posts {
+        lines.push(ledger_posting(indent, p, amount_fmt));
+    }
+    Ok(lines.join("\n"))
+}
+
+impl Export for LedgerExporter {
+    fn write_export<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let amount_fmt = cfg.get_amount_format();
+        for txn in &txn_data.txns {
+            writeln!(writer, "{}\n", ledger_txn(txn, cfg, &amount_fmt)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn ledger_export() {
+        let journal = indoc!(
+            "|2024-01-02 (#42) 'Shares
+             | # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+             | # tags: invest:stocks, trip
+             | # invoice: 2024-117
+             | ; bought
+             | Assets:Broker  10 ACME_A {4.5 EUR} @ 4.6 EUR ; lot 1
+             | Assets:Cash
+             |
+             |2024-01-03T10:00:00+02:00 'Sell
+             | Assets:Cash  40 EUR
+             | Assets:Broker  -10 ACME_A = -40 EUR
+             |
+             |2024-01-04 'Balance
+             | Expenses:Food  5 € == 5 €
+             |  # tags: vat:deductible
+             |  # receipt: R-1
+             | Assets:Cash  -5 €
+             |  # date: 2024-01-05
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut out = Vec::new();
+        LedgerExporter {}
+            .write_export(&settings, &mut out, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let expected = indoc!(
+            r#"|2024-01-02 (#42) Shares
+               |    ; uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+               |    ; :invest.stocks:trip:
+               |    ; invoice: 2024-117
+               |    ; bought
+               |    Assets:Broker  10 "ACME_A" {4.5 EUR} @ 4.6 EUR  ; lot 1
+               |    Assets:Cash
+               |
+               |2024-01-03 Sell
+               |    ; timestamp: 2024-01-03T10:00:00+02:00
+               |    Assets:Cash  40 EUR
+               |    Assets:Broker  -10 "ACME_A" @@ 40 EUR
+               |
+               |2024-01-04 Balance
+               |    Expenses:Food  5 € = 5 €
+               |        ; :vat.deductible:
+               |        ; receipt: R-1
+               |    Assets:Cash  -5 €
+               |        ; [2024-01-05]
+               |
+               |"#
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(out).unwrap(/*:test:*/), expected);
+    }
+
+    #[test]
+    fn ledger_export_tags() {
+        let journal = indoc!(
+            "|2024-01-02 'Tags
+             | # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+             | # location: geo:60.17,24.94
+             | # tags: a, b:c, d
+             | # invoice: 2024-117
+             | Expenses:Food  5 €
+             |  # tags: e, f
+             |  # receipt: R-1
+             | Assets:Cash
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut out = Vec::new();
+        LedgerExporter {}
+            .write_export(&settings, &mut out, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let expected = indoc!(
+            "|2024-01-02 Tags
+             |    ; uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+             |    ; location: geo:60.17,24.94
+             |    ; :a:b.c:d:
+             |    ; invoice: 2024-117
+             |    Expenses:Food  5 €
+             |        ; :e:f:
+             |        ; receipt: R-1
+             |    Assets:Cash
+             |
+             |"
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(out).unwrap(/*:test:*/), expected);
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Implicit prices of txns as price database
+//!
+//! Prices of closing positions (`@` and `=`) are written as price entries
+//! (`P` lines), which could be used as price database.
+use crate::export::Export;
+use crate::kernel::Settings;
+use crate::model::TxnSet;
+use crate::model::price_entry::implicit_prices;
+use crate::tackler;
+use std::io;
+use tackler_api::txn_ts::rfc_3339;
+
+#[derive(Debug, Clone)]
+pub struct PriceDbExporter {}
+
+impl Export for PriceDbExporter {
+    fn write_export<W: io::Write + ?Sized>(
+        &self,
+        _cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        for price in implicit_prices(txn_data.txns.iter().copied()) {
+            writeln!(
+                writer,
+                "P {} {} {} {}{}",
+                rfc_3339(&price.timestamp),
+                price.base_commodity.name,
+                price.eq_amount,
+                price.eq_commodity.name,
+                price
+                    .comments
+                    .as_ref()
+                    .map(|c| format!(" ; {c}"))
+                    .unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn implicit_prices_export() {
+        let journal = indoc!(
+            "|2024-01-02 'buy
+             | # uuid: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+             | Assets:Broker  10 ACME @ 120 USD
+             | Assets:Cash
+             |
+             |2024-01-03 'sell
+             | Assets:Broker  -4 ACME = -500 USD
+             | Assets:Cash
+             |
+             |2024-01-04 'no price
+             | Assets:Broker  1 ACME
+             | Equity:Acme
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let mut buf = Vec::new();
+        PriceDbExporter {}
+            .write_export(&settings, &mut buf, &txn_set)
+            .unwrap(/*:test:*/);
+        let price_db = String::from_utf8(buf).unwrap(/*:test:*/);
+        assert_eq!(
+            price_db,
+            indoc!(
+                "|P 2024-01-02T00:00:00+00:00 ACME 120 USD ; txn: 8f1d0c16-6ef4-4c3c-9a9f-33f5b5e2a0a1
+                 |P 2024-01-03T00:00:00+00:00 ACME 125 USD
+                 |"
+            )
+            .strip_margin()
+        );
+
+        // exported prices are valid price db
+        parser::pricedb_from_str(&mut price_db.as_str(), &mut settings).unwrap(/*:test:*/);
+    }
+}
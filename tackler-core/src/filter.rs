@@ -39,6 +39,7 @@ impl Predicate<Transaction> for TxnFilter {
             TxnFilter::TxnFilterBBoxLatLonAlt(tf) => tf.eval(txn),
             TxnFilter::TxnFilterTxnTags(tf) => tf.eval(txn),
             TxnFilter::TxnFilterTxnComments(tf) => tf.eval(txn),
+            TxnFilter::TxnFilterTxnMetadata(tf) => tf.eval(txn),
 
             // txn posting filters
             TxnFilter::TxnFilterPostingAccount(tf) => tf.eval(txn),
@@ -47,6 +48,7 @@ impl Predicate<Transaction> for TxnFilter {
             TxnFilter::TxnFilterPostingAmountLess(tf) => tf.eval(txn),
             TxnFilter::TxnFilterPostingAmountGreater(tf) => tf.eval(txn),
             TxnFilter::TxnFilterPostingCommodity(tf) => tf.eval(txn),
+            TxnFilter::TxnFilterPostingTags(tf) => tf.eval(txn),
         }
     }
 }
@@ -77,6 +79,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: None,
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -100,6 +103,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: None,
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -115,6 +119,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: None,
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -130,6 +135,7 @@ mod tests {
                 uuid: uuid.map(|uuid_str| Uuid::parse_str(uuid_str).unwrap(/*:test:*/)),
                 location: None,
                 tags: None,
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -145,6 +151,7 @@ mod tests {
                 uuid: None,
                 location: GeoPoint::from(lat, lon, alt).ok(),
                 tags: None,
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -160,6 +167,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: tags.map(|tags| tags.iter().map(|t| Arc::new(str::to_string(*t))).collect()),
+                meta: None,
                 comments: None,
             },
             posts: vec![],
@@ -175,6 +183,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: None,
+                meta: None,
                 comments: cmts
                     .map(|comments| comments.iter().map(|t| str::to_string(*t)).collect()),
             },
@@ -208,6 +217,7 @@ mod tests {
                 uuid: None,
                 location: None,
                 tags: None,
+                meta: None,
                 comments: None,
             },
             vec![e_p, a_p],
@@ -9,3 +9,4 @@ mod posting_amount_greater;
 mod posting_amount_less;
 mod posting_comment;
 mod posting_commodity;
+mod posting_tags;
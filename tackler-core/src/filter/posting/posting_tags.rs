@@ -0,0 +1,75 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::model::Transaction;
+use tackler_api::filters::posting::TxnFilterPostingTags;
+
+use crate::kernel::Predicate;
+
+impl Predicate<Transaction> for TxnFilterPostingTags {
+    fn eval(&self, txn: &Transaction) -> bool {
+        txn.posts.iter().any(|p| {
+            p.meta
+                .as_ref()
+                .and_then(|m| m.tags.as_ref())
+                .is_some_and(|tags| tags.iter().any(|t| self.regex.is_match(t)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_api::filters::TxnFilter;
+    use tackler_rs::IndocUtils;
+    use tackler_rs::regex::new_full_haystack_regex;
+
+    #[test]
+    // desc: filter by posting tags
+    fn posting_tags() {
+        let journal = indoc!(
+            "|2025-01-01 'no tags
+             | # tags: vat:deductible
+             | e:food  10
+             | a:cash
+             |
+             |2025-01-02 'split receipt
+             | e:office  80
+             |  # tags: vat:deductible, receipt
+             | e:food  20
+             | a:cash
+             |
+             |2025-01-03 'last posting
+             | e:food  10
+             | a:cash
+             |  # tags: vat:other
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let tf = TxnFilterPostingTags {
+            regex: new_full_haystack_regex("vat:.*").unwrap(/*:test:*/),
+        };
+        let cases = [false, true, true];
+        for (txn, result) in txn_set.txns.iter().zip(cases) {
+            assert_eq!(tf.eval(txn), result);
+        }
+
+        let filt = TxnFilter::TxnFilterPostingTags(TxnFilterPostingTags {
+            regex: new_full_haystack_regex("receipt").unwrap(/*:test:*/),
+        });
+        let cases = [false, true, false];
+        for (txn, result) in txn_set.txns.iter().zip(cases) {
+            assert_eq!(filt.eval(txn), result);
+        }
+    }
+}
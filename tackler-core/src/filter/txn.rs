@@ -8,6 +8,7 @@ mod txn_bbox_lat_lon_alt;
 mod txn_code;
 mod txn_comments;
 mod txn_description;
+mod txn_metadata;
 mod txn_tags;
 mod txn_ts_begin;
 mod txn_ts_end;
@@ -0,0 +1,74 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::model::Transaction;
+use tackler_api::filters::txn::TxnFilterTxnMetadata;
+
+use crate::kernel::Predicate;
+
+impl Predicate<Transaction> for TxnFilterTxnMetadata {
+    fn eval(&self, txn: &Transaction) -> bool {
+        txn.header.meta.as_ref().is_some_and(|meta| {
+            meta.iter()
+                .any(|(key, value)| self.key.is_match(key) && self.value.is_match(value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_api::filters::TxnFilter;
+    use tackler_rs::IndocUtils;
+    use tackler_rs::regex::new_full_haystack_regex;
+
+    #[test]
+    // desc: filter by txn key-value metadata
+    fn txn_metadata() {
+        let journal = indoc!(
+            "|2024-01-01 'no metadata
+             | e:office  10
+             | a:cash
+             |
+             |2024-01-02 'invoice
+             | # invoice: 2024-117
+             | # receipt: receipts/2024/117.pdf
+             | e:office  10
+             | a:cash
+             |
+             |2024-01-03 'receipt
+             | # receipt: receipts/2024/2024-118.pdf
+             | e:office  10
+             | a:cash
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let tf = TxnFilterTxnMetadata {
+            key: new_full_haystack_regex("invoice").unwrap(/*:test:*/),
+            value: new_full_haystack_regex("2024-.*").unwrap(/*:test:*/),
+        };
+        let cases = [false, true, false];
+        for (txn, result) in txn_set.txns.iter().zip(cases) {
+            assert_eq!(tf.eval(txn), result);
+        }
+
+        let filt = TxnFilter::TxnFilterTxnMetadata(TxnFilterTxnMetadata {
+            key: new_full_haystack_regex("invoice|receipt").unwrap(/*:test:*/),
+            value: new_full_haystack_regex(".*2024-.*").unwrap(/*:test:*/),
+        });
+        let cases = [false, true, true];
+        for (txn, result) in txn_set.txns.iter().zip(cases) {
+            assert_eq!(filt.eval(txn), result);
+        }
+    }
+}
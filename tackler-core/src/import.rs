@@ -0,0 +1,226 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Import of bank statements
+//!
+//! Supported statement formats are CSV, camt.053 and MT940, see [`ImportFormat`].
+//! Bank statement entries are converted into txns by [`ImportProfile`].
+//!
+//! Each txn gets an UUID, which is generated from the fingerprint of the entry
+//! (account, date, amount, commodity, description and reference), so the same
+//! entry produces always the same UUID. Entries which UUIDs are already in the
+//! journal are skipped.
+//!
+//! Generated txns are validated with the settings (chart of accounts,
+//! commodities and tags) before they are returned.
+use crate::kernel::Settings;
+use crate::kernel::hash::fingerprint_uuid;
+use crate::model::TxnData;
+use crate::parser;
+use crate::tackler;
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+mod camt053;
+mod csv_import;
+mod mt940;
+mod profile;
+
+pub use camt053::import_camt053;
+pub use csv_import::import_csv;
+pub use mt940::import_mt940;
+pub use profile::ImportProfile;
+
+/// Format of bank statement
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ImportFormat {
+    /// CSV, read by `[csv]` settings of the profile
+    #[default]
+    Csv,
+    /// ISO 20022 camt.053 (XML)
+    Camt053,
+    /// SWIFT MT940
+    Mt940,
+}
+#[rustfmt::skip]
+impl ImportFormat {
+    pub const CSV:     &'static str = "csv";
+    pub const CAMT053: &'static str = "camt053";
+    pub const MT940:   &'static str = "mt940";
+
+    pub fn from(f: &str) -> Result<Self, tackler::Error> {
+        match f {
+            ImportFormat::CSV => Ok(ImportFormat::Csv),
+            ImportFormat::CAMT053 => Ok(ImportFormat::Camt053),
+            ImportFormat::MT940 => Ok(ImportFormat::Mt940),
+            _ => Err(format!("Unknown import format {f}").into()),
+        }
+    }
+}
+
+/// Import bank statement of given format
+///
+/// `journal` is the existing journal, its txns are used to skip
+/// entries which are already imported.
+pub fn import_statement(
+    format: ImportFormat,
+    input: &str,
+    profile: &ImportProfile,
+    journal: &TxnData,
+    settings: &mut Settings,
+) -> Result<Import, tackler::Error> {
+    match format {
+        ImportFormat::Csv => import_csv(input, profile, journal, settings),
+        ImportFormat::Camt053 => import_camt053(input, profile, journal, settings),
+        ImportFormat::Mt940 => import_mt940(input, profile, journal, settings),
+    }
+}
+
+/// Entry of bank statement
+#[derive(Debug, Clone)]
+pub(crate) struct ImportEntry {
+    /// Line (or position) of the entry in the statement
+    pub(crate) line: usize,
+    pub(crate) date: Date,
+    pub(crate) code: Option<String>,
+    pub(crate) description: String,
+    pub(crate) amount: Decimal,
+    pub(crate) commodity: Option<String>,
+    /// Bank's reference or archive id of the entry
+    pub(crate) reference: Option<String>,
+}
+
+/// Result of import
+#[derive(Debug)]
+pub struct Import {
+    /// Imported txns as txn journal
+    pub journal: String,
+    /// Count of imported txns
+    pub imported: usize,
+    /// Count of skipped entries, which were already in the journal
+    pub skipped: usize,
+}
+
+fn valid_code_char(c: char) -> bool {
+    !matches!(
+        c,
+        ')' | '\'' | '(' | '[' | ']' | '{' | '}' | '<' | '>' | '\r' | '\n'
+    )
+}
+
+fn entry_to_txn(
+    entry: &ImportEntry,
+    commodity: &str,
+    uuid: Uuid,
+    profile: &ImportProfile,
+) -> String {
+    let description = entry.description.replace(['\r', '\n'], " ");
+    let (account, tags) = profile.select(description.trim());
+    let comm = if commodity.is_empty() {
+        String::new()
+    } else {
+        format!(" {commodity}")
+    };
+
+    format!(
+        "{}{} '{}\n # uuid: {}\n{} {}  {}{}\n {}\n",
+        entry.date,
+        entry
+            .code
+            .as_ref()
+            .map(|c| c
+                .chars()
+                .filter(|c| valid_code_char(*c))
+                .collect::<String>())
+            .filter(|c| !c.trim().is_empty())
+            .map(|c| format!(" ({})", c.trim()))
+            .unwrap_or_default(),
+        description.trim(),
+        uuid,
+        if tags.is_empty() {
+            String::new()
+        } else {
+            format!(" # tags: {}\n", tags.join(", "))
+        },
+        account,
+        -entry.amount,
+        comm,
+        profile.txn.account,
+    )
+}
+
+/// Convert statement entries into txn journal
+///
+/// Txns are sorted by date, and entries which are already in the journal are skipped.
+pub(crate) fn entries_to_journal(
+    mut entries: Vec<ImportEntry>,
+    profile: &ImportProfile,
+    journal: &TxnData,
+    settings: &mut Settings,
+) -> Result<Import, tackler::Error> {
+    let known_uuids: HashSet<Uuid> = journal
+        .get_all()?
+        .txns
+        .iter()
+        .filter_map(|txn| txn.header.uuid)
+        .collect();
+
+    entries.sort_by_key(|e| e.date);
+
+    // Identical entries (e.g. two similar purchases on the same day)
+    // are separated by their occurrence
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut txns = Vec::new();
+    let mut skipped = 0;
+    for entry in &entries {
+        if entry.amount.is_zero() {
+            let msg = format!(
+                "Import error at statement line {}: amount is zero",
+                entry.line
+            );
+            return Err(msg.into());
+        }
+        let commodity = entry
+            .commodity
+            .as_deref()
+            .or(profile.txn.commodity.as_deref())
+            .unwrap_or_default();
+
+        let fingerprint = format!(
+            "{}|{}|{}|{}|{}|{}",
+            profile.txn.account,
+            entry.date,
+            entry.amount.normalize(),
+            commodity,
+            entry.description.trim(),
+            entry.reference.as_deref().unwrap_or_default()
+        );
+        let n = occurrences.entry(fingerprint.clone()).or_insert(0);
+        *n += 1;
+        let uuid = fingerprint_uuid(&format!("{fingerprint}|{n}"));
+
+        if known_uuids.contains(&uuid) {
+            skipped += 1;
+            continue;
+        }
+
+        let txn = entry_to_txn(entry, commodity, uuid, profile);
+        match parser::format_journal(&txn, settings) {
+            Ok(txn) => txns.push(txn),
+            Err(err) => {
+                let msg = format!("Import error at statement line {}: {err}", entry.line);
+                return Err(msg.into());
+            }
+        }
+    }
+
+    Ok(Import {
+        journal: txns.join("\n"),
+        imported: txns.len(),
+        skipped,
+    })
+}
@@ -0,0 +1,225 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! ISO 20022 camt.053 (Bank to Customer Statement)
+//!
+//! Each entry (`Ntry`) of the statement is one txn:
+//! - date: booking date (`BookgDt`)
+//! - amount: `Amt` with `CdtDbtInd` (`DBIT` is negative), commodity is `Ccy` of `Amt`
+//! - code and reference: bank reference (`AcctSvcrRef`)
+//! - description: counterparty (creditor of debit, debtor of credit entry)
+//!   and unstructured remittance information, or additional entry information
+use crate::import::{Import, ImportEntry, ImportProfile, entries_to_journal};
+use crate::kernel::Settings;
+use crate::model::TxnData;
+use crate::tackler;
+use jiff::civil::Date;
+use roxmltree::{Document, Node};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn path<'a, 'i>(node: Node<'a, 'i>, names: &[&str]) -> Option<Node<'a, 'i>> {
+    names.iter().try_fold(node, |n, name| child(n, name))
+}
+
+fn text(node: Node<'_, '_>, names: &[&str]) -> Option<String> {
+    path(node, names)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn counterparty(tx_dtls: Node<'_, '_>, is_debit: bool) -> Option<String> {
+    let party = if is_debit { "Cdtr" } else { "Dbtr" };
+    // camt.053.001.02 has name directly under party, newer versions under `Pty`
+    text(tx_dtls, &["RltdPties", party, "Nm"])
+        .or_else(|| text(tx_dtls, &["RltdPties", party, "Pty", "Nm"]))
+}
+
+fn to_entry(doc: &Document<'_>, ntry: Node<'_, '_>) -> Result<ImportEntry, tackler::Error> {
+    let line = doc.text_pos_at(ntry.range().start).row as usize;
+
+    let date_str = text(ntry, &["BookgDt", "Dt"])
+        .or_else(|| text(ntry, &["BookgDt", "DtTm"]).map(|dt| dt.chars().take(10).collect()))
+        .ok_or("missing booking date (BookgDt)")?;
+    let date = Date::from_str(&date_str)
+        .map_err(|err| format!("invalid booking date '{date_str}': {err}"))?;
+
+    let amt = child(ntry, "Amt").ok_or("missing amount (Amt)")?;
+    let amt_str = amt.text().unwrap_or_default().trim();
+    let amount =
+        Decimal::from_str(amt_str).map_err(|err| format!("invalid amount '{amt_str}': {err}"))?;
+    let is_debit = match text(ntry, &["CdtDbtInd"]).as_deref() {
+        Some("DBIT") => true,
+        Some("CRDT") => false,
+        _ => return Err("missing or invalid credit/debit indicator (CdtDbtInd)".into()),
+    };
+
+    let tx_dtls = path(ntry, &["NtryDtls", "TxDtls"]);
+    let reference = text(ntry, &["AcctSvcrRef"])
+        .or_else(|| tx_dtls.and_then(|tx| text(tx, &["Refs", "AcctSvcrRef"])));
+
+    let counterparty = tx_dtls.and_then(|tx| counterparty(tx, is_debit));
+    let remittance = tx_dtls
+        .and_then(|tx| child(tx, "RmtInf"))
+        .map(|rmt| {
+            rmt.children()
+                .filter(|n| n.is_element() && n.tag_name().name() == "Ustrd")
+                .filter_map(|n| n.text())
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|r| !r.is_empty())
+        .or_else(|| text(ntry, &["AddtlNtryInf"]));
+
+    let description = match (counterparty, remittance) {
+        (Some(cp), Some(rmt)) => format!("{cp} - {rmt}"),
+        (Some(cp), None) => cp,
+        (None, Some(rmt)) => rmt,
+        (None, None) => String::new(),
+    };
+
+    Ok(ImportEntry {
+        line,
+        date,
+        code: reference.clone(),
+        description,
+        amount: if is_debit { -amount } else { amount },
+        commodity: amt.attribute("Ccy").map(str::to_string),
+        reference,
+    })
+}
+
+/// Import camt.053 bank statement
+///
+/// `journal` is the existing journal, its txns are used to skip
+/// entries which are already imported.
+pub fn import_camt053(
+    input: &str,
+    profile: &ImportProfile,
+    journal: &TxnData,
+    settings: &mut Settings,
+) -> Result<Import, tackler::Error> {
+    let doc = match Document::parse(input) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let msg = format!("camt.053 error: invalid XML: {err}");
+            return Err(msg.into());
+        }
+    };
+
+    let entries = doc
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Ntry")
+        .map(|ntry| {
+            to_entry(&doc, ntry).map_err(|err| {
+                let line = doc.text_pos_at(ntry.range().start).row;
+                format!("camt.053 error at line {line}: {err}").into()
+            })
+        })
+        .collect::<Result<Vec<_>, tackler::Error>>()?;
+
+    entries_to_journal(entries, profile, journal, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+
+    const PROFILE: &str = indoc!(
+        r#"[txn]
+        account = "Assets:Bank"
+        default-account = "Expenses:Unknown"
+
+        [[rules]]
+        description = "(?i).*market.*"
+        account = "Expenses:Food"
+        tags = [ "food" ]
+        "#
+    );
+
+    const CAMT053: &str = indoc!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+          <BkToCstmrStmt>
+            <Stmt>
+              <Ntry>
+                <Amt Ccy="EUR">12.30</Amt>
+                <CdtDbtInd>DBIT</CdtDbtInd>
+                <BookgDt><Dt>2024-01-02</Dt></BookgDt>
+                <AcctSvcrRef>REF-1</AcctSvcrRef>
+                <NtryDtls><TxDtls>
+                  <RltdPties><Cdtr><Nm>K-Market Helsinki</Nm></Cdtr></RltdPties>
+                  <RmtInf><Ustrd>Receipt 42</Ustrd></RmtInf>
+                </TxDtls></NtryDtls>
+              </Ntry>
+              <Ntry>
+                <Amt Ccy="EUR">1000</Amt>
+                <CdtDbtInd>CRDT</CdtDbtInd>
+                <BookgDt><DtTm>2024-01-01T10:00:00</DtTm></BookgDt>
+                <NtryDtls><TxDtls>
+                  <Refs><AcctSvcrRef>REF-2</AcctSvcrRef></Refs>
+                  <RltdPties><Dbtr><Pty><Nm>ACME Corp</Nm></Pty></Dbtr></RltdPties>
+                </TxDtls></NtryDtls>
+              </Ntry>
+            </Stmt>
+          </BkToCstmrStmt>
+        </Document>
+        "#
+    );
+
+    fn empty_journal() -> TxnData {
+        TxnData::from(None, Vec::new(), &None).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn camt053_import() {
+        let profile = ImportProfile::from_toml(PROFILE).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let import =
+            import_camt053(CAMT053, &profile, &empty_journal(), &mut settings).unwrap(/*:test:*/);
+        assert_eq!(import.imported, 2);
+
+        let txns: Vec<_> = import.journal.split("\n\n").collect();
+        assert!(txns[0].starts_with("2024-01-01 (REF-2) 'ACME Corp\n"));
+        assert!(txns[0].contains("   Expenses:Unknown  -1000 EUR\n"));
+        assert!(txns[1].starts_with("2024-01-02 (REF-1) 'K-Market Helsinki - Receipt 42\n"));
+        assert!(txns[1].contains("   # tags: food\n"));
+        assert!(txns[1].contains("   Expenses:Food  12.30 EUR\n"));
+
+        let journal = parser::string_to_txns(&mut import.journal.as_str(), &mut settings)
+            .unwrap(/*:test:*/);
+        let second = import_camt053(CAMT053, &profile, &journal, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(second.imported, 0);
+        assert_eq!(second.skipped, 2);
+    }
+
+    #[test]
+    fn camt053_errors() {
+        let profile = ImportProfile::from_toml(PROFILE).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let err = import_camt053("<Document>", &profile, &empty_journal(), &mut settings)
+            .unwrap_err(/*:test:*/);
+        assert!(err.to_string().starts_with("camt.053 error: invalid XML"));
+
+        let camt = "<Document>\n<Ntry><Amt Ccy=\"EUR\">1</Amt></Ntry></Document>";
+        let err =
+            import_camt053(camt, &profile, &empty_journal(), &mut settings).unwrap_err(/*:test:*/);
+        assert_eq!(
+            err.to_string(),
+            "camt.053 error at line 2: missing booking date (BookgDt)"
+        );
+    }
+}
@@ -0,0 +1,337 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::import::profile::{Column, CsvProfile};
+use crate::import::{Import, ImportEntry, ImportProfile, entries_to_journal};
+use crate::kernel::Settings;
+use crate::model::TxnData;
+use crate::tackler;
+use jiff::fmt::strtime;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// CSV record with its line number
+type Record = (usize, Vec<String>);
+
+/// Read CSV records
+///
+/// Fields could be quoted with `"`, and inside quoted field, `""` is a quote.
+/// Empty lines are skipped.
+fn read_records(
+    input: &str,
+    delimiter: char,
+    first_line: usize,
+) -> Result<Vec<Record>, tackler::Error> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut line = first_line;
+    let mut record_line = first_line;
+    let mut in_quotes = false;
+    let mut is_quoted = false;
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.trim().is_empty() && !is_quoted => {
+                field.clear();
+                in_quotes = true;
+                is_quoted = true;
+            }
+            '\n' if in_quotes => {
+                field.push(c);
+                line += 1;
+            }
+            '\r' if !in_quotes => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                if !(fields.len() == 1 && fields[0].trim().is_empty()) {
+                    records.push((record_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                is_quoted = false;
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter && !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                is_quoted = false;
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        let msg = format!("CSV error at line {record_line}: unterminated quoted field");
+        return Err(msg.into());
+    }
+    fields.push(field);
+    if !(fields.len() == 1 && fields[0].trim().is_empty()) {
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
+
+fn column_index(column: &Column, header: Option<&[String]>) -> Result<usize, tackler::Error> {
+    match column {
+        Column::Number(n) if *n > 0 => Ok(n - 1),
+        Column::Number(n) => {
+            let msg = format!("CSV import: invalid column number {n} (first column is 1)");
+            Err(msg.into())
+        }
+        Column::Name(name) => match header {
+            Some(header) => match header.iter().position(|h| h.trim() == name) {
+                Some(idx) => Ok(idx),
+                None => {
+                    let msg = format!("CSV import: column '{name}' is not found from the header");
+                    Err(msg.into())
+                }
+            },
+            None => {
+                let msg =
+                    format!("CSV import: column '{name}' is given by name, but there is no header");
+                Err(msg.into())
+            }
+        },
+    }
+}
+
+fn parse_amount(value: &str, csv: &CsvProfile) -> Result<Decimal, tackler::Error> {
+    let amount: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && Some(*c) != csv.thousands_separator)
+        .map(|c| match c {
+            c if c == csv.decimal_separator => '.',
+            '\u{2212}' => '-',
+            c => c,
+        })
+        .collect();
+    let amount = amount.strip_prefix('+').unwrap_or(&amount);
+
+    Decimal::from_str(amount).map_err(|err| format!("invalid amount '{value}': {err}").into())
+}
+
+struct CsvColumns {
+    date: usize,
+    description: usize,
+    amount: usize,
+    code: Option<usize>,
+    commodity: Option<usize>,
+    reference: Option<usize>,
+}
+
+fn to_entry(
+    (line, fields): &Record,
+    cols: &CsvColumns,
+    csv: &CsvProfile,
+) -> Result<ImportEntry, tackler::Error> {
+    let field = |idx: usize| match fields.get(idx) {
+        Some(f) => Ok(f.trim()),
+        None => Err(format!("missing column {}", idx + 1)),
+    };
+    let opt_field = |idx: Option<usize>| -> Result<Option<String>, String> {
+        match idx {
+            Some(idx) => Ok(Some(field(idx)?.to_string()).filter(|f| !f.is_empty())),
+            None => Ok(None),
+        }
+    };
+
+    let entry = || -> Result<ImportEntry, tackler::Error> {
+        let date_str = field(cols.date)?;
+        let date = match strtime::parse(&csv.date_format, date_str).and_then(|d| d.to_date()) {
+            Ok(date) => date,
+            Err(err) => {
+                let msg = format!("invalid date '{date_str}': {err}");
+                return Err(msg.into());
+            }
+        };
+        Ok(ImportEntry {
+            line: *line,
+            date,
+            code: opt_field(cols.code)?,
+            description: field(cols.description)?.to_string(),
+            amount: parse_amount(field(cols.amount)?, csv)?,
+            commodity: opt_field(cols.commodity)?,
+            reference: opt_field(cols.reference)?,
+        })
+    };
+    entry().map_err(|err| format!("CSV error at line {line}: {err}").into())
+}
+
+/// Import CSV bank statement
+///
+/// `journal` is the existing journal, its txns are used to skip
+/// entries which are already imported.
+pub fn import_csv(
+    input: &str,
+    profile: &ImportProfile,
+    journal: &TxnData,
+    settings: &mut Settings,
+) -> Result<Import, tackler::Error> {
+    let Some(csv) = &profile.csv else {
+        return Err("Import profile doesn't have CSV settings ([csv])".into());
+    };
+
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let data = input
+        .split_inclusive('\n')
+        .skip(csv.skip_lines)
+        .collect::<String>();
+    let mut records = read_records(&data, csv.delimiter, csv.skip_lines + 1)?.into_iter();
+
+    let header = if csv.header {
+        records.next().map(|(_, h)| h)
+    } else {
+        None
+    };
+    let header = header.as_deref();
+    let opt_index =
+        |col: &Option<Column>| col.as_ref().map(|c| column_index(c, header)).transpose();
+
+    let cols = CsvColumns {
+        date: column_index(&csv.columns.date, header)?,
+        description: column_index(&csv.columns.description, header)?,
+        amount: column_index(&csv.columns.amount, header)?,
+        code: opt_index(&csv.columns.code)?,
+        commodity: opt_index(&csv.columns.commodity)?,
+        reference: opt_index(&csv.columns.reference)?,
+    };
+
+    let entries = records
+        .map(|r| to_entry(&r, &cols, csv))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    entries_to_journal(entries, profile, journal, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    const PROFILE: &str = indoc!(
+        r#"|[txn]
+           |account = "Assets:Bank"
+           |commodity = "EUR"
+           |default-account = "Expenses:Unknown"
+           |
+           |[csv]
+           |delimiter = ";"
+           |skip-lines = 1
+           |date-format = "%d.%m.%Y"
+           |decimal-separator = ","
+           |thousands-separator = " "
+           |
+           |[csv.columns]
+           |date = "Date"
+           |description = "Message"
+           |amount = 3
+           |reference = "Archive"
+           |
+           |[[rules]]
+           |description = "(?i).*market.*"
+           |account = "Expenses:Food"
+           |tags = [ "food" ]
+           |"#
+    );
+
+    const CSV: &str = indoc!(
+        r#"|Statement of Assets:Bank
+           |Date;Message;Amount;Archive
+           |02.01.2024;"K-Market; Helsinki";-12,30;A1
+           |01.01.2024;Salary;1 000,00;A2
+           |
+           |02.01.2024;"K-Market; Helsinki";-12,30;A3
+           |"#
+    );
+
+    fn empty_journal() -> TxnData {
+        TxnData::from(None, Vec::new(), &None).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn csv_read_records() {
+        let records = read_records("a,\"b,\"\"c\"\"\"\r\n\n\"d\ne\",f", ',', 1).unwrap(/*:test:*/);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0],
+            (1, vec!["a".to_string(), "b,\"c\"".to_string()])
+        );
+        assert_eq!(records[1], (3, vec!["d\ne".to_string(), "f".to_string()]));
+
+        assert!(read_records("a,\"b", ',', 1).is_err());
+    }
+
+    #[test]
+    fn csv_import() {
+        let profile = ImportProfile::from_toml(&PROFILE.strip_margin()).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let import = import_csv(&CSV.strip_margin(), &profile, &empty_journal(), &mut settings)
+            .unwrap(/*:test:*/);
+
+        assert_eq!(import.imported, 3);
+        assert_eq!(import.skipped, 0);
+
+        let txns: Vec<_> = import.journal.split("\n\n").collect();
+        assert_eq!(txns.len(), 3);
+        assert!(txns[0].starts_with("2024-01-01 'Salary\n"));
+        assert!(txns[0].ends_with("   Expenses:Unknown  -1000.00 EUR\n   Assets:Bank"));
+        assert!(txns[1].starts_with("2024-01-02 'K-Market; Helsinki\n"));
+        assert!(txns[1].contains("   # tags: food\n"));
+        assert!(txns[1].ends_with("   Expenses:Food  12.30 EUR\n   Assets:Bank"));
+        assert_ne!(txns[1].lines().nth(1), txns[2].lines().nth(1));
+    }
+
+    #[test]
+    fn csv_import_skip_existing() {
+        let profile = ImportProfile::from_toml(&PROFILE.strip_margin()).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let first = import_csv(&CSV.strip_margin(), &profile, &empty_journal(), &mut settings)
+            .unwrap(/*:test:*/);
+
+        let journal =
+            parser::string_to_txns(&mut first.journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let second =
+            import_csv(&CSV.strip_margin(), &profile, &journal, &mut settings).unwrap(/*:test:*/);
+
+        assert_eq!(second.imported, 0);
+        assert_eq!(second.skipped, 3);
+        assert!(second.journal.is_empty());
+    }
+
+    #[test]
+    fn csv_import_errors() {
+        let profile = ImportProfile::from_toml(&PROFILE.strip_margin()).unwrap(/*:test:*/);
+
+        let mut settings = Settings::default();
+        let csv = "x\nDate;Message;Amount;Archive\n2024-01-01;a;1;A\n";
+        let err = import_csv(csv, &profile, &empty_journal(), &mut settings).unwrap_err(/*:test:*/);
+        assert!(
+            err.to_string()
+                .starts_with("CSV error at line 3: invalid date '2024-01-01'")
+        );
+
+        let csv = "x\nDate;Message;Amount;Archive\n01.01.2024;a;x;A\n";
+        let err = import_csv(csv, &profile, &empty_journal(), &mut settings).unwrap_err(/*:test:*/);
+        assert!(
+            err.to_string()
+                .starts_with("CSV error at line 3: invalid amount 'x'")
+        );
+
+        let csv = "x\nDate;Msg;Amount;Archive\n01.01.2024;a;1;A\n";
+        let err = import_csv(csv, &profile, &empty_journal(), &mut settings).unwrap_err(/*:test:*/);
+        assert!(err.to_string().contains("column 'Message' is not found"));
+    }
+}
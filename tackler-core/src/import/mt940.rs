@@ -0,0 +1,335 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! SWIFT MT940 (Customer Statement Message)
+//!
+//! Each statement line (`:61:`) with its information field (`:86:`) is one txn:
+//! - date: entry (booking) date, or value date if entry date is not given
+//! - amount: amount with debit/credit mark, commodity is the currency of
+//!   the opening balance (`:60F:` or `:60M:`)
+//! - code and reference: bank reference, or customer reference if there is no bank reference
+//! - description: information field, if it's structured (`?20`..`?33` subfields),
+//!   then counterparty (`?32`, `?33`) and remittance information (`?20`..`?29`)
+use crate::import::{Import, ImportEntry, ImportProfile, entries_to_journal};
+use crate::kernel::Settings;
+use crate::model::TxnData;
+use crate::tackler;
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// MT940 field: line number, tag and value (continuation lines are separated by `\n`)
+type Field = (usize, String, String);
+
+fn read_fields(input: &str) -> Vec<Field> {
+    let mut fields: Vec<Field> = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let tag = line
+            .strip_prefix(':')
+            .and_then(|l| l.split_once(':'))
+            .filter(|(tag, _)| {
+                !tag.is_empty() && tag.len() <= 3 && tag.chars().all(|c| c.is_ascii_alphanumeric())
+            });
+        match tag {
+            Some((tag, value)) => fields.push((idx + 1, tag.to_string(), value.to_string())),
+            // end of message, SWIFT block headers and trailers
+            None if line.trim() == "-" || line.starts_with('{') || line.starts_with("-}") => {}
+            None => {
+                if let Some((_, _, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    fields
+}
+
+fn parse_date(yymmdd: &str) -> Result<Date, tackler::Error> {
+    let num = |s: &str| {
+        s.parse::<i8>()
+            .map_err(|_| format!("invalid date '{yymmdd}'"))
+    };
+    if yymmdd.len() != 6 {
+        let msg = format!("invalid date '{yymmdd}'");
+        return Err(msg.into());
+    }
+    let year = 2000 + i16::from(num(&yymmdd[0..2])?);
+    Date::new(year, num(&yymmdd[2..4])?, num(&yymmdd[4..6])?)
+        .map_err(|err| format!("invalid date '{yymmdd}': {err}").into())
+}
+
+/// Entry date (MMDD) is on the same year as the value date,
+/// except on the turn of the year.
+fn entry_date(value_date: Date, mmdd: &str) -> Result<Date, tackler::Error> {
+    let num = |s: &str| {
+        s.parse::<i8>()
+            .map_err(|_| format!("invalid entry date '{mmdd}'"))
+    };
+    let month = num(&mmdd[0..2])?;
+    let day = num(&mmdd[2..4])?;
+    let year = match (value_date.month(), month) {
+        (12, 1) => value_date.year() + 1,
+        (1, 12) => value_date.year() - 1,
+        _ => value_date.year(),
+    };
+    Date::new(year, month, day).map_err(|err| format!("invalid entry date '{mmdd}': {err}").into())
+}
+
+struct StatementLine {
+    date: Date,
+    amount: Decimal,
+    reference: Option<String>,
+}
+
+/// Parse statement line (`:61:`)
+///
+/// `YYMMDD[MMDD](C|D|RC|RD)[funds code]amount type reference[//bank reference]`
+fn parse_statement_line(value: &str) -> Result<StatementLine, tackler::Error> {
+    let line = value.lines().next().unwrap_or_default();
+    let Some(value_date) = line.get(0..6) else {
+        return Err("missing value date".into());
+    };
+    let value_date = parse_date(value_date)?;
+    let mut rest = &line[6..];
+
+    let date = match rest.get(0..4) {
+        Some(mmdd) if mmdd.chars().all(|c| c.is_ascii_digit()) => {
+            rest = &rest[4..];
+            entry_date(value_date, mmdd)?
+        }
+        _ => value_date,
+    };
+
+    let is_debit = if let Some(r) = rest.strip_prefix("RC") {
+        rest = r;
+        true
+    } else if let Some(r) = rest.strip_prefix("RD") {
+        rest = r;
+        false
+    } else if let Some(r) = rest.strip_prefix('C') {
+        rest = r;
+        false
+    } else if let Some(r) = rest.strip_prefix('D') {
+        rest = r;
+        true
+    } else {
+        return Err("missing or invalid debit/credit mark".into());
+    };
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        // funds code
+        rest = &rest[1..];
+    }
+
+    let amount_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == ','))
+        .unwrap_or(rest.len());
+    let amount_str = &rest[..amount_len];
+    // amount could be without decimals, e.g. "100,"
+    let amount = match Decimal::from_str(amount_str.replace(',', ".").trim_end_matches('.')) {
+        Ok(amount) => amount,
+        Err(err) => {
+            let msg = format!("invalid amount '{amount_str}': {err}");
+            return Err(msg.into());
+        }
+    };
+
+    // transaction type identification code (e.g. NTRF)
+    let refs = rest[amount_len..].get(4..).unwrap_or_default();
+    let (customer_ref, bank_ref) = match refs.split_once("//") {
+        Some((customer_ref, bank_ref)) => (customer_ref, Some(bank_ref)),
+        None => (refs, None),
+    };
+    let reference = bank_ref
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .or(Some(customer_ref.trim()).filter(|r| !r.is_empty() && *r != "NONREF"))
+        .map(str::to_string);
+
+    Ok(StatementLine {
+        date,
+        amount: if is_debit { -amount } else { amount },
+        reference,
+    })
+}
+
+/// Description from information field (`:86:`)
+fn description(info: &str) -> String {
+    let bytes = info.as_bytes();
+    let is_structured =
+        bytes.len() > 4 && bytes[..3].iter().all(u8::is_ascii_digit) && bytes[3] == b'?';
+    if !is_structured {
+        return info
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    let info = info.replace('\n', "");
+    let mut remittance = String::new();
+    let mut counterparty = String::new();
+    for subfield in info[4..].split('?') {
+        let Some(code) = subfield.get(0..2) else {
+            continue;
+        };
+        match code {
+            "20" | "21" | "22" | "23" | "24" | "25" | "26" | "27" | "28" | "29" => {
+                remittance.push_str(&subfield[2..]);
+            }
+            "32" | "33" => counterparty.push_str(&subfield[2..]),
+            _ => {}
+        }
+    }
+    match (counterparty.trim(), remittance.trim()) {
+        ("", rmt) => rmt.to_string(),
+        (cp, "") => cp.to_string(),
+        (cp, rmt) => format!("{cp} - {rmt}"),
+    }
+}
+
+/// Import MT940 bank statement
+///
+/// `journal` is the existing journal, its txns are used to skip
+/// entries which are already imported.
+pub fn import_mt940(
+    input: &str,
+    profile: &ImportProfile,
+    journal: &TxnData,
+    settings: &mut Settings,
+) -> Result<Import, tackler::Error> {
+    let mut entries: Vec<ImportEntry> = Vec::new();
+    let mut currency = None;
+    // information field belongs to the directly preceding statement line
+    let mut prev_tag = String::new();
+
+    for (line, tag, value) in read_fields(input) {
+        match tag.as_str() {
+            "60F" | "60M" => {
+                currency = value
+                    .get(7..10)
+                    .filter(|c| c.chars().all(|c| c.is_ascii_alphabetic()))
+                    .map(str::to_string);
+            }
+            "61" => {
+                let stmt_line = match parse_statement_line(&value) {
+                    Ok(stmt_line) => stmt_line,
+                    Err(err) => {
+                        let msg = format!("MT940 error at line {line}: {err}");
+                        return Err(msg.into());
+                    }
+                };
+                entries.push(ImportEntry {
+                    line,
+                    date: stmt_line.date,
+                    code: stmt_line.reference.clone(),
+                    description: String::new(),
+                    amount: stmt_line.amount,
+                    commodity: currency.clone(),
+                    reference: stmt_line.reference,
+                });
+            }
+            "86" if prev_tag == "61" => {
+                if let Some(entry) = entries.last_mut() {
+                    entry.description = description(&value);
+                }
+            }
+            _ => {}
+        }
+        prev_tag = tag;
+    }
+
+    entries_to_journal(entries, profile, journal, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+
+    const PROFILE: &str = indoc!(
+        r#"[txn]
+        account = "Assets:Bank"
+        default-account = "Expenses:Unknown"
+
+        [[rules]]
+        description = "(?i).*market.*"
+        account = "Expenses:Food"
+        tags = [ "food" ]
+        "#
+    );
+
+    const MT940: &str = indoc!(
+        "{1:F01BANKFIHHAXXX0000000000}{2:I940BANKFIHHXXXXN}{4:
+        :20:STATEMENT1
+        :25:FI2112345600000785
+        :28C:00001/001
+        :60F:C231231EUR1000,00
+        :61:2401020102D12,30NTRFNONREF//BANKREF1
+        :86:K-MARKET HELSINKI
+        RECEIPT 42
+        :61:2312300101C1000,NMSCSALARY
+        :86:166?00GUTSCHRIFT?20SALARY 2023-?2112?32ACME?33 CORP
+        :62F:C240102EUR1987,70
+        -}"
+    );
+
+    fn empty_journal() -> TxnData {
+        TxnData::from(None, Vec::new(), &None).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn mt940_statement_line() {
+        let stmt_line = parse_statement_line("231231RD1,5NTRF//X\nextra").unwrap(/*:test:*/);
+        assert_eq!(stmt_line.date, Date::constant(2023, 12, 31));
+        assert_eq!(
+            stmt_line.amount,
+            Decimal::from_str("1.5").unwrap(/*:test:*/)
+        );
+        assert_eq!(stmt_line.reference.as_deref(), Some("X"));
+
+        assert!(parse_statement_line("240101X1,0NTRF").is_err());
+        assert!(parse_statement_line("241301C1,0NTRF").is_err());
+    }
+
+    #[test]
+    fn mt940_import() {
+        let profile = ImportProfile::from_toml(PROFILE).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let import =
+            import_mt940(MT940, &profile, &empty_journal(), &mut settings).unwrap(/*:test:*/);
+        assert_eq!(import.imported, 2);
+
+        let txns: Vec<_> = import.journal.split("\n\n").collect();
+        assert!(txns[0].starts_with("2024-01-01 (SALARY) 'ACME CORP - SALARY 2023-12\n"));
+        assert!(txns[0].contains("   Expenses:Unknown  -1000 EUR\n"));
+        assert!(txns[1].starts_with("2024-01-02 (BANKREF1) 'K-MARKET HELSINKI RECEIPT 42\n"));
+        assert!(txns[1].contains("   Expenses:Food  12.30 EUR\n"));
+
+        let journal =
+            parser::string_to_txns(&mut import.journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let second = import_mt940(MT940, &profile, &journal, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(second.imported, 0);
+        assert_eq!(second.skipped, 2);
+    }
+
+    #[test]
+    fn mt940_errors() {
+        let profile = ImportProfile::from_toml(PROFILE).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+
+        let mt940 = ":20:X\n:60F:C240101EUR0,00\n:61:240102Z1,00NTRF\n";
+        let err =
+            import_mt940(mt940, &profile, &empty_journal(), &mut settings).unwrap_err(/*:test:*/);
+        assert_eq!(
+            err.to_string(),
+            "MT940 error at line 3: missing or invalid debit/credit mark"
+        );
+    }
+}
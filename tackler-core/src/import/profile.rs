@@ -0,0 +1,217 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::tackler;
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use tackler_rs::regex::new_full_haystack_regex;
+
+#[derive(Debug, Clone, Deserialize)]
+struct ImportProfileRaw {
+    txn: TxnProfileRaw,
+    csv: Option<CsvProfileRaw>,
+    rules: Option<Vec<RuleRaw>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TxnProfileRaw {
+    account: String,
+    commodity: Option<String>,
+    #[serde(rename = "default-account")]
+    default_account: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CsvProfileRaw {
+    delimiter: Option<char>,
+    header: Option<bool>,
+    #[serde(rename = "skip-lines")]
+    skip_lines: Option<usize>,
+    #[serde(rename = "date-format")]
+    date_format: String,
+    #[serde(rename = "decimal-separator")]
+    decimal_separator: Option<char>,
+    #[serde(rename = "thousands-separator")]
+    thousands_separator: Option<char>,
+    columns: ColumnsRaw,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ColumnsRaw {
+    date: Column,
+    description: Column,
+    amount: Column,
+    code: Option<Column>,
+    commodity: Option<Column>,
+    reference: Option<Column>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RuleRaw {
+    description: String,
+    account: String,
+    tags: Option<Vec<String>>,
+}
+
+/// CSV column, by column name (header) or by column number (first column is 1)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Column {
+    Number(usize),
+    Name(String),
+}
+
+/// Settings for the txns of import
+#[derive(Debug, Clone)]
+pub(crate) struct TxnProfile {
+    /// Account of the bank statement
+    pub(crate) account: String,
+    /// Commodity of amounts, if it's not given by the statement
+    pub(crate) commodity: Option<String>,
+    /// Counter account, if none of the rules match
+    pub(crate) default_account: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Columns {
+    pub(crate) date: Column,
+    pub(crate) description: Column,
+    pub(crate) amount: Column,
+    pub(crate) code: Option<Column>,
+    pub(crate) commodity: Option<Column>,
+    pub(crate) reference: Option<Column>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CsvProfile {
+    pub(crate) delimiter: char,
+    pub(crate) header: bool,
+    pub(crate) skip_lines: usize,
+    pub(crate) date_format: String,
+    pub(crate) decimal_separator: char,
+    pub(crate) thousands_separator: Option<char>,
+    pub(crate) columns: Columns,
+}
+
+/// Rule to select counter account and tags by description
+#[derive(Debug, Clone)]
+pub(crate) struct Rule {
+    pub(crate) description: Regex,
+    pub(crate) account: String,
+    pub(crate) tags: Vec<String>,
+}
+
+/// Import profile
+///
+/// Profile defines how the bank statement is read, and how its entries
+/// are converted into transactions. Counter account and tags of txn are
+/// selected by the first rule which matches the description of the entry.
+#[derive(Debug, Clone)]
+pub struct ImportProfile {
+    pub(crate) txn: TxnProfile,
+    pub(crate) csv: Option<CsvProfile>,
+    pub(crate) rules: Vec<Rule>,
+}
+
+impl ImportProfile {
+    pub fn from<P: AsRef<Path>>(path: P) -> Result<ImportProfile, tackler::Error> {
+        match fs::read_to_string(&path) {
+            Ok(s) => ImportProfile::from_toml(s.as_str()),
+            Err(err) => {
+                let msg = format!(
+                    "Import profile error while reading file '{}': {err}",
+                    path.as_ref().display()
+                );
+                Err(msg.into())
+            }
+        }
+    }
+
+    pub fn from_toml(profile: &str) -> Result<ImportProfile, tackler::Error> {
+        let raw: ImportProfileRaw = toml::from_str(profile)?;
+
+        let csv = raw.csv.map(|csv| CsvProfile {
+            delimiter: csv.delimiter.unwrap_or(','),
+            header: csv.header.unwrap_or(true),
+            skip_lines: csv.skip_lines.unwrap_or(0),
+            date_format: csv.date_format,
+            decimal_separator: csv.decimal_separator.unwrap_or('.'),
+            thousands_separator: csv.thousands_separator,
+            columns: Columns {
+                date: csv.columns.date,
+                description: csv.columns.description,
+                amount: csv.columns.amount,
+                code: csv.columns.code,
+                commodity: csv.columns.commodity,
+                reference: csv.columns.reference,
+            },
+        });
+
+        let rules = raw
+            .rules
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| match new_full_haystack_regex(&r.description) {
+                Ok(description) => Ok(Rule {
+                    description,
+                    account: r.account,
+                    tags: r.tags.unwrap_or_default(),
+                }),
+                Err(err) => {
+                    let msg = format!("Import profile: invalid rule '{}': {err}", r.description);
+                    Err(msg)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ImportProfile {
+            txn: TxnProfile {
+                account: raw.txn.account,
+                commodity: raw.txn.commodity,
+                default_account: raw.txn.default_account,
+            },
+            csv,
+            rules,
+        })
+    }
+
+    /// Counter account and tags for description
+    pub(crate) fn select(&self, description: &str) -> (&str, &[String]) {
+        match self
+            .rules
+            .iter()
+            .find(|r| r.description.is_match(description))
+        {
+            Some(rule) => (rule.account.as_str(), rule.tags.as_slice()),
+            None => (self.txn.default_account.as_str(), &[]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_example() {
+        let profile = ImportProfile::from("../examples/import-csv.toml").unwrap(/*:test:*/);
+
+        assert_eq!(profile.txn.account, "Assets:Bank:Checking");
+        assert_eq!(profile.rules.len(), 2);
+        assert_eq!(
+            profile.select("K-MARKET"),
+            ("Expenses:Groceries", &["food".to_string()][..])
+        );
+        assert_eq!(profile.select("Salary 2024-01"), ("Income:Salary", &[][..]));
+        assert_eq!(profile.select("ATM"), ("Expenses:Unknown", &[][..]));
+
+        let csv = profile.csv.unwrap(/*:test:*/);
+        assert_eq!(csv.delimiter, ';');
+        assert_eq!(csv.decimal_separator, ',');
+        assert!(matches!(csv.columns.amount, Column::Name(ref n) if n == "Amount"));
+        assert!(csv.columns.code.is_none());
+    }
+}
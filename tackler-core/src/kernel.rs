@@ -5,16 +5,22 @@
 
 pub use settings::Settings;
 pub(crate) mod accumulator;
+pub mod amount_format;
 pub mod balance;
 pub mod hash;
+pub mod lots;
+pub mod pnl;
 pub mod price_lookup;
 pub mod report_item_selector;
 pub mod report_settings;
 pub mod settings;
+pub mod statement;
 
 pub use report_settings::BalanceGroupSettings;
 pub use report_settings::BalanceSettings;
+pub use report_settings::PnlSettings;
 pub use report_settings::RegisterSettings;
+pub use report_settings::StatementSettings;
 
 ///
 /// Predicate to test if item x is part of set or not
@@ -10,15 +10,36 @@ use crate::kernel::{RegisterSettings, Settings};
 use crate::model::{RegisterEntry, RegisterPosting, Transaction, TxnAccount, TxnRefs};
 use crate::tackler;
 use itertools::Itertools;
+use jiff::tz::TimeZone;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 use std::io;
-
-pub(crate) type RegisterReporterFn<W> =
-    fn(writer: &mut W, &RegisterEntry<'_>, &RegisterSettings) -> Result<(), tackler::Error>;
+use tackler_api::txn_ts;
+use tackler_api::txn_ts::GroupBy;
 
 pub(crate) type TxnGroupByOp<'a> = Box<dyn Fn(&Transaction) -> String + 'a>;
 
+/// Get group-by key operation for txns, key is txn timestamp in report timezone
+pub(crate) fn group_by_op<'a>(group_by: GroupBy, tz: TimeZone) -> TxnGroupByOp<'a> {
+    match group_by {
+        GroupBy::IsoWeekDate => Box::new(move |txn: &Transaction| {
+            txn_ts::as_tz_iso_week_date(&txn.header.timestamp, tz.clone())
+        }),
+        GroupBy::IsoWeek => Box::new(move |txn: &Transaction| {
+            txn_ts::as_tz_iso_week(&txn.header.timestamp, tz.clone())
+        }),
+        GroupBy::Date => {
+            Box::new(move |txn: &Transaction| txn_ts::as_tz_date(&txn.header.timestamp, tz.clone()))
+        }
+        GroupBy::Month => Box::new(move |txn: &Transaction| {
+            txn_ts::as_tz_month(&txn.header.timestamp, tz.clone())
+        }),
+        GroupBy::Year => {
+            Box::new(move |txn: &Transaction| txn_ts::as_tz_year(&txn.header.timestamp, tz.clone()))
+        }
+    }
+}
+
 pub(crate) fn balance_groups<T>(
     txns: &TxnRefs<'_>,
     group_by_op: TxnGroupByOp<'_>,
@@ -42,17 +63,18 @@ where
         .collect()
 }
 
-pub(crate) fn register_engine<'a, W, T>(
+pub(crate) fn register_engine<'a, W, T, F>(
     txns: &'a TxnRefs<'_>,
     price_lookup_ctx: &PriceLookupCtx<'_>,
     ras: &T,
     w: &mut W,
-    reporter: RegisterReporterFn<W>,
+    mut reporter: F,
     register_settings: &RegisterSettings,
 ) -> Result<(), tackler::Error>
 where
     W: io::Write + ?Sized,
     T: RegisterSelector<'a> + ?Sized,
+    F: FnMut(&mut W, &RegisterEntry<'_>, &RegisterSettings) -> Result<(), tackler::Error>,
 {
     let mut register_engine: HashMap<TxnAccount, Decimal> = HashMap::new();
 
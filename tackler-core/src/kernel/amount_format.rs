@@ -0,0 +1,279 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Formatting of amounts in reports and exports
+//!
+//! Precision of amounts is defined by `report.scale`, unless there is
+//! commodity format with decimals for the commodity in Chart of Commodities.
+//! Commodity format could also define rounding strategy and display symbol.
+//!
+//! Amounts of text reports are formatted with decimal and digit grouping
+//! separators of `report.number-format`, other report formats (JSON, CSV, TSV)
+//! use always plain numbers.
+use crate::config::{CommodityFormat, NumberFormat, Scale, SymbolPosition};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct AmountFormat {
+    scale: Scale,
+    number_format: NumberFormat,
+    formats: Arc<HashMap<String, CommodityFormat>>,
+}
+
+impl AmountFormat {
+    pub(crate) fn new(
+        scale: Scale,
+        number_format: NumberFormat,
+        formats: Arc<HashMap<String, CommodityFormat>>,
+    ) -> Self {
+        AmountFormat {
+            scale,
+            number_format,
+            formats,
+        }
+    }
+
+    /// Rounded amount and its precision
+    fn round(&self, amount: &Decimal, comm: &str) -> (Decimal, usize) {
+        let fmt = self.formats.get(comm);
+        let prec = match fmt.and_then(|f| f.decimals) {
+            Some(decimals) => decimals as usize,
+            None => self.scale.get_precision(amount),
+        };
+        let strategy = fmt.map_or(RoundingStrategy::MidpointAwayFromZero, |f| {
+            f.rounding.strategy()
+        });
+        (amount.round_dp_with_strategy(prec as u32, strategy), prec)
+    }
+
+    /// Rounded amount without symbol (JSON and table reports)
+    pub(crate) fn amount(&self, amount: &Decimal, comm: &str) -> String {
+        let (amount, prec) = self.round(amount, comm);
+        format!("{:.prec$}", amount)
+    }
+
+    /// Number with decimal and digit grouping separators of text reports
+    fn localize(&self, number: &str) -> String {
+        let nf = &self.number_format;
+        let (sign, digits) = match number.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", number),
+        };
+        let (int, frac) = match digits.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (digits, None),
+        };
+
+        let mut txt = String::with_capacity(number.len() + int.len() / 3);
+        txt.push_str(sign);
+        for (i, c) in int.chars().enumerate() {
+            if let Some(sep) = nf.grouping_sep {
+                if i > 0 && (int.len() - i) % 3 == 0 {
+                    txt.push(sep);
+                }
+            }
+            txt.push(c);
+        }
+        if let Some(frac) = frac {
+            txt.push(nf.decimal_sep);
+            txt.push_str(frac);
+        }
+        txt
+    }
+
+    /// Rounded amount of text reports, without symbol
+    pub(crate) fn txt_number(&self, amount: &Decimal, comm: &str) -> String {
+        self.localize(&self.amount(amount, comm))
+    }
+
+    /// Rounded amount of text reports, with prefix symbol of commodity
+    pub(crate) fn txt_amount(&self, amount: &Decimal, comm: &str) -> String {
+        match self.prefix_symbol(comm) {
+            Some(sym) => {
+                let (amount, prec) = self.round(amount, comm);
+                let number = self.localize(&format!("{:.prec$}", amount.abs()));
+                match amount.is_sign_negative() {
+                    true => format!("-{sym}{number}"),
+                    false => format!("{sym}{number}"),
+                }
+            }
+            None => self.txt_number(amount, comm),
+        }
+    }
+
+    /// Width of amount in text reports
+    pub(crate) fn txt_width(&self, amount: &Decimal, comm: &str) -> usize {
+        self.txt_amount(amount, comm).chars().count()
+    }
+
+    /// Amount of text report column, which is aligned to the right
+    ///
+    /// Positive amount which fills the whole column is separated with a space
+    /// from the previous column.
+    pub(crate) fn txt_column(&self, amount: &Decimal, comm: &str, width: usize) -> String {
+        let txt = self.txt_amount(amount, comm);
+        if amount.is_sign_positive() && txt.chars().count() >= width {
+            format!(" {txt}")
+        } else {
+            format!("{txt:>width$}")
+        }
+    }
+
+    /// Commodity of text reports, which is shown after the amount
+    ///
+    /// This is suffix symbol or name of commodity, and it's empty
+    /// if the symbol of commodity is shown before the amount.
+    pub(crate) fn txt_commodity<'a>(&'a self, comm: &'a str) -> &'a str {
+        match self.formats.get(comm) {
+            Some(CommodityFormat {
+                symbol: Some(sym),
+                symbol_position,
+                ..
+            }) => match symbol_position {
+                SymbolPosition::Prefix => "",
+                SymbolPosition::Suffix => sym.as_str(),
+            },
+            _ => comm,
+        }
+    }
+
+    fn prefix_symbol(&self, comm: &str) -> Option<&str> {
+        self.formats
+            .get(comm)
+            .filter(|f| f.symbol_position == SymbolPosition::Prefix)
+            .and_then(|f| f.symbol.as_deref())
+    }
+
+    /// Price rate (JSON and table reports)
+    ///
+    /// Rate is not an amount of commodity, so it's rounded only by report scale.
+    pub(crate) fn rate(&self, rate: &Decimal) -> String {
+        let prec = self.scale.get_precision(rate);
+        format!("{:.prec$}", rate.round_dp(prec as u32))
+    }
+
+    /// Price rate of text reports
+    pub(crate) fn txt_rate(&self, rate: &Decimal) -> String {
+        self.localize(&self.rate(rate))
+    }
+
+    /// Exact amount of exports
+    ///
+    /// Amount is never rounded, but it's padded to the decimals of commodity.
+    pub(crate) fn exact_amount(&self, amount: &Decimal, comm: &str) -> String {
+        match self.formats.get(comm).and_then(|f| f.decimals) {
+            Some(decimals) if amount.scale() < decimals => {
+                format!("{:.prec$}", amount, prec = decimals as usize)
+            }
+            _ => amount.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Rounding;
+    use rust_decimal_macros::dec;
+
+    fn amount_format() -> AmountFormat {
+        let formats = [
+            CommodityFormat {
+                name: "JPY".to_string(),
+                decimals: Some(0),
+                symbol: Some("¥".to_string()),
+                symbol_position: SymbolPosition::Prefix,
+                rounding: Rounding::HalfEven,
+            },
+            CommodityFormat {
+                name: "BTC".to_string(),
+                decimals: Some(8),
+                symbol: None,
+                symbol_position: SymbolPosition::Suffix,
+                rounding: Rounding::Down,
+            },
+            CommodityFormat {
+                name: "EUR".to_string(),
+                decimals: None,
+                symbol: Some("€".to_string()),
+                symbol_position: SymbolPosition::Suffix,
+                rounding: Rounding::HalfUp,
+            },
+        ]
+        .into_iter()
+        .map(|f| (f.name.clone(), f))
+        .collect();
+        AmountFormat::new(Scale::default(), NumberFormat::default(), Arc::new(formats))
+    }
+
+    #[test]
+    fn commodity_formats() {
+        let fmt = amount_format();
+
+        assert_eq!(fmt.txt_amount(&dec!(1234.5), "JPY"), "¥1234");
+        assert_eq!(fmt.txt_amount(&dec!(-1235.5), "JPY"), "-¥1236");
+        assert_eq!(fmt.amount(&dec!(1235.5), "JPY"), "1236");
+        assert_eq!(fmt.txt_commodity("JPY"), "");
+
+        assert_eq!(fmt.txt_amount(&dec!(0.123456789), "BTC"), "0.12345678");
+        assert_eq!(fmt.txt_amount(&dec!(1), "BTC"), "1.00000000");
+        assert_eq!(fmt.txt_commodity("BTC"), "BTC");
+
+        assert_eq!(fmt.txt_amount(&dec!(12.345), "EUR"), "12.345");
+        assert_eq!(fmt.txt_amount(&dec!(12.3), "EUR"), "12.30");
+        assert_eq!(fmt.txt_commodity("EUR"), "€");
+
+        // report.scale and default rounding
+        assert_eq!(fmt.txt_amount(&dec!(1.123456789), "USD"), "1.1234568");
+        assert_eq!(fmt.txt_amount(&dec!(1), ""), "1.00");
+        assert_eq!(fmt.txt_commodity("USD"), "USD");
+    }
+
+    #[test]
+    fn number_formats() {
+        let fi = AmountFormat {
+            number_format: NumberFormat {
+                decimal_sep: ',',
+                grouping_sep: Some(' '),
+            },
+            ..amount_format()
+        };
+        assert_eq!(fi.txt_amount(&dec!(1234567.891), "USD"), "1 234 567,891");
+        assert_eq!(fi.txt_amount(&dec!(-123456.7), "USD"), "-123 456,70");
+        assert_eq!(fi.txt_amount(&dec!(-1234567.5), "JPY"), "-¥1 234 568");
+        assert_eq!(fi.txt_amount(&dec!(123), "JPY"), "¥123");
+        assert_eq!(fi.txt_number(&dec!(-1000), ""), "-1 000,00");
+        // JSON and table reports
+        assert_eq!(fi.amount(&dec!(1234567.891), "USD"), "1234567.891");
+        // rates are not rounded by commodity
+        assert_eq!(fi.txt_rate(&dec!(1234.5)), "1 234,50");
+        assert_eq!(fi.txt_rate(&dec!(0.123456789)), "0,1234568");
+        assert_eq!(fi.rate(&dec!(1234.5)), "1234.50");
+
+        let en = AmountFormat {
+            number_format: NumberFormat {
+                decimal_sep: '.',
+                grouping_sep: Some(','),
+            },
+            ..amount_format()
+        };
+        assert_eq!(en.txt_amount(&dec!(1234567.89), "USD"), "1,234,567.89");
+        assert_eq!(en.txt_amount(&dec!(-999.5), "USD"), "-999.50");
+        assert_eq!(en.txt_column(&dec!(1000), "USD", 8), " 1,000.00");
+        assert_eq!(en.txt_column(&dec!(-1000), "USD", 10), " -1,000.00");
+    }
+
+    #[test]
+    fn exact_amounts() {
+        let fmt = amount_format();
+
+        assert_eq!(fmt.exact_amount(&dec!(1), "BTC"), "1.00000000");
+        assert_eq!(fmt.exact_amount(&dec!(0.123456789), "BTC"), "0.123456789");
+        assert_eq!(fmt.exact_amount(&dec!(1234.5), "JPY"), "1234.5");
+        assert_eq!(fmt.exact_amount(&dec!(12.3), "USD"), "12.3");
+    }
+}
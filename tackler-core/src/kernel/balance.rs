@@ -7,7 +7,9 @@ use crate::kernel::Settings;
 use crate::kernel::price_lookup::PriceLookupCtx;
 use crate::kernel::report_item_selector::BalanceSelector;
 use crate::model::balance_tree_node::ord_by_btn;
-use crate::model::{BalanceTreeNode, Commodity, Transaction, TxnAccount, TxnSet};
+use crate::model::{
+    BalanceAssertion, BalanceTreeNode, Commodity, Transaction, TxnAccount, TxnRefs, TxnSet,
+};
 use crate::tackler;
 use itertools::Itertools;
 use rust_decimal::Decimal;
@@ -15,6 +17,7 @@ use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
 };
+use tackler_api::txn_ts;
 
 pub type Deltas = HashMap<Option<Arc<Commodity>>, Decimal>;
 pub type BTNs = Vec<BalanceTreeNode>;
@@ -254,15 +257,7 @@ impl Balance {
                 deltas: Default::default(),
             })
         } else {
-            let deltas = filt_bal
-                .iter()
-                .chunk_by(|btn| btn.acctn.comm.clone())
-                .into_iter()
-                .map(|(c, bs)| {
-                    let dsum = bs.map(|b| b.account_sum).sum();
-                    (c.is_any().then_some(c), dsum)
-                })
-                .collect();
+            let deltas = Balance::deltas(&filt_bal);
 
             Ok(Balance {
                 title: title.to_string(),
@@ -271,4 +266,165 @@ impl Balance {
             })
         }
     }
+
+    pub(crate) fn deltas(bal: &[BalanceTreeNode]) -> Deltas {
+        bal.iter()
+            .chunk_by(|btn| btn.acctn.comm.clone())
+            .into_iter()
+            .map(|(c, bs)| {
+                let dsum = bs.map(|b| b.account_sum).sum();
+                (c.is_any().then_some(c), dsum)
+            })
+            .collect()
+    }
+
+    /// Flip signs of accounts with normal credit balance (liabilities, equity and income),
+    /// so that their balances are shown as positive values.
+    ///
+    /// Account type is by account of the balance tree node. Signs are flipped
+    /// only for presentation, so deltas are kept as sums of the original account sums.
+    pub(crate) fn natural_signs(mut self, settings: &Settings) -> Balance {
+        // don't make negative zeros
+        let flip = |d: Decimal| if d.is_zero() { d } else { -d };
+        for btn in &mut self.bal {
+            let acc_type = settings.get_account_type(&btn.acctn.atn.account);
+            if acc_type.is_some_and(|t| t.is_credit()) {
+                btn.account_sum = flip(btn.account_sum);
+                btn.sub_acc_tree_sum = flip(btn.sub_acc_tree_sum);
+            }
+        }
+        self
+    }
+
+    /// Verify balance assertions with running account totals
+    ///
+    /// Posting level assertions (`== amount`) are verified after the posting,
+    /// and balance directives after all transactions up to and including
+    /// the timestamp of directive. Account totals are calculated
+    /// by account and commodity, without sub-accounts and without price conversion.
+    ///
+    /// * `txns` sorted sequence of transactions
+    /// * `directives` balance assertion directives
+    pub(crate) fn verify_assertions(
+        txns: &TxnRefs<'_>,
+        directives: &[BalanceAssertion],
+    ) -> Result<(), tackler::Error> {
+        fn assertion_error(
+            acctn: &TxnAccount,
+            total: Decimal,
+            asserted: Decimal,
+            location: String,
+        ) -> tackler::Error {
+            let comm = if acctn.comm.is_any() {
+                format!(" {}", acctn.comm.name)
+            } else {
+                String::default()
+            };
+            format!(
+                "Balance assertion error: account '{}' balance is {}{}, but asserted balance is {}{} (difference: {}{}) at {}",
+                acctn.atn.account,
+                total,
+                comm,
+                asserted,
+                comm,
+                total - asserted,
+                comm,
+                location
+            )
+            .into()
+        }
+        let check_directive = |ba: &BalanceAssertion, totals: &HashMap<TxnAccount, Decimal>| {
+            let total = totals.get(&ba.acctn).copied().unwrap_or_default();
+            if total == ba.amount {
+                Ok(())
+            } else {
+                Err(assertion_error(
+                    &ba.acctn,
+                    total,
+                    ba.amount,
+                    format!("balance directive: {}", ba.location()),
+                ))
+            }
+        };
+
+        let mut directives = directives
+            .iter()
+            .map(|ba| Ok((ba.end()?, ba)))
+            .collect::<Result<Vec<_>, tackler::Error>>()?
+            .into_iter()
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .peekable();
+        let mut totals: HashMap<TxnAccount, Decimal> = HashMap::new();
+
+        for txn in txns {
+            while let Some((_, ba)) = directives.next_if(|(end, _)| *end < txn.header.timestamp) {
+                check_directive(ba, &totals)?;
+            }
+            for p in &txn.posts {
+                let total = totals.entry(p.acctn.clone()).or_default();
+                *total += p.amount;
+
+                if let Some(asserted) = p.balance_assertion {
+                    if *total != asserted {
+                        let location = format!(
+                            "txn: {}{}",
+                            txn_ts::rfc_3339(&txn.header.timestamp),
+                            txn.header
+                                .uuid
+                                .map(|u| format!(", txn uuid: {u}"))
+                                .unwrap_or_default()
+                        );
+                        return Err(assertion_error(&p.acctn, *total, asserted, location));
+                    }
+                }
+            }
+        }
+        for (_, ba) in directives {
+            check_directive(ba, &totals)?;
+        }
+        Ok(())
+    }
+
+    /// Verify that account balances are not against their normal balance
+    ///
+    /// Accounts with normal debit balance (assets and expenses) must not have credit
+    /// (negative) balance, and accounts with normal credit balance (liabilities, equity
+    /// and income) must not have debit (positive) balance at the end of the report period.
+    /// Only accounts with normal balance types of settings are checked (only in strict mode).
+    /// Account sums of the balance tree are checked, so this must be done before
+    /// flipping the signs for natural presentation.
+    pub(crate) fn verify_normal_balances(&self, settings: &Settings) -> Result<(), tackler::Error> {
+        let acc_types = settings.get_normal_balance_types();
+        if acc_types.is_empty() {
+            return Ok(());
+        }
+
+        for btn in &self.bal {
+            let (acctn, total) = (&btn.acctn, &btn.account_sum);
+            let Some(acc_type) = settings.get_account_type(&acctn.atn.account) else {
+                continue;
+            };
+            if !acc_types.contains(&acc_type) {
+                continue;
+            }
+            let balance = match (acc_type.is_credit(), total.is_sign_positive()) {
+                (true, true) if !total.is_zero() => "debit",
+                (false, false) if !total.is_zero() => "credit",
+                _ => continue,
+            };
+            let msg = format!(
+                "Normal balance error: {} account '{}' has {} balance {}{}",
+                acc_type,
+                acctn.atn.account,
+                balance,
+                total,
+                match acctn.comm.is_any() {
+                    true => format!(" {}", acctn.comm.name),
+                    false => String::new(),
+                }
+            );
+            return Err(msg.into());
+        }
+        Ok(())
+    }
 }
@@ -5,13 +5,20 @@
 
 use crate::tackler;
 use digest::DynDigest;
+use sha2::{Digest, Sha256};
 use std::fmt::{Debug, Formatter, Write};
 use tackler_api::metadata::Checksum;
+use uuid::Uuid;
 
-#[derive(Clone)]
 pub struct Hash {
     hash_algo: String,
-    hasher: Box<dyn DynDigest>,
+    hasher: Box<dyn DynDigest + Send + Sync>,
+}
+
+impl Clone for Hash {
+    fn clone(&self) -> Self {
+        Hash::from(self.hash_algo.as_str()).expect("IE: hash algorithm is validated")
+    }
 }
 
 impl Default for Hash {
@@ -60,7 +67,7 @@ impl Hash {
     }
 
     pub fn checksum(&self, items: &[String], separator: &[u8]) -> Result<Checksum, tackler::Error> {
-        let mut hasher = self.hasher.clone();
+        let mut hasher = self.hasher.box_clone();
 
         for i in items {
             hasher.update(i.as_bytes());
@@ -78,6 +85,17 @@ impl Hash {
     }
 }
 
+/// Deterministic UUID derived from the fingerprint
+///
+/// Same fingerprint gives always the same UUID, so generated txns
+/// (imports, closing txns) have stable identity between runs.
+pub(crate) fn fingerprint_uuid(fingerprint: &str) -> Uuid {
+    let digest = Sha256::digest(fingerprint.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
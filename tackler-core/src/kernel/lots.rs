@@ -0,0 +1,371 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Lot tracking of postings with cost basis
+//!
+//! Posting with positive amount and cost basis `{ ... }` opens a new lot.
+//! Posting with negative amount closes open lots of the same account and commodity.
+//! Closing posting with cost basis is matched only with lots of that cost (specific lot),
+//! otherwise the lots are matched by the used lot method (FIFO or LIFO).
+//!
+//! Closing more units than there is held in open lots is an error.
+use crate::config::LotMethod;
+use crate::model::posting::CostBasis;
+use crate::model::{Commodity, Posting, Transaction, TxnAccount, TxnRefs};
+use crate::tackler;
+use jiff::Zoned;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tackler_api::txn_ts;
+
+/// Open lot
+#[derive(Debug, Clone)]
+pub struct Lot {
+    /// Timestamp of opening transaction
+    pub opened: Zoned,
+    /// Units which are still open
+    pub units: Decimal,
+    /// Cost basis of the lot
+    pub cost_basis: CostBasis,
+}
+
+/// Closed (or partially closed) lot
+#[derive(Debug, Clone)]
+pub struct LotClosing {
+    /// Timestamp of closing transaction
+    pub ts: Zoned,
+    /// Account (and commodity) of the lot
+    pub account: TxnAccount,
+    /// Timestamp of opening transaction
+    pub opened: Zoned,
+    /// Amount of closed units
+    pub units: Decimal,
+    /// Cost basis of the lot
+    pub cost_basis: CostBasis,
+    /// Unit price of closing posting (`@` or `=`), if any
+    pub unit_price: Option<(Decimal, Arc<Commodity>)>,
+}
+
+impl LotClosing {
+    /// Realized gain (or loss) of this closing in the cost basis commodity
+    ///
+    /// This is `None`, if the closing posting doesn't have unit price,
+    /// or the price is not in the same commodity as the cost basis.
+    pub fn realized_gain(&self) -> Option<Decimal> {
+        match &self.unit_price {
+            Some((price, comm)) if *comm == self.cost_basis.commodity => {
+                Some(self.units * (price - self.cost_basis.unit_cost))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Lots of the transaction set
+#[derive(Debug, Default)]
+pub struct Lots {
+    open: BTreeMap<TxnAccount, Vec<Lot>>,
+    closings: Vec<LotClosing>,
+}
+
+impl Lots {
+    /// Track lots of sorted transactions
+    pub fn from(txns: &TxnRefs<'_>, method: LotMethod) -> Result<Lots, tackler::Error> {
+        let mut lots = Lots::default();
+
+        for txn in txns {
+            lots.add(txn, method)?;
+        }
+
+        Ok(lots)
+    }
+
+    /// Track lots of the next transaction (transactions must be added in sorted order)
+    pub fn add(&mut self, txn: &Transaction, method: LotMethod) -> Result<(), tackler::Error> {
+        for p in &txn.posts {
+            if p.amount.is_sign_positive() {
+                if let Some(cb) = &p.cost_basis {
+                    self.open.entry(p.acctn.clone()).or_default().push(Lot {
+                        opened: txn.header.timestamp.clone(),
+                        units: p.amount,
+                        cost_basis: cb.clone(),
+                    });
+                }
+            } else if p.cost_basis.is_some() || self.open.contains_key(&p.acctn) {
+                self.close(txn, p, method)?;
+            }
+        }
+        self.open.retain(|_, l| !l.is_empty());
+
+        Ok(())
+    }
+
+    /// All open lots, sorted by account, and then by opening time
+    pub fn open_lots(&self) -> impl Iterator<Item = (&TxnAccount, &Lot)> {
+        self.open
+            .iter()
+            .flat_map(|(acc, lots)| lots.iter().map(move |l| (acc, l)))
+    }
+
+    /// All lot closings, in the order of closing transactions
+    pub fn closings(&self) -> &[LotClosing] {
+        &self.closings
+    }
+
+    fn close(
+        &mut self,
+        txn: &Transaction,
+        p: &Posting,
+        method: LotMethod,
+    ) -> Result<(), tackler::Error> {
+        let lot_error = |msg: String| -> tackler::Error {
+            let uuid = txn
+                .header
+                .uuid
+                .map(|u| format!(", txn uuid: {u}"))
+                .unwrap_or_default();
+            format!(
+                "Lot error: {} (account: '{}', txn: {}{})",
+                msg,
+                p.acctn.atn.account,
+                txn_ts::rfc_3339(&txn.header.timestamp),
+                uuid
+            )
+            .into()
+        };
+
+        if method == LotMethod::Specific && p.cost_basis.is_none() {
+            let msg = "lot method is 'specific', but closing posting has no cost basis";
+            return Err(lot_error(msg.to_string()));
+        }
+
+        let Some(lots) = self.open.get_mut(&p.acctn) else {
+            let msg = format!(
+                "closing {} {}, but there are no open lots",
+                -p.amount, p.acctn.comm.name
+            );
+            return Err(lot_error(msg));
+        };
+
+        let mut idx: Vec<usize> = lots
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| p.cost_basis.as_ref().is_none_or(|cb| *cb == l.cost_basis))
+            .map(|(i, _)| i)
+            .collect();
+
+        let units = -p.amount;
+        let held: Decimal = idx.iter().map(|i| lots[*i].units).sum();
+        if units > held {
+            let msg = format!(
+                "closing {} {}{}, but there is only {} in open lots",
+                units,
+                p.acctn.comm.name,
+                p.cost_basis
+                    .as_ref()
+                    .map(|cb| format!(" {cb}"))
+                    .unwrap_or_default(),
+                held
+            );
+            return Err(lot_error(msg));
+        }
+
+        if method == LotMethod::Lifo {
+            idx.reverse();
+        }
+
+        let unit_price = if p.txn_commodity.is_any() && p.txn_commodity != p.acctn.comm {
+            Some((p.txn_amount / p.amount, p.txn_commodity.clone()))
+        } else {
+            None
+        };
+
+        let mut to_close = units;
+        for i in idx {
+            if to_close.is_zero() {
+                break;
+            }
+            let lot = &mut lots[i];
+            let closed = to_close.min(lot.units);
+            lot.units -= closed;
+            to_close -= closed;
+
+            self.closings.push(LotClosing {
+                ts: txn.header.timestamp.clone(),
+                account: p.acctn.clone(),
+                opened: lot.opened.clone(),
+                units: closed,
+                cost_basis: lot.cost_basis.clone(),
+                unit_price: unit_price.clone(),
+            });
+        }
+        lots.retain(|l| !l.units.is_zero());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use rust_decimal_macros::dec;
+    use tackler_rs::IndocUtils;
+
+    fn lots_from(input: &str, method: LotMethod) -> Result<Lots, tackler::Error> {
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut input.strip_margin().as_str(), &mut settings)?;
+        let txn_set = txn_data.get_all()?;
+        Lots::from(&txn_set.txns, method)
+    }
+
+    const TXNS: &str = indoc!(
+        "|2024-01-01 'buy 1
+         | Assets:Invest  10 ACME {10 €} @ 10 €
+         | Assets:Cash
+         |
+         |2024-02-01 'buy 2
+         | Assets:Invest  10 ACME {12 €} @ 12 €
+         | Assets:Cash
+         |
+         |2024-03-01 'sell
+         | Assets:Invest  -15 ACME @ 15 €
+         | Assets:Cash
+         |"
+    );
+
+    #[test]
+    fn lots_fifo() {
+        let lots = lots_from(TXNS, LotMethod::Fifo).unwrap(/*:test:*/);
+
+        let closings = lots.closings();
+        assert_eq!(closings.len(), 2);
+        assert_eq!(closings[0].units, dec!(10));
+        assert_eq!(closings[0].realized_gain(), Some(dec!(50)));
+        assert_eq!(closings[1].units, dec!(5));
+        assert_eq!(closings[1].realized_gain(), Some(dec!(15)));
+
+        let open: Vec<_> = lots.open_lots().collect();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].1.units, dec!(5));
+        assert_eq!(open[0].1.cost_basis.unit_cost, dec!(12));
+    }
+
+    #[test]
+    fn lots_lifo() {
+        let lots = lots_from(TXNS, LotMethod::Lifo).unwrap(/*:test:*/);
+
+        let closings = lots.closings();
+        assert_eq!(closings.len(), 2);
+        assert_eq!(closings[0].units, dec!(10));
+        assert_eq!(closings[0].cost_basis.unit_cost, dec!(12));
+        assert_eq!(closings[1].units, dec!(5));
+        assert_eq!(closings[1].cost_basis.unit_cost, dec!(10));
+
+        let open: Vec<_> = lots.open_lots().collect();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].1.units, dec!(5));
+        assert_eq!(open[0].1.cost_basis.unit_cost, dec!(10));
+    }
+
+    #[test]
+    fn lots_specific() {
+        let txns = indoc!(
+            "|2024-01-01 'buy 1
+             | Assets:Invest  10 ACME {10 €} @ 10 €
+             | Assets:Cash
+             |
+             |2024-02-01 'buy 2
+             | Assets:Invest  10 ACME {12 €} @ 12 €
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Invest  -4 ACME {12 €} @ 15 €
+             | Assets:Cash
+             |"
+        );
+        let lots = lots_from(txns, LotMethod::Specific).unwrap(/*:test:*/);
+
+        let closings = lots.closings();
+        assert_eq!(closings.len(), 1);
+        assert_eq!(closings[0].units, dec!(4));
+        assert_eq!(closings[0].realized_gain(), Some(dec!(12)));
+        assert_eq!(lots.open_lots().count(), 2);
+    }
+
+    #[test]
+    fn lots_specific_without_cost_basis() {
+        let res = lots_from(TXNS, LotMethod::Specific);
+        assert!(res.is_err());
+        assert!(res.err().unwrap(/*:test:*/).to_string().contains("no cost basis"));
+    }
+
+    #[test]
+    fn lots_reject_over_closing() {
+        let txns = indoc!(
+            "|2024-01-01 'buy
+             | Assets:Invest  10 ACME {10 €} @ 10 €
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Invest  -11 ACME @ 15 €
+             | Assets:Cash
+             |"
+        );
+        let res = lots_from(txns, LotMethod::Fifo);
+        assert!(res.is_err());
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .contains("closing 11 ACME, but there is only 10 in open lots")
+        );
+    }
+
+    #[test]
+    fn lots_reject_over_closing_specific_lot() {
+        let txns = indoc!(
+            "|2024-01-01 'buy 1
+             | Assets:Invest  10 ACME {10 €} @ 10 €
+             | Assets:Cash
+             |
+             |2024-02-01 'buy 2
+             | Assets:Invest  10 ACME {12 €} @ 12 €
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Invest  -11 ACME {12 €} @ 15 €
+             | Assets:Cash
+             |"
+        );
+        let res = lots_from(txns, LotMethod::Fifo);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn lots_reject_closing_without_open_lots() {
+        let txns = indoc!(
+            "|2024-01-01 'buy
+             | Assets:Invest  10 ACME {10 €} @ 10 €
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Other  -1 ACME {10 €} @ 15 €
+             | Assets:Cash
+             |"
+        );
+        let res = lots_from(txns, LotMethod::Fifo);
+        assert!(res.is_err());
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .contains("closing 1 ACME, but there are no open lots")
+        );
+    }
+}
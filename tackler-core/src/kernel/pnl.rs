@@ -0,0 +1,428 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Realized and unrealized gains (PnL)
+//!
+//! Realized gains are calculated from lot closings: proceeds of the closing
+//! (unit price of closing posting) minus the cost basis of closed lots.
+//! Lot closings without unit price (e.g. transfers) don't realize any gains.
+//!
+//! Unrealized gains are calculated from open lots: value of the open position
+//! at the time of valuation minus the cost basis of open lots. Open positions
+//! can only be valued when there is price conversion (`report.commodity` and
+//! price lookup) active.
+//!
+//! With price conversion, cost and proceeds are converted into report commodity,
+//! cost at the time of opening, and proceeds at the time of closing.
+//!
+//! Lots are tracked with all txns up to the end of the selected txns,
+//! so lots could be opened by txns which are not selected (e.g. by time filter),
+//! but only the closings of the selected txns are realized.
+use crate::kernel::Settings;
+use crate::kernel::lots::Lots;
+use crate::kernel::price_lookup::{PriceLookup, PriceLookupCtx};
+use crate::kernel::report_item_selector::PnlSelector;
+use crate::model::{Commodity, TxnAccount, TxnSet};
+use crate::tackler;
+use jiff::Zoned;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tackler_api::txn_ts;
+
+/// PnL of one account and position commodity
+#[derive(Debug, Clone)]
+pub struct PnlItem {
+    /// Account and commodity of the position
+    pub(crate) acctn: TxnAccount,
+    /// Commodity of gains (cost basis commodity or report commodity)
+    pub(crate) gain_commodity: Arc<Commodity>,
+    pub(crate) realized: Decimal,
+    pub(crate) open_units: Decimal,
+    pub(crate) open_cost: Decimal,
+    /// Value of open units, `None` if it can't be valued
+    pub(crate) open_value: Option<Decimal>,
+}
+
+impl PnlItem {
+    pub(crate) fn unrealized(&self) -> Option<Decimal> {
+        self.open_value.map(|v| v - self.open_cost)
+    }
+}
+
+/// PnL sums of one position commodity, or of all positions (without position commodity)
+#[derive(Debug, Clone)]
+pub struct PnlSum {
+    pub(crate) commodity: Option<Arc<Commodity>>,
+    pub(crate) gain_commodity: Arc<Commodity>,
+    pub(crate) realized: Decimal,
+    pub(crate) open_units: Decimal,
+    pub(crate) unrealized: Option<Decimal>,
+}
+
+#[derive(Debug)]
+pub struct Pnl {
+    pub(crate) title: String,
+    /// Items by account, and then by commodities
+    pub(crate) items: Vec<PnlItem>,
+}
+
+impl Pnl {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn sums<F>(&self, key: F) -> Vec<PnlSum>
+    where
+        F: Fn(&PnlItem) -> Option<Arc<Commodity>>,
+    {
+        let mut sums: BTreeMap<(Option<Arc<Commodity>>, Arc<Commodity>), PnlSum> = BTreeMap::new();
+        for item in &self.items {
+            let comm = key(item);
+            let sum = sums
+                .entry((comm.clone(), item.gain_commodity.clone()))
+                .or_insert_with(|| PnlSum {
+                    commodity: comm,
+                    gain_commodity: item.gain_commodity.clone(),
+                    realized: Decimal::ZERO,
+                    open_units: Decimal::ZERO,
+                    unrealized: Some(Decimal::ZERO),
+                });
+            sum.realized += item.realized;
+            sum.open_units += item.open_units;
+            sum.unrealized = sum.unrealized.zip(item.unrealized()).map(|(s, u)| s + u);
+        }
+        sums.into_values().collect()
+    }
+
+    /// PnL sums by position commodity
+    pub(crate) fn by_commodity(&self) -> Vec<PnlSum> {
+        self.sums(|item| Some(item.acctn.comm.clone()))
+    }
+
+    /// PnL totals by gain commodity
+    pub(crate) fn totals(&self) -> Vec<PnlSum> {
+        self.sums(|_| None)
+    }
+}
+
+type PnlItems = BTreeMap<(TxnAccount, Arc<Commodity>), PnlItem>;
+
+impl Pnl {
+    fn get_item<'a>(
+        items: &'a mut PnlItems,
+        acctn: &TxnAccount,
+        gain_commodity: Arc<Commodity>,
+        is_valued: bool,
+    ) -> &'a mut PnlItem {
+        items
+            .entry((acctn.clone(), gain_commodity.clone()))
+            .or_insert_with(|| PnlItem {
+                acctn: acctn.clone(),
+                gain_commodity,
+                realized: Decimal::ZERO,
+                open_units: Decimal::ZERO,
+                open_cost: Decimal::ZERO,
+                open_value: is_valued.then_some(Decimal::ZERO),
+            })
+    }
+
+    pub(crate) fn from<T: PnlSelector + ?Sized>(
+        title: &str,
+        txns: &TxnSet<'_>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        acc_sel: &T,
+        settings: &Settings,
+    ) -> Result<Pnl, tackler::Error> {
+        let Some(period_end) = txns.txns.last() else {
+            return Ok(Pnl {
+                title: title.to_string(),
+                items: Vec::new(),
+            });
+        };
+
+        // Lots are tracked with all txns up to the end of selected txns, so that
+        // lots opened by non-selected txns are known. Only closings of selected
+        // txns are realized.
+        let method = settings.get_lot_method();
+        let mut lots = Lots::default();
+        let mut selected = txns.txns.iter().peekable();
+        let mut realized_closings = Vec::new();
+        for txn in &txns.all_txns {
+            let first_closing = lots.closings().len();
+            lots.add(txn, method)?;
+            if selected.next_if(|s| std::ptr::eq(**s, *txn)).is_some() {
+                realized_closings.push(first_closing..lots.closings().len());
+            }
+            if std::ptr::eq(*txn, *period_end) {
+                break;
+            }
+        }
+
+        let valuation_ts = match settings.get_price_lookup() {
+            PriceLookup::GivenTime(ts) => ts,
+            _ => period_end.header.timestamp.clone(),
+        };
+
+        // Convert amount to report commodity at the time of `ts`, if it's possible
+        let convert = |amount: Decimal, comm: &Arc<Commodity>, ts: &Zoned| match price_lookup_ctx
+            .in_commodity()
+        {
+            Some(in_comm) if in_comm != comm => match price_lookup_ctx.price_at(comm, ts) {
+                Some(rate) => (amount * rate, in_comm.clone()),
+                None => (amount, comm.clone()),
+            },
+            _ => (amount, comm.clone()),
+        };
+
+        let is_valued = price_lookup_ctx.in_commodity().is_some();
+        let mut items: PnlItems = BTreeMap::new();
+
+        for closing in realized_closings
+            .into_iter()
+            .flat_map(|r| &lots.closings()[r])
+        {
+            if !acc_sel.eval(&closing.account) {
+                continue;
+            }
+            let Some((unit_price, price_comm)) = &closing.unit_price else {
+                continue;
+            };
+            let cb = &closing.cost_basis;
+            let (proceeds, proceeds_comm) =
+                convert(closing.units * unit_price, price_comm, &closing.ts);
+            let (cost, cost_comm) =
+                convert(closing.units * cb.unit_cost, &cb.commodity, &closing.opened);
+
+            if proceeds_comm != cost_comm {
+                let msg = format!(
+                    "PnL error: can't calculate realized gain, proceeds are in '{}', but cost is in '{}' (account: '{}', txn: {})",
+                    proceeds_comm.name,
+                    cost_comm.name,
+                    closing.account.atn.account,
+                    txn_ts::rfc_3339(&closing.ts),
+                );
+                return Err(msg.into());
+            }
+            let item = Self::get_item(&mut items, &closing.account, cost_comm, is_valued);
+            item.realized += proceeds - cost;
+        }
+
+        for (acctn, lot) in lots.open_lots() {
+            if !acc_sel.eval(acctn) {
+                continue;
+            }
+            let cb = &lot.cost_basis;
+            let (cost, cost_comm) = convert(lot.units * cb.unit_cost, &cb.commodity, &lot.opened);
+
+            let value = match price_lookup_ctx.in_commodity() {
+                Some(in_comm) if *in_comm == cost_comm => {
+                    if acctn.comm == *in_comm {
+                        Some(lot.units)
+                    } else {
+                        price_lookup_ctx
+                            .price_of(&acctn.comm, &valuation_ts)
+                            .map(|rate| lot.units * rate)
+                    }
+                }
+                _ => None,
+            };
+            let item = Self::get_item(&mut items, acctn, cost_comm, is_valued);
+            item.open_units += lot.units;
+            item.open_cost += cost;
+            item.open_value = item.open_value.zip(value).map(|(s, v)| s + v);
+        }
+
+        Ok(Pnl {
+            title: title.to_string(),
+            items: items.into_values().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::report_item_selector::{PnlAllSelector, PnlByAccountSelector};
+    use crate::parser;
+    use indoc::indoc;
+    use jiff::tz::TimeZone;
+    use rust_decimal_macros::dec;
+    use tackler_api::filters::FilterDefinition;
+    use tackler_rs::IndocUtils;
+
+    const TXNS: &str = indoc!(
+        "|2024-01-01 'buy 1
+         | Assets:Invest  10 ACME {10 €} @ 10 €
+         | Assets:Cash
+         |
+         |2024-02-01 'buy 2
+         | Assets:Invest  10 ACME {12 €} @ 12 €
+         | Assets:Cash
+         |
+         |2024-03-01 'sell
+         | Assets:Invest  -15 ACME @ 15 €
+         | Assets:Cash
+         |"
+    );
+
+    fn pnl_from<T: PnlSelector>(
+        input: &str,
+        settings: &mut Settings,
+        acc_sel: &T,
+    ) -> Result<Pnl, tackler::Error> {
+        let txn_data = parser::string_to_txns(&mut input.strip_margin().as_str(), settings)?;
+        let txn_set = txn_data.get_all()?;
+        Pnl::from(
+            "PnL",
+            &txn_set,
+            &PriceLookupCtx::default(),
+            acc_sel,
+            settings,
+        )
+    }
+
+    #[test]
+    fn pnl_realized() {
+        let mut settings = Settings::default();
+        let pnl = pnl_from(TXNS, &mut settings, &PnlAllSelector::default()).unwrap(/*:test:*/);
+
+        assert_eq!(pnl.items.len(), 1);
+        let item = &pnl.items[0];
+        assert_eq!(item.acctn.atn.account, "Assets:Invest");
+        assert_eq!(item.acctn.comm.name, "ACME");
+        assert_eq!(item.gain_commodity.name, "€");
+        assert_eq!(item.realized, dec!(65));
+        assert_eq!(item.open_units, dec!(5));
+        assert_eq!(item.open_cost, dec!(60));
+        assert_eq!(item.unrealized(), None);
+
+        let totals = pnl.totals();
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].realized, dec!(65));
+        assert_eq!(totals[0].unrealized, None);
+    }
+
+    #[test]
+    fn pnl_unrealized() {
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut TXNS.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let price_db = parser::pricedb_from_str(&mut "P 2024-04-01 ACME 20 €\n", &mut settings).unwrap(/*:test:*/);
+        let eur = settings.get_commodity("€").unwrap(/*:test:*/);
+
+        let price_lookup_ctx = PriceLookup::LastPriceDbEntry.make_ctx(&txn_set.txns, Some(eur), &price_db).unwrap(/*:test:*/);
+        let pnl = Pnl::from(
+            "PnL",
+            &txn_set,
+            &price_lookup_ctx,
+            &PnlAllSelector::default(),
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+
+        assert_eq!(pnl.items.len(), 1);
+        let item = &pnl.items[0];
+        assert_eq!(item.realized, dec!(65));
+        assert_eq!(item.open_value, Some(dec!(100)));
+        assert_eq!(item.unrealized(), Some(dec!(40)));
+
+        let by_comm = pnl.by_commodity();
+        assert_eq!(by_comm.len(), 1);
+        assert_eq!(by_comm[0].open_units, dec!(5));
+        assert_eq!(by_comm[0].unrealized, Some(dec!(40)));
+    }
+
+    #[test]
+    fn pnl_by_account() {
+        let mut settings = Settings::default();
+        let acc_sel = PnlByAccountSelector::from(&["Assets:Other"]).unwrap(/*:test:*/);
+        let pnl = pnl_from(TXNS, &mut settings, &acc_sel).unwrap(/*:test:*/);
+
+        assert!(pnl.is_empty());
+    }
+
+    #[test]
+    fn pnl_filtered_txns() {
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut TXNS.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let filter =
+            FilterDefinition::from_expr("ts >= 2024-02-15", TimeZone::UTC).unwrap(/*:test:*/);
+        let txn_set = txn_data.filter(&filter).unwrap(/*:test:*/);
+        assert_eq!(txn_set.txns.len(), 1);
+
+        // lots are opened by txns outside of the filter
+        let pnl = Pnl::from(
+            "PnL",
+            &txn_set,
+            &PriceLookupCtx::default(),
+            &PnlAllSelector::default(),
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(pnl.items.len(), 1);
+        assert_eq!(pnl.items[0].realized, dec!(65));
+        assert_eq!(pnl.items[0].open_units, dec!(5));
+
+        // closing is outside of the filter, open lots are at the end of period
+        let filter =
+            FilterDefinition::from_expr("ts < 2024-02-15", TimeZone::UTC).unwrap(/*:test:*/);
+        let txn_set = txn_data.filter(&filter).unwrap(/*:test:*/);
+        let pnl = Pnl::from(
+            "PnL",
+            &txn_set,
+            &PriceLookupCtx::default(),
+            &PnlAllSelector::default(),
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(pnl.items.len(), 1);
+        assert_eq!(pnl.items[0].realized, dec!(0));
+        assert_eq!(pnl.items[0].open_units, dec!(20));
+        assert_eq!(pnl.items[0].open_cost, dec!(220));
+    }
+
+    #[test]
+    fn pnl_cost_at_opening_time() {
+        let mut settings = Settings::default();
+        let txns = indoc!(
+            "|2024-01-01 'buy
+             | Assets:Invest  10 ACME {10 EUR} @ 10 EUR
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Invest  -4 ACME @ 15 EUR
+             | Assets:Cash
+             |"
+        );
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let price_db = parser::pricedb_from_str(
+            &mut "P 2023-12-01 EUR 1.00 USD\nP 2024-02-01 EUR 2.00 USD\nP 2024-04-01 ACME 20 USD\n",
+            &mut settings,
+        )
+        .unwrap(/*:test:*/);
+        let usd = settings.get_commodity("USD").unwrap(/*:test:*/);
+
+        let price_lookup_ctx = PriceLookup::LastPriceDbEntry.make_ctx(&txn_set.txns, Some(usd), &price_db).unwrap(/*:test:*/);
+        let pnl = Pnl::from(
+            "PnL",
+            &txn_set,
+            &price_lookup_ctx,
+            &PnlAllSelector::default(),
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+
+        assert_eq!(pnl.items.len(), 1);
+        let item = &pnl.items[0];
+        assert_eq!(item.gain_commodity.name, "USD");
+        // proceeds: 4 * 15 * 2.00, cost: 4 * 10 * 1.00
+        assert_eq!(item.realized, dec!(80));
+        // cost: 6 * 10 * 1.00, value: 6 * 20
+        assert_eq!(item.open_cost, dec!(60));
+        assert_eq!(item.open_value, Some(dec!(120)));
+    }
+}
@@ -7,28 +7,100 @@ use crate::model::{
     Commodity, Transaction, TxnAccount, TxnRefs,
     price_entry::{PriceDb, PriceEntry},
 };
+use crate::tackler;
 use itertools::Itertools;
 use jiff::tz::TimeZone;
 use jiff::{Timestamp, Zoned};
 use rust_decimal::Decimal;
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     sync::Arc,
 };
 use tackler_api::metadata::items::{PriceRecord, PriceRecords};
+use tackler_api::txn_ts::rfc_3339;
+
+/// One hop of price conversion path
+///
+/// Hop is based on price entries of one commodity pair (base → eq).
+/// Inverse hop converts from eq commodity to base commodity
+/// with the inverse of the price.
+#[derive(Debug, Clone)]
+struct Hop<'p> {
+    base: Arc<Commodity>,
+    eq: Arc<Commodity>,
+    /// Price entries of the pair, sorted by timestamp
+    entries: Vec<&'p PriceEntry>,
+    /// Rates of price entries (inverse of price with inverse hop)
+    rates: Vec<Decimal>,
+    inverse: bool,
+    /// First zero price of inverse hop, which has no inverse
+    zero_price: Option<&'p PriceEntry>,
+}
+
+impl Hop<'_> {
+    /// Last known rate at the time of `ts`
+    fn rate_at(&self, ts: &Zoned) -> Option<Decimal> {
+        let i = match self.entries.binary_search_by_key(&ts, |e| &e.timestamp) {
+            Ok(i) => Some(i),
+            Err(i) => i.checked_sub(1),
+        };
+        i.map(|i| self.rates[i])
+    }
+
+    /// Last rate of the hop
+    fn last_rate(&self) -> Option<Decimal> {
+        self.rates.last().copied()
+    }
+
+    /// Time of the first price entry of the hop
+    fn since(&self) -> Option<&Zoned> {
+        self.entries
+            .first()
+            .into_iter()
+            .chain(self.zero_price.as_ref())
+            .map(|e| &e.timestamp)
+            .min()
+    }
+}
+
+/// Hops by commodity
+///
+/// These are conversion paths by source commodity,
+/// or hops from commodity to other commodities (price graph).
+type Paths<'p> = HashMap<Arc<Commodity>, Vec<Hop<'p>>>;
 
 #[derive(Debug)]
 enum Cache<'p> {
-    Fixed(HashMap<Arc<Commodity>, (Zoned, Decimal)>),
-    Timed(HashMap<Arc<Commodity>, Vec<&'p PriceEntry>>),
+    /// Rates are the last price entry of each hop
+    Fixed(Paths<'p>),
+    /// Hops have all price entries, and rates are looked up at the time of txn
+    ///
+    /// Paths are by the time since they are valid (sorted by time).
+    Timed(Vec<(Zoned, Paths<'p>)>),
 }
-impl Cache<'_> {
-    fn is_empty(&self) -> bool {
+impl<'p> Cache<'p> {
+    /// Conversion paths at the time of `ts`
+    fn paths_at(&self, ts: &Zoned) -> Option<&Paths<'p>> {
         match &self {
-            Cache::Fixed(map) => map.is_empty(),
-            Cache::Timed(map) => map.is_empty(),
+            Cache::Fixed(paths) => Some(paths),
+            Cache::Timed(paths) => {
+                let i = paths.partition_point(|(since, _)| since <= ts);
+                i.checked_sub(1).map(|i| &paths[i].1)
+            }
+        }
+    }
+
+    /// Hops of all conversion paths
+    fn hops(&self) -> Box<dyn Iterator<Item = &Hop<'p>> + '_> {
+        match &self {
+            Cache::Fixed(paths) => Box::new(paths.values().flatten()),
+            Cache::Timed(paths) => Box::new(paths.iter().flat_map(|(_, p)| p.values().flatten())),
         }
     }
+
+    fn is_empty(&self) -> bool {
+        self.hops().next().is_none()
+    }
 }
 
 #[derive(Debug)]
@@ -53,38 +125,71 @@ impl PriceLookupCtx<'_> {
 }
 
 impl PriceLookupCtx<'_> {
-    pub fn metadata(&self) -> PriceRecords {
-        let rates = if let Some(target) = self.in_commodity.clone() {
-            let r = match &self.cache {
-                Cache::Fixed(map) => map
-                    .iter()
-                    .sorted_by_key(|k| *k)
-                    .map(|(k, v)| PriceRecord {
-                        ts: Some(v.0.clone()),
-                        source: k.name.clone(),
-                        rate: Some(format!("{}", v.1)),
-                        target: target.name.clone(),
-                    })
-                    .collect(),
-                Cache::Timed(map) => map
-                    .iter()
-                    .sorted_by_key(|k| *k)
-                    .map(|(k, _)| PriceRecord {
-                        ts: None,
-                        source: k.name.clone(),
-                        rate: None,
-                        target: target.name.clone(),
-                    })
-                    .collect(),
-            };
-            r
-        } else {
-            Vec::new()
-        };
+    /// Price records of all used hops (price entries)
+    ///
+    /// Inverse hops are listed with their original price entries,
+    /// so that all records could be found from the price database.
+    /// Rates are formatted with `fmt_rate`.
+    pub fn metadata<F: Fn(&Decimal) -> String>(&self, fmt_rate: F) -> PriceRecords {
+        if self.in_commodity.is_none() {
+            return PriceRecords { rates: Vec::new() };
+        }
+        let hops = self
+            .cache
+            .hops()
+            .map(|hop| ((hop.base.clone(), hop.eq.clone()), hop))
+            .collect::<BTreeMap<_, _>>();
+
+        let rates = hops
+            .into_values()
+            .map(|hop| {
+                let entry = match &self.cache {
+                    Cache::Fixed(_) => hop.entries.last(),
+                    Cache::Timed(_) => None,
+                };
+                PriceRecord {
+                    ts: entry.map(|e| e.timestamp.clone()),
+                    source: hop.base.name.clone(),
+                    rate: entry.map(|e| fmt_rate(&e.eq_amount)),
+                    target: hop.eq.name.clone(),
+                }
+            })
+            .collect();
 
         PriceRecords { rates }
     }
 
+    /// Target commodity of price conversion, if any
+    pub(crate) fn in_commodity(&self) -> Option<&Arc<Commodity>> {
+        self.in_commodity.as_ref()
+    }
+
+    /// Price of one unit of `comm` in target commodity
+    ///
+    /// With fixed price lookup (last price, given time) `ts` is ignored,
+    /// otherwise the price of each hop is the last known price at the time of `ts`.
+    pub(crate) fn price_of(&self, comm: &Arc<Commodity>, ts: &Zoned) -> Option<Decimal> {
+        self.in_commodity.as_ref()?;
+        match &self.cache {
+            Cache::Fixed(paths) => paths.get(comm)?.iter().map(Hop::last_rate).product(),
+            Cache::Timed(_) => self.price_at(comm, ts),
+        }
+    }
+
+    /// Price of one unit of `comm` in target commodity at the time of `ts`
+    ///
+    /// The price of each hop is the last known price at the time of `ts`
+    /// with all price lookup types, e.g. for cost at the time of lot opening.
+    pub(crate) fn price_at(&self, comm: &Arc<Commodity>, ts: &Zoned) -> Option<Decimal> {
+        self.in_commodity.as_ref()?;
+        self.cache
+            .paths_at(ts)?
+            .get(comm)?
+            .iter()
+            .map(|h| h.rate_at(ts))
+            .product()
+    }
+
     #[inline]
     pub(crate) fn convert_prices<'r, 's, 't>(
         &'s self,
@@ -110,47 +215,192 @@ impl PriceLookupCtx<'_> {
         't: 'r,
     {
         Box::new(txn.posts.iter().map(move |p| {
-            if p.acctn.comm.is_any() {
-                let mut acctn = p.acctn.clone();
-                let mut amount = p.amount;
-                match &self.cache {
-                    Cache::Fixed(cache) => {
-                        if let Some(c) = cache.get(&p.acctn.comm) {
-                            acctn.comm = in_commodity.clone();
-                            amount *= c.1;
-                        }
-                        (acctn, amount, None)
-                    }
-                    Cache::Timed(comm_cache) => {
-                        if let Some(cache) = comm_cache.get(&p.acctn.comm) {
-                            let i = match cache.binary_search_by_key(
-                                &(&txn.header.timestamp, &p.acctn.comm),
-                                |e| (&e.timestamp, &e.base_commodity),
-                            ) {
-                                Ok(i) => Some(i),
-                                Err(i) => i.checked_sub(1),
-                            };
-                            let rate = if let Some(i) = i {
-                                acctn.comm = in_commodity.clone();
-                                amount *= cache[i].eq_amount;
-                                Some(cache[i].eq_amount)
-                            } else {
-                                None
-                            };
-                            (acctn, amount, rate)
-                        } else {
-                            // Cache miss
-                            (p.acctn.clone(), p.amount, None)
-                        }
-                    }
+            let rate = match p.acctn.comm.is_any() {
+                true => self.price_of(&p.acctn.comm, &txn.header.timestamp),
+                false => None,
+            };
+            match rate {
+                Some(rate) => {
+                    let mut acctn = p.acctn.clone();
+                    acctn.comm = in_commodity.clone();
+                    let txn_rate = match &self.cache {
+                        Cache::Fixed(_) => None,
+                        Cache::Timed(_) => Some(rate),
+                    };
+                    (acctn, p.amount * rate, txn_rate)
                 }
-            } else {
-                (p.acctn.clone(), p.amount, None)
+                // Cache miss
+                None => (p.acctn.clone(), p.amount, None),
             }
         }))
     }
 }
 
+/// Make price graph of price entries before `lookup_ts`
+///
+/// Hops have all price entries of the commodity pair (before `lookup_ts`).
+/// With lookup timestamp, the last entry of hop is its fixed price.
+/// Without lookup timestamp, inverse hop of zero price has its zero price entry.
+fn price_graph<'p>(
+    price_db: &'p PriceDb,
+    lookup_ts: Option<&Zoned>,
+) -> Result<Paths<'p>, tackler::Error> {
+    let mut pairs: BTreeMap<(Arc<Commodity>, Arc<Commodity>), Vec<&'p PriceEntry>> =
+        BTreeMap::new();
+    for e in price_db
+        .iter()
+        .filter(|e| e.base_commodity != e.eq_commodity)
+        .filter(|e| lookup_ts.is_none_or(|ts| e.timestamp < *ts))
+    {
+        pairs
+            .entry((e.base_commodity.clone(), e.eq_commodity.clone()))
+            .or_default()
+            .push(e);
+    }
+
+    let mut graph = Paths::new();
+    for ((base, eq), mut entries) in pairs {
+        entries.sort_by_key(|e| &e.timestamp); // make sure it's sorted
+        let inverse_entries: Vec<_> = entries
+            .iter()
+            .filter(|e| !e.eq_amount.is_zero())
+            .copied()
+            .collect();
+        let inverse_rates = inverse_entries
+            .iter()
+            .map(|e| {
+                Decimal::ONE.checked_div(e.eq_amount).ok_or_else(|| {
+                    let msg = format!(
+                        "Price error: inverse of price is out of range: {} {} {} {}",
+                        rfc_3339(&e.timestamp),
+                        e.base_commodity.name,
+                        e.eq_amount,
+                        e.eq_commodity.name
+                    );
+                    tackler::Error::from(msg)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // with fixed price, there is no inverse if the last price is zero
+        let has_inverse = match lookup_ts {
+            Some(_) => entries.last().is_some_and(|e| !e.eq_amount.is_zero()),
+            None => !inverse_entries.is_empty(),
+        };
+        let zero_price = match lookup_ts {
+            Some(_) => None,
+            None => entries.iter().find(|e| e.eq_amount.is_zero()).copied(),
+        };
+        if has_inverse || zero_price.is_some() {
+            graph.entry(eq.clone()).or_default().push(Hop {
+                base: base.clone(),
+                eq: eq.clone(),
+                entries: inverse_entries,
+                rates: inverse_rates,
+                inverse: true,
+                zero_price,
+            });
+        }
+        let rates = entries.iter().map(|e| e.eq_amount).collect();
+        graph.entry(base.clone()).or_default().push(Hop {
+            base,
+            eq,
+            entries,
+            rates,
+            inverse: false,
+            zero_price: None,
+        });
+    }
+    Ok(graph)
+}
+
+/// Shortest conversion path from `from` to `to`
+///
+/// Path is searched breadth-first, so it has the least count of hops.
+/// With paths of the same length, the path is selected by commodity names,
+/// and direct prices are preferred over inverse prices.
+fn find_path<'p>(
+    graph: &Paths<'p>,
+    from: &Arc<Commodity>,
+    to: &Arc<Commodity>,
+) -> Option<Vec<Hop<'p>>> {
+    let target_of = |hop: &Hop<'p>| match hop.inverse {
+        true => hop.base.clone(),
+        false => hop.eq.clone(),
+    };
+
+    let mut prev: HashMap<Arc<Commodity>, &Hop<'p>> = HashMap::new();
+    let mut queue = VecDeque::from([from.clone()]);
+    while let Some(comm) = queue.pop_front() {
+        if comm == *to {
+            let mut path = Vec::new();
+            let mut c = comm;
+            while let Some(hop) = prev.get(&c) {
+                path.push((*hop).clone());
+                c = match hop.inverse {
+                    true => hop.eq.clone(),
+                    false => hop.base.clone(),
+                };
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let Some(hops) = graph.get(&comm) else {
+            continue;
+        };
+        for hop in hops.iter().sorted_by_key(|h| (target_of(h), h.inverse)) {
+            let target = target_of(hop);
+            if target != *from && !prev.contains_key(&target) {
+                prev.insert(target.clone(), hop);
+                queue.push_back(target);
+            }
+        }
+    }
+    None
+}
+
+/// Conversion paths of `commodities` into `in_commodity`
+///
+/// It's an error, if the path has an inverse hop of zero price.
+fn conversion_paths<'p>(
+    graph: &Paths<'p>,
+    commodities: &BTreeSet<Arc<Commodity>>,
+    in_commodity: &Arc<Commodity>,
+) -> Result<Paths<'p>, tackler::Error> {
+    let mut paths = Paths::new();
+    for comm in commodities.iter().filter(|comm| *comm != in_commodity) {
+        let Some(path) = find_path(graph, comm, in_commodity) else {
+            continue;
+        };
+        if let Some(e) = path.iter().find_map(|hop| hop.zero_price) {
+            let msg = format!(
+                "Price error: there is no inverse of zero price: {} {} {} {}",
+                rfc_3339(&e.timestamp),
+                e.base_commodity.name,
+                e.eq_amount,
+                e.eq_commodity.name
+            );
+            return Err(msg.into());
+        }
+        paths.insert(comm.clone(), path);
+    }
+    Ok(paths)
+}
+
+/// Price graph with hops which have price entries at the time of `ts`
+fn price_graph_at<'p>(graph: &Paths<'p>, ts: &Zoned) -> Paths<'p> {
+    graph
+        .iter()
+        .map(|(comm, hops)| {
+            let hops = hops
+                .iter()
+                .filter(|hop| hop.since().is_some_and(|since| since <= ts))
+                .cloned()
+                .collect();
+            (comm.clone(), hops)
+        })
+        .collect()
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum PriceLookup {
     #[default]
@@ -166,17 +416,17 @@ impl PriceLookup {
         txns: &TxnRefs<'_>,
         in_commodity: Option<Arc<Commodity>>,
         price_db: &'p PriceDb,
-    ) -> PriceLookupCtx<'p> {
+    ) -> Result<PriceLookupCtx<'p>, tackler::Error> {
         let Some(in_commodity) = in_commodity else {
             // No commodity conversion, short-circuit out
-            return PriceLookupCtx::default();
+            return Ok(PriceLookupCtx::default());
         };
         let lookup_timestamp = match self {
             PriceLookup::AtTheTimeOfTxn => None,
             PriceLookup::LastPriceDbEntry => Some(Timestamp::MAX.to_zoned(TimeZone::UTC)),
             PriceLookup::GivenTime(t) => Some(t.clone()),
 
-            PriceLookup::None => return PriceLookupCtx::default(),
+            PriceLookup::None => return Ok(PriceLookupCtx::default()),
         };
         //
         // Ok, we have real commodity conversion case
@@ -188,38 +438,249 @@ impl PriceLookup {
             .map(|p| p.acctn.comm.clone())
             .collect::<BTreeSet<_>>();
 
+        let graph = price_graph(price_db, lookup_timestamp.as_ref())?;
         let cache = match lookup_timestamp {
-            Some(lookup_timestamp) => Cache::Fixed(
-                price_db
-                    .iter()
-                    .filter(|e| {
-                        used_commodities.contains(&e.base_commodity)
-                            && e.eq_commodity == in_commodity
-                            && e.timestamp < lookup_timestamp
-                    })
-                    .map(|e| (e.base_commodity.clone(), (e.timestamp.clone(), e.eq_amount)))
-                    .collect(),
-            ),
+            Some(_) => Cache::Fixed(conversion_paths(&graph, &used_commodities, &in_commodity)?),
             None => {
-                let mut cache = HashMap::new();
-                for comm in used_commodities {
-                    let comm_cache: Vec<_> = price_db
-                        .iter()
-                        .filter(|e| comm == e.base_commodity && e.eq_commodity == in_commodity)
-                        .sorted_by_key(|e| &e.timestamp) // make sure it's sorted
-                        .collect();
-
-                    if !comm_cache.is_empty() {
-                        cache.insert(comm, comm_cache);
-                    }
-                }
-                Cache::Timed(cache)
+                // Paths are searched only with hops which have prices at the time of lookup,
+                // so paths are searched again each time when there is a new hop
+                let paths = graph
+                    .values()
+                    .flatten()
+                    .filter_map(Hop::since)
+                    .sorted()
+                    .dedup()
+                    .map(|since| {
+                        let graph_at = price_graph_at(&graph, since);
+                        let paths = conversion_paths(&graph_at, &used_commodities, &in_commodity)?;
+                        Ok((since.clone(), paths))
+                    })
+                    .collect::<Result<Vec<_>, tackler::Error>>()?;
+                Cache::Timed(paths)
             }
         };
 
-        PriceLookupCtx {
+        Ok(PriceLookupCtx {
             cache,
             in_commodity: Some(in_commodity),
-        }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    fn setup() -> (Settings, PriceDb) {
+        let mut settings = Settings::default();
+        let price_db = indoc!(
+            "|P 2024-01-01 SEK 0.09 EUR
+             |P 2024-01-01 EUR 1.25 USD
+             |P 2024-01-01 ACME 100 USD
+             |P 2024-02-01 EUR 1.60 USD
+             |"
+        )
+        .strip_margin();
+        let price_db =
+            parser::pricedb_from_str(&mut price_db.as_str(), &mut settings).unwrap(/*:test:*/);
+        (settings, price_db)
+    }
+
+    fn comm(settings: &Settings, name: &str) -> Arc<Commodity> {
+        settings.get_commodity(name).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn multi_hop_and_inverse_prices() {
+        let (mut settings, price_db) = setup();
+        let journal = indoc!(
+            "|2024-01-15 'sek
+             | Assets:Sek  100 SEK
+             | Equity:Sek
+             |
+             |2024-01-16 'acme
+             | Assets:Broker  1 ACME
+             | Equity:Acme
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let usd = comm(&settings, "USD");
+        let sek = comm(&settings, "SEK");
+        let acme = comm(&settings, "ACME");
+        let jan = settings.parse_timestamp("2024-01-20").unwrap(/*:test:*/);
+
+        // SEK -> EUR -> USD, at the time of txn
+        let ctx = PriceLookup::AtTheTimeOfTxn.make_ctx(&txn_set.txns, Some(usd.clone()), &price_db).unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&sek, &jan), Some(Decimal::new(1125, 4)));
+        assert_eq!(ctx.price_of(&acme, &jan), Some(Decimal::from(100)));
+
+        // ACME -> USD -> EUR (inverse), with the last price
+        let eur = comm(&settings, "EUR");
+        let ctx = PriceLookup::LastPriceDbEntry.make_ctx(&txn_set.txns, Some(eur), &price_db).unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&acme, &jan), Some(Decimal::new(625, 1)));
+
+        let records: Vec<_> = ctx
+            .metadata(|r| r.to_string())
+            .rates
+            .iter()
+            .map(|r| (r.source.clone(), r.rate.clone(), r.target.clone()))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (
+                    "ACME".to_string(),
+                    Some("100".to_string()),
+                    "USD".to_string()
+                ),
+                (
+                    "EUR".to_string(),
+                    Some("1.60".to_string()),
+                    "USD".to_string()
+                ),
+                (
+                    "SEK".to_string(),
+                    Some("0.09".to_string()),
+                    "EUR".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_price_path() {
+        let (mut settings, price_db) = setup();
+        let journal = indoc!(
+            "|2024-01-15 'nok
+             | Assets:Nok  100 NOK
+             | Equity:Nok
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let usd = comm(&settings, "USD");
+        let ctx = PriceLookup::LastPriceDbEntry.make_ctx(&txn_set.txns, Some(usd), &price_db).unwrap(/*:test:*/);
+        assert!(ctx.is_empty());
+    }
+
+    #[test]
+    fn implicit_prices() {
+        let mut settings = Settings::default();
+        settings.price.implicit_prices = true;
+        let journal = indoc!(
+            "|2024-01-02 'buy
+             | Assets:Broker  10 ACME @ 120 USD
+             | Assets:Cash
+             |
+             |2024-01-03 'more
+             | Assets:Broker  1 ACME
+             | Equity:Acme
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let usd = comm(&settings, "USD");
+        let acme = comm(&settings, "ACME");
+        let ts = settings.parse_timestamp("2024-01-03").unwrap(/*:test:*/);
+
+        let price_db = settings.price.get_price_db(&txn_set.all_txns);
+        assert_eq!(price_db.len(), 1);
+        // settings are not modified
+        assert!(settings.price.price_db.is_empty());
+        let ctx = PriceLookup::AtTheTimeOfTxn.make_ctx(
+            &txn_set.txns,
+            Some(usd),
+            &price_db,
+        ).unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&acme, &ts), Some(Decimal::from(120)));
+    }
+    #[test]
+    fn timed_path_by_lookup_time() {
+        let mut settings = Settings::default();
+        let price_db = indoc!(
+            "|P 2024-01-01 ACME 10 EUR
+             |P 2024-01-01 EUR 1.25 USD
+             |P 2024-03-01 ACME 13 USD
+             |"
+        )
+        .strip_margin();
+        let price_db =
+            parser::pricedb_from_str(&mut price_db.as_str(), &mut settings).unwrap(/*:test:*/);
+        let journal = indoc!(
+            "|2024-01-15 'acme
+             | Assets:Broker  1 ACME
+             | Equity:Acme
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let usd = comm(&settings, "USD");
+        let acme = comm(&settings, "ACME");
+        let ctx = PriceLookup::AtTheTimeOfTxn.make_ctx(&txn_set.txns, Some(usd), &price_db).unwrap(/*:test:*/);
+
+        // direct price is not known yet: ACME -> EUR -> USD
+        let feb = settings.parse_timestamp("2024-02-01").unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&acme, &feb), Some(Decimal::new(1250, 2)));
+        // ACME -> USD
+        let apr = settings.parse_timestamp("2024-04-01").unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&acme, &apr), Some(Decimal::from(13)));
+        // no prices yet
+        let dec = settings.parse_timestamp("2023-12-01").unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&acme, &dec), None);
+    }
+
+    #[test]
+    fn timed_inverse_of_zero_price() {
+        let mut settings = Settings::default();
+        let price_db = indoc!(
+            "|P 2024-01-01 EUR 2 XYZ
+             |P 2024-02-01 EUR 0 XYZ
+             |P 2024-03-01 EUR 4 XYZ
+             |"
+        )
+        .strip_margin();
+        let price_db =
+            parser::pricedb_from_str(&mut price_db.as_str(), &mut settings).unwrap(/*:test:*/);
+        let journal = indoc!(
+            "|2024-01-15 'xyz
+             | Assets:Xyz  1 XYZ
+             | Equity:Xyz
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let eur = comm(&settings, "EUR");
+
+        let res = PriceLookup::AtTheTimeOfTxn.make_ctx(&txn_set.txns, Some(eur.clone()), &price_db);
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .starts_with("Price error: there is no inverse of zero price: 2024-02-01")
+        );
+
+        // with fixed price, only the last price is used
+        let xyz = comm(&settings, "XYZ");
+        let ts = settings.parse_timestamp("2024-04-01").unwrap(/*:test:*/);
+        let ctx = PriceLookup::LastPriceDbEntry.make_ctx(&txn_set.txns, Some(eur), &price_db).unwrap(/*:test:*/);
+        assert_eq!(ctx.price_of(&xyz, &ts), Some(Decimal::new(25, 2)));
     }
 }
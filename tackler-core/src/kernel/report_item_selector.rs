@@ -5,7 +5,7 @@
 
 use crate::kernel::Predicate;
 use crate::kernel::hash::Hash;
-use crate::model::{BalanceTreeNode, RegisterPosting};
+use crate::model::{BalanceTreeNode, RegisterPosting, TxnAccount};
 use crate::tackler;
 use regex::RegexSet;
 use tackler_api::metadata::Checksum;
@@ -172,3 +172,58 @@ impl ReportItemSelector for RegisterAllSelector {
         })
     }
 }
+
+pub trait PnlItemSelector: Predicate<TxnAccount> {}
+pub trait PnlSelector: PnlItemSelector + ReportItemSelector {}
+
+#[derive(Default)]
+pub struct PnlAllSelector {}
+
+impl Predicate<TxnAccount> for PnlAllSelector {
+    fn eval(&self, _: &TxnAccount) -> bool {
+        true
+    }
+}
+
+impl PnlItemSelector for PnlAllSelector {}
+impl PnlSelector for PnlAllSelector {}
+
+impl ReportItemSelector for PnlAllSelector {
+    fn checksum(&self, _hash: Hash) -> Result<Checksum, tackler::Error> {
+        Ok(Checksum {
+            algorithm: "None".to_string(),
+            value: "select all".to_string(),
+        })
+    }
+}
+
+pub struct PnlByAccountSelector {
+    regexs: RegexSet,
+}
+
+impl PnlByAccountSelector {
+    pub fn from(patterns: &[&str]) -> Result<PnlByAccountSelector, tackler::Error> {
+        let pas = PnlByAccountSelector {
+            regexs: new_full_haystack_regex_set(patterns)?,
+        };
+        Ok(pas)
+    }
+}
+
+impl PnlItemSelector for PnlByAccountSelector {}
+impl PnlSelector for PnlByAccountSelector {}
+
+impl Predicate<TxnAccount> for PnlByAccountSelector {
+    fn eval(&self, acctn: &TxnAccount) -> bool {
+        self.regexs.is_match(&acctn.atn.account)
+    }
+}
+
+impl ReportItemSelector for PnlByAccountSelector {
+    fn checksum(&self, hash: Hash) -> Result<Checksum, tackler::Error> {
+        let mut accsel = peeled_patterns(&self.regexs);
+        accsel.sort();
+        let h = hash.checksum(&accsel, "\n".as_bytes())?;
+        Ok(h)
+    }
+}
@@ -2,9 +2,10 @@
  * Tackler-NG 2025
  * SPDX-License-Identifier: Apache-2.0
  */
-use crate::config::Scale;
 use crate::kernel::Settings;
+use crate::kernel::amount_format::AmountFormat;
 use crate::kernel::price_lookup::PriceLookup;
+use crate::kernel::statement::StatementType;
 use crate::model::Commodity;
 use crate::tackler;
 use jiff::tz::TimeZone;
@@ -15,9 +16,10 @@ use tackler_api::txn_ts::{GroupBy, TimestampStyle};
 pub struct BalanceSettings {
     pub(crate) title: String,
     pub(crate) ras: Vec<String>,
-    pub(crate) scale: Scale,
+    pub(crate) amount_fmt: AmountFormat,
     pub(crate) report_commodity: Option<Arc<Commodity>>,
     pub(crate) price_lookup: PriceLookup,
+    pub(crate) natural_signs: bool,
 }
 
 impl TryFrom<&Settings> for BalanceSettings {
@@ -27,9 +29,10 @@ impl TryFrom<&Settings> for BalanceSettings {
         Ok(BalanceSettings {
             title: settings.report.balance.title.clone(),
             ras: settings.get_balance_ras(),
-            scale: settings.report.scale.clone(),
+            amount_fmt: settings.get_amount_format(),
             report_commodity: settings.get_report_commodity(),
             price_lookup: settings.get_price_lookup(),
+            natural_signs: settings.report.natural_signs,
         })
     }
 }
@@ -42,7 +45,8 @@ pub struct BalanceGroupSettings {
     pub report_tz: TimeZone,
     pub report_commodity: Option<Arc<Commodity>>,
     pub price_lookup: PriceLookup,
-    pub scale: Scale,
+    pub amount_fmt: AmountFormat,
+    pub natural_signs: bool,
 }
 
 impl TryFrom<&Settings> for BalanceGroupSettings {
@@ -56,7 +60,8 @@ impl TryFrom<&Settings> for BalanceGroupSettings {
             report_tz: settings.report.report_tz.clone(),
             report_commodity: settings.get_report_commodity(),
             price_lookup: settings.get_price_lookup(),
-            scale: settings.report.scale.clone(),
+            amount_fmt: settings.get_amount_format(),
+            natural_signs: settings.report.natural_signs,
         };
         Ok(bgs)
     }
@@ -70,7 +75,7 @@ pub struct RegisterSettings {
     pub report_commodity: Option<Arc<Commodity>>,
     pub price_lookup: PriceLookup,
     pub timestamp_style: TimestampStyle,
-    pub(crate) scale: Scale,
+    pub(crate) amount_fmt: AmountFormat,
 }
 
 impl TryFrom<&Settings> for RegisterSettings {
@@ -84,8 +89,65 @@ impl TryFrom<&Settings> for RegisterSettings {
             report_commodity: settings.get_report_commodity(),
             price_lookup: settings.get_price_lookup(),
             timestamp_style: settings.report.register.timestamp_style,
-            scale: settings.report.scale.clone(),
+            amount_fmt: settings.get_amount_format(),
         };
         Ok(rs)
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct PnlSettings {
+    pub title: String,
+    pub ras: Vec<String>,
+    pub report_commodity: Option<Arc<Commodity>>,
+    pub price_lookup: PriceLookup,
+    pub(crate) amount_fmt: AmountFormat,
+}
+
+impl TryFrom<&Settings> for PnlSettings {
+    type Error = tackler::Error;
+
+    fn try_from(settings: &Settings) -> Result<PnlSettings, tackler::Error> {
+        let ps = PnlSettings {
+            title: settings.report.pnl.title.clone(),
+            ras: settings.get_pnl_ras(),
+            report_commodity: settings.get_report_commodity(),
+            price_lookup: settings.get_price_lookup(),
+            amount_fmt: settings.get_amount_format(),
+        };
+        Ok(ps)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatementSettings {
+    pub stmt_type: StatementType,
+    pub title: String,
+    pub group_by: Option<GroupBy>,
+    pub report_tz: TimeZone,
+    pub report_commodity: Option<Arc<Commodity>>,
+    pub price_lookup: PriceLookup,
+    pub amount_fmt: AmountFormat,
+}
+
+impl StatementSettings {
+    pub fn from(
+        settings: &Settings,
+        stmt_type: StatementType,
+    ) -> Result<StatementSettings, tackler::Error> {
+        let stmt = match stmt_type {
+            StatementType::IncomeStatement => &settings.report.income_statement,
+            StatementType::BalanceSheet => &settings.report.balance_sheet,
+        };
+        let ss = StatementSettings {
+            stmt_type,
+            title: stmt.title.clone(),
+            group_by: stmt.group_by,
+            report_tz: settings.report.report_tz.clone(),
+            report_commodity: settings.get_report_commodity(),
+            price_lookup: settings.get_price_lookup(),
+            amount_fmt: settings.get_amount_format(),
+        };
+        Ok(ss)
+    }
+}
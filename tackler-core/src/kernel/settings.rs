@@ -4,21 +4,27 @@
  */
 use crate::config::overlaps::OverlapConfig;
 use crate::config::{
-    AccountSelectors, Config, Export, ExportType, Kernel, PriceLookupType, Report, ReportType,
+    AccountInfo, AccountSelectors, AccountType, CommodityFormat, Config, EquityMode, Export,
+    ExportType, Kernel, LotMethod, PriceLookupType, Report, ReportFormats, ReportType,
 };
+use crate::kernel::amount_format::AmountFormat;
 use crate::kernel::hash::Hash;
 use crate::kernel::price_lookup::PriceLookup;
-use crate::model::TxnAccount;
-use crate::model::price_entry::PriceDb;
+use crate::model::price_entry::{self, PriceDb};
 use crate::model::{AccountTreeNode, Commodity};
+use crate::model::{TxnAccount, TxnRefs};
 use crate::parser::GitInputSelector;
 use crate::{config, parser, tackler};
+use itertools::Itertools;
 use jiff::Zoned;
+use regex::Regex;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tackler_api::txn_header::Tag;
 use tackler_api::txn_ts::GroupBy;
+use tackler_rs::regex::peeled_pattern;
 
 pub struct GitInput {
     pub repo: PathBuf,
@@ -42,9 +48,10 @@ pub enum InputSettings {
     Git(GitInput),
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 struct Commodities {
     names: HashMap<String, Arc<Commodity>>,
+    formats: Arc<HashMap<String, CommodityFormat>>,
     permit_empty_commodity: bool,
 }
 
@@ -52,11 +59,12 @@ impl Commodities {
     fn default_empty_ok() -> Self {
         Commodities {
             names: HashMap::new(),
+            formats: Arc::new(HashMap::new()),
             permit_empty_commodity: true,
         }
     }
 
-    fn from(cfg: &Config) -> Result<Commodities, tackler::Error> {
+    fn from(cfg: &Config, strict_mode: bool) -> Result<Commodities, tackler::Error> {
         let cfg_comm = &cfg.transaction.commodities;
         let permit_empty_commodity = cfg_comm.permit_empty_commodity.unwrap_or(false);
 
@@ -74,17 +82,39 @@ impl Commodities {
                     }
                 },
             )?;
+        let formats = cfg_comm
+            .formats
+            .iter()
+            .try_fold(HashMap::new(), |mut formats, fmt| {
+                let name = fmt.name.as_str();
+                if strict_mode && !comms.contains_key(name) {
+                    let msg = format!(
+                        "Invalid Chart of Commodities: format of unknown commodity '{name}'"
+                    );
+                    return Err(msg);
+                }
+                if formats.insert(name.to_string(), fmt.clone()).is_some() {
+                    let msg = format!(
+                        "Invalid Chart of Commodities: duplicate format of commodity '{name}'"
+                    );
+                    return Err(msg);
+                }
+                Ok(formats)
+            })?;
         Ok(Commodities {
             names: comms,
+            formats: Arc::new(formats),
             permit_empty_commodity,
         })
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 struct AccountTrees {
     defined_accounts: HashMap<String, Arc<AccountTreeNode>>,
     synthetic_parents: HashMap<String, Arc<AccountTreeNode>>,
+    infos: HashMap<String, AccountInfo>,
+    normal_balance_types: Vec<AccountType>,
 }
 
 impl AccountTrees {
@@ -144,6 +174,43 @@ impl AccountTrees {
         Ok(AccountTrees {
             defined_accounts,
             synthetic_parents,
+            infos: HashMap::new(),
+            normal_balance_types: Vec::new(),
+        })
+    }
+
+    /// Add account infos (type, description and code) of accounts and account subtrees
+    ///
+    /// In strict mode, the account of info must be defined account or parent of it.
+    fn with_infos(
+        self,
+        account_infos: &[AccountInfo],
+        normal_balance_types: &[AccountType],
+        strict_mode: bool,
+    ) -> Result<AccountTrees, tackler::Error> {
+        let infos = account_infos
+            .iter()
+            .try_fold(HashMap::new(), |mut infos, info| {
+                let name = info.name.as_str();
+                if strict_mode
+                    && !self.defined_accounts.contains_key(name)
+                    && !self.synthetic_parents.contains_key(name)
+                {
+                    let msg =
+                        format!("Invalid Chart of Accounts: info of unknown account '{name}'");
+                    return Err(msg);
+                }
+                if infos.insert(name.to_string(), info.clone()).is_some() {
+                    let msg =
+                        format!("Invalid Chart of Accounts: duplicate info of account '{name}'");
+                    return Err(msg);
+                }
+                Ok(infos)
+            })?;
+        Ok(AccountTrees {
+            infos,
+            normal_balance_types: normal_balance_types.to_vec(),
+            ..self
         })
     }
 }
@@ -153,6 +220,29 @@ pub struct Price {
     // todo: fix visibility
     pub price_db: PriceDb,
     pub lookup_type: PriceLookupType,
+    /// Prices of closing positions are added into price db
+    pub implicit_prices: bool,
+}
+
+impl Price {
+    /// Price db with implicit prices of txns, if they are activated
+    ///
+    /// Price db is merged for each price lookup, and explicit price entries
+    /// have precedence over implicit entries with the same timestamp.
+    pub(crate) fn get_price_db(&self, txns: &TxnRefs<'_>) -> Cow<'_, PriceDb> {
+        if !self.implicit_prices {
+            return Cow::Borrowed(&self.price_db);
+        }
+        let price_db = self
+            .price_db
+            .iter()
+            .cloned()
+            .chain(price_entry::implicit_prices(txns.iter().copied()))
+            .sorted()
+            .dedup()
+            .collect();
+        Cow::Owned(price_db)
+    }
 }
 
 #[derive(Debug)]
@@ -161,6 +251,8 @@ pub struct Settings {
     pub(crate) report: Report,
     pub(crate) export: Export,
     strict_mode: bool,
+    /// Continue parsing after an error at the next txn header
+    pub(crate) recovery_mode: bool,
     kernel: Kernel,
     pub price: Price,
     price_lookup: PriceLookup,
@@ -168,12 +260,16 @@ pub struct Settings {
     accounts: AccountTrees,
     commodities: Commodities,
     tags: HashMap<String, Arc<Tag>>,
+    /// Declared metadata keys and their value regexes, if there is Chart of Metadata
+    meta_keys: Option<HashMap<String, Option<Regex>>>,
+    equity_cutoff: Option<Zoned>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             strict_mode: false,
+            recovery_mode: false,
             audit_mode: false,
             report: Report::default(),
             export: Export::default(),
@@ -184,6 +280,8 @@ impl Default for Settings {
             accounts: AccountTrees::default(),
             commodities: Commodities::default_empty_ok(),
             tags: HashMap::new(),
+            meta_keys: None,
+            equity_cutoff: None,
         }
     }
 }
@@ -201,6 +299,16 @@ impl Settings {
     pub fn try_from(cfg: Config, overlaps: OverlapConfig) -> Result<Settings, tackler::Error> {
         let strict_mode = overlaps.strict.mode.unwrap_or(cfg.kernel.strict);
         let audit_mode = overlaps.audit.mode.unwrap_or(cfg.kernel.audit.mode);
+        let recovery_mode = overlaps.recovery.mode.unwrap_or(cfg.kernel.recovery);
+        let attachment_check = overlaps
+            .audit
+            .attachments
+            .unwrap_or(cfg.kernel.audit.attachments.check);
+        if attachment_check && cfg.kernel.audit.attachments.keys.is_empty() {
+            let msg =
+                "Attachment check is activated, but there are no `kernel.audit.attachments.keys`";
+            return Err(msg.into());
+        }
 
         let reports = match overlaps.target.reports {
             Some(reports) => config::to_report_targets(&reports)?,
@@ -214,10 +322,16 @@ impl Settings {
         let lookup_type = overlaps.price.lookup_type.unwrap_or(cfg.price.lookup_type);
 
         let db_path = overlaps.price.db_path.unwrap_or(cfg.price.db_path.clone());
+        let implicit_prices = cfg.price.implicit_prices;
 
-        let account_trees = AccountTrees::from(&cfg.transaction.accounts.names, strict_mode)?;
+        let cfg_accounts = &cfg.transaction.accounts;
+        let account_trees = AccountTrees::from(&cfg_accounts.names, strict_mode)?.with_infos(
+            &cfg_accounts.infos,
+            &cfg_accounts.normal_balance_types,
+            strict_mode,
+        )?;
 
-        let mut commodities = Commodities::from(&cfg)?;
+        let mut commodities = Commodities::from(&cfg, strict_mode)?;
 
         let tags = cfg
             .transaction
@@ -230,6 +344,13 @@ impl Settings {
                 tags
             });
 
+        let meta_keys = cfg.transaction.metadata.as_ref().map(|md| {
+            md.keys
+                .iter()
+                .map(|key| (key.name.clone(), key.regex.clone()))
+                .collect::<HashMap<_, _>>()
+        });
+
         if strict_mode
             && exports.contains(&ExportType::Equity)
             && !account_trees
@@ -239,6 +360,16 @@ impl Settings {
             let msg = "Unknown `equity.equity-account` and `strict` mode is on".to_string();
             return Err(msg.into());
         }
+        if let Some(re_account) = &cfg.export.equity.retained_earnings {
+            if strict_mode
+                && exports.contains(&ExportType::Equity)
+                && cfg.export.equity.mode == EquityMode::Closing
+                && !account_trees.defined_accounts.contains_key(re_account)
+            {
+                let msg = "Unknown `equity.retained-earnings` and `strict` mode is on".to_string();
+                return Err(msg.into());
+            }
+        }
 
         let cfg_rpt_commodity = cfg
             .report
@@ -273,10 +404,24 @@ impl Settings {
             .map(|g| GroupBy::from(g.as_str()))
             .unwrap_or(Ok(cfg.report.balance_group.group_by))?;
 
+        let to_formats =
+            |fmts: Option<Vec<String>>| fmts.map(|f| config::to_report_formats(&f)).transpose();
+        let report_formats = to_formats(overlaps.report.formats)?;
+        let balance_formats = to_formats(overlaps.report.balance_formats)?;
+        let balance_group_formats = to_formats(overlaps.report.balance_group_formats)?;
+        let register_formats = to_formats(overlaps.report.register_formats)?;
+        let pnl_formats = to_formats(overlaps.report.pnl_formats)?;
+        let income_statement_formats = to_formats(overlaps.report.income_statement_formats)?;
+        let balance_sheet_formats = to_formats(overlaps.report.balance_sheet_formats)?;
+
+        let mut kernel = cfg.kernel;
+        kernel.audit.attachments.check = attachment_check;
+
         let mut tmp_settings = Settings {
             strict_mode,
+            recovery_mode,
             audit_mode,
-            kernel: cfg.kernel,
+            kernel,
             price: Price::default(), // this is not real, see next one
             price_lookup: PriceLookup::default(), // this is not real, see next one
             global_acc_sel: overlaps.report.account_overlap,
@@ -292,8 +437,37 @@ impl Settings {
             accounts: account_trees,
             commodities,
             tags,
+            meta_keys,
+            equity_cutoff: None,
         };
         tmp_settings.report.balance_group.group_by = group_by;
+        if let Some(fmts) = report_formats {
+            tmp_settings.report.balance.formats = fmts.clone();
+            tmp_settings.report.balance_group.formats = fmts.clone();
+            tmp_settings.report.register.formats = fmts.clone();
+            tmp_settings.report.pnl.formats = fmts.clone();
+            tmp_settings.report.income_statement.formats = fmts.clone();
+            tmp_settings.report.balance_sheet.formats = fmts;
+        }
+        // per report formats have precedence over formats of all reports
+        if let Some(fmts) = balance_formats {
+            tmp_settings.report.balance.formats = fmts;
+        }
+        if let Some(fmts) = balance_group_formats {
+            tmp_settings.report.balance_group.formats = fmts;
+        }
+        if let Some(fmts) = register_formats {
+            tmp_settings.report.register.formats = fmts;
+        }
+        if let Some(fmts) = pnl_formats {
+            tmp_settings.report.pnl.formats = fmts;
+        }
+        if let Some(fmts) = income_statement_formats {
+            tmp_settings.report.income_statement.formats = fmts;
+        }
+        if let Some(fmts) = balance_sheet_formats {
+            tmp_settings.report.balance_sheet.formats = fmts;
+        }
 
         let given_time = overlaps.price.before_time;
 
@@ -338,17 +512,33 @@ impl Settings {
         };
 
         let price = match &lookup_type {
-            PriceLookupType::None => Price::default(),
+            PriceLookupType::None => Price {
+                implicit_prices,
+                ..Price::default()
+            },
+            // db-path is "none", all prices are implicit prices
+            _ if db_path.as_os_str().is_empty() => Price {
+                lookup_type,
+                implicit_prices,
+                ..Price::default()
+            },
             _ => Price {
                 // we need half-baked settings here bc commodity and timestamp lookups
                 price_db: parser::pricedb_from_file(&db_path, &mut tmp_settings)?,
                 lookup_type,
+                implicit_prices,
             },
         };
 
+        let equity_cutoff = match tmp_settings.export.equity.cutoff.clone() {
+            Some(ts) => Some(tmp_settings.parse_timestamp(ts.as_str())?),
+            None => None,
+        };
+
         Ok(Settings {
             price,
             price_lookup,
+            equity_cutoff,
             ..tmp_settings
         })
     }
@@ -362,6 +552,16 @@ impl Settings {
         }
     }
 
+    /// Metadata keys of attachments, if attachment check is activated
+    pub(crate) fn get_attachment_keys(&self) -> Option<&[String]> {
+        let attachments = &self.kernel.audit.attachments;
+        if attachments.check {
+            Some(attachments.keys.as_slice())
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn get_txn_account(
         &self,
         name: &str,
@@ -444,6 +644,15 @@ impl Settings {
             }
         }
     }
+    /// Formatter of amounts, with display formats of commodities
+    pub fn get_amount_format(&self) -> AmountFormat {
+        AmountFormat::new(
+            self.report.scale.clone(),
+            self.report.number_format.clone(),
+            self.commodities.formats.clone(),
+        )
+    }
+
     pub(crate) fn get_or_create_commodity(
         &mut self,
         name: Option<&str>,
@@ -516,6 +725,90 @@ impl Settings {
         }
     }
 
+    /// Check key-value metadata against Chart of Metadata
+    ///
+    /// Metadata is checked only in strict mode, and if there is Chart of Metadata.
+    pub(crate) fn check_meta_value(&self, key: &str, value: &str) -> Result<(), tackler::Error> {
+        let Some(meta_keys) = self.meta_keys.as_ref().filter(|_| self.strict_mode) else {
+            return Ok(());
+        };
+        match meta_keys.get(key) {
+            None => {
+                let msg = format!("Unknown metadata key: '{key}'");
+                Err(msg.into())
+            }
+            Some(Some(regex)) if !regex.is_match(value) => {
+                let msg = format!(
+                    "Invalid value of metadata key '{key}': '{value}' doesn't match '{}'",
+                    peeled_pattern(regex)
+                );
+                Err(msg.into())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Settings for parser worker
+    ///
+    /// Worker has its own copy of Chart of Accounts, Commodities and Tags,
+    /// and items created by the worker are merged back by [`Settings::merge_parser`].
+    pub(crate) fn fork_parser(&self) -> Settings {
+        Settings {
+            strict_mode: self.strict_mode,
+            recovery_mode: self.recovery_mode,
+            audit_mode: self.audit_mode,
+            kernel: self.kernel.clone(),
+            accounts: self.accounts.clone(),
+            commodities: self.commodities.clone(),
+            tags: self.tags.clone(),
+            meta_keys: self.meta_keys.clone(),
+            ..Settings::default()
+        }
+    }
+
+    /// Merge accounts, commodities and tags created by parser worker
+    pub(crate) fn merge_parser(&mut self, worker: Settings) {
+        for (name, atn) in worker.accounts.defined_accounts {
+            self.accounts.defined_accounts.entry(name).or_insert(atn);
+        }
+        for (name, comm) in worker.commodities.names {
+            self.commodities.names.entry(name).or_insert(comm);
+        }
+        for (name, tag) in worker.tags {
+            self.tags.entry(name).or_insert(tag);
+        }
+    }
+
+    /// Account info (type, description and code) of the account
+    pub fn get_account_info(&self, account: &str) -> Option<&AccountInfo> {
+        self.accounts.infos.get(account)
+    }
+
+    /// Account type of the account, or type of the nearest parent account with type
+    pub fn get_account_type(&self, account: &str) -> Option<AccountType> {
+        let mut name = account;
+        loop {
+            if let Some(acc_type) = self.get_account_info(name).and_then(|i| i.acc_type) {
+                return Some(acc_type);
+            }
+            match name.rsplit_once(':') {
+                Some((parent, _)) => name = parent,
+                None => return None,
+            }
+        }
+    }
+
+    /// Account types which must not have balance against their normal balance
+    ///
+    /// This is checked only in strict mode.
+    pub(crate) fn get_normal_balance_types(&self) -> &[AccountType] {
+        if self.strict_mode {
+            &self.accounts.normal_balance_types
+        } else {
+            &[]
+        }
+    }
+
     pub fn get_price_lookup(&self) -> PriceLookup {
         self.price_lookup.clone()
     }
@@ -593,6 +886,11 @@ impl Settings {
             }
         }
     }
+    /// Timezone of txn timestamps without offset
+    pub fn get_timezone(&self) -> jiff::tz::TimeZone {
+        self.kernel.timestamp.timezone.clone()
+    }
+
     pub fn get_offset_date(&self, date: jiff::civil::Date) -> Result<jiff::Zoned, tackler::Error> {
         let ts = date.to_datetime(self.kernel.timestamp.default_time);
         match ts.to_zoned(self.kernel.timestamp.timezone.clone()) {
@@ -604,6 +902,10 @@ impl Settings {
         }
     }
 
+    pub fn get_lot_method(&self) -> LotMethod {
+        self.kernel.lot_method
+    }
+
     pub fn get_report_commodity(&self) -> Option<Arc<Commodity>> {
         self.report.commodity.as_ref().map(|c| c.clone())
     }
@@ -612,6 +914,17 @@ impl Settings {
         self.report.targets.clone()
     }
 
+    pub fn get_report_formats(&self, report: &ReportType) -> ReportFormats {
+        match report {
+            ReportType::Balance => self.report.balance.formats.clone(),
+            ReportType::BalanceGroup => self.report.balance_group.formats.clone(),
+            ReportType::Register => self.report.register.formats.clone(),
+            ReportType::Pnl => self.report.pnl.formats.clone(),
+            ReportType::IncomeStatement => self.report.income_statement.formats.clone(),
+            ReportType::BalanceSheet => self.report.balance_sheet.formats.clone(),
+        }
+    }
+
     pub fn get_export_targets(&self) -> Vec<ExportType> {
         self.export.targets.clone()
     }
@@ -635,15 +948,77 @@ impl Settings {
         self.get_account_selector(&self.report.register.acc_sel)
     }
 
+    pub fn get_pnl_ras(&self) -> AccountSelectors {
+        self.get_account_selector(&self.report.pnl.acc_sel)
+    }
+
     pub fn get_equity_ras(&self) -> AccountSelectors {
         self.get_account_selector(&self.export.equity.acc_sel)
     }
+
+    /// Cutoff timestamp of closing txns
+    pub fn get_equity_cutoff(&self) -> Option<Zoned> {
+        self.equity_cutoff.clone()
+    }
+}
+
+#[cfg(test)]
+impl Settings {
+    /// Activate attachment check with metadata keys
+    pub(crate) fn set_attachment_keys(&mut self, keys: &[&str]) {
+        self.kernel.audit.attachments.check = true;
+        self.kernel.audit.attachments.keys = keys.iter().map(|k| k.to_string()).collect();
+    }
+
+    /// Set account types of accounts or account subtrees
+    pub(crate) fn set_account_types(&mut self, types: &[(&str, AccountType)]) {
+        for (name, acc_type) in types {
+            let info = AccountInfo {
+                name: name.to_string(),
+                acc_type: Some(*acc_type),
+                description: None,
+                code: None,
+            };
+            self.accounts.infos.insert(name.to_string(), info);
+        }
+    }
+
+    /// Set display formats of commodities
+    pub(crate) fn set_commodity_formats(&mut self, formats: &[CommodityFormat]) {
+        self.commodities.formats = Arc::new(
+            formats
+                .iter()
+                .map(|f| (f.name.clone(), f.clone()))
+                .collect(),
+        );
+    }
+
+    /// Set strict mode with Chart of Metadata (key and value regex)
+    pub(crate) fn set_strict_meta_keys(&mut self, keys: &[(&str, Option<&str>)]) {
+        self.strict_mode = true;
+        self.meta_keys = Some(
+            keys.iter()
+                .map(|(key, re)| {
+                    let re = re.map(|re| {
+                        tackler_rs::regex::new_full_haystack_regex(re).unwrap(/*:test:*/)
+                    });
+                    (key.to_string(), re)
+                })
+                .collect(),
+        );
+    }
 }
 
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
     use super::*;
+    use crate::kernel::balance::Balance;
+    use crate::kernel::price_lookup::PriceLookupCtx;
+    use crate::kernel::report_item_selector::BalanceAllSelector;
+    use jiff::tz::TimeZone;
+    use tackler_api::filters::FilterDefinition;
+    use tackler_rs::IndocUtils;
 
     #[test]
     fn accounts_strict_false() {
@@ -808,4 +1183,125 @@ mod tests {
         assert_eq!(settings.accounts.synthetic_parents.len(), 1);
         assert_eq!(txntn_2.atn.account, "a");
     }
+
+    fn account_info(name: &str, acc_type: Option<AccountType>) -> AccountInfo {
+        AccountInfo {
+            name: name.to_string(),
+            acc_type,
+            description: None,
+            code: None,
+        }
+    }
+
+    #[test]
+    fn account_types() {
+        let accounts = vec![
+            "Income:Salary".to_string(),
+            "Income:Interest:Bank".to_string(),
+            "Assets:Cash".to_string(),
+        ];
+        let infos = vec![
+            account_info("Income", Some(AccountType::Income)),
+            account_info("Income:Interest", None),
+            account_info("Income:Interest:Bank", Some(AccountType::Asset)),
+        ];
+        let settings = Settings {
+            accounts: AccountTrees::from(&accounts, true)
+                .unwrap(/*:test:*/)
+                .with_infos(&infos, &[], true)
+                .unwrap(/*:test:*/),
+            ..Settings::default()
+        };
+
+        assert_eq!(
+            settings.get_account_type("Income"),
+            Some(AccountType::Income)
+        );
+        assert_eq!(
+            settings.get_account_type("Income:Salary"),
+            Some(AccountType::Income)
+        );
+        assert_eq!(
+            settings.get_account_type("Income:Interest"),
+            Some(AccountType::Income)
+        );
+        assert_eq!(
+            settings.get_account_type("Income:Interest:Bank"),
+            Some(AccountType::Asset)
+        );
+        assert_eq!(settings.get_account_type("Assets:Cash"), None);
+        assert!(settings.get_account_info("Income:Salary").is_none());
+
+        let unknown = vec![account_info("Expenses", Some(AccountType::Expense))];
+        assert!(
+            AccountTrees::from(&accounts, true)
+                .unwrap(/*:test:*/)
+                .with_infos(&unknown, &[], true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn strict_normal_balance() {
+        let accounts = vec![
+            "Income:Sales".to_string(),
+            "Assets:Bank".to_string(),
+            "Expenses:Refunds".to_string(),
+        ];
+        let infos = vec![
+            account_info("Income", Some(AccountType::Income)),
+            account_info("Assets", Some(AccountType::Asset)),
+        ];
+        let mut settings = Settings {
+            strict_mode: true,
+            accounts: AccountTrees::from(&accounts, true)
+                .unwrap(/*:test:*/)
+                .with_infos(&infos, &[AccountType::Income], true)
+                .unwrap(/*:test:*/),
+            ..Settings::default()
+        };
+        let journal = indoc::indoc!(
+            "|2024-01-01 'sale
+             | Assets:Bank  100
+             | Income:Sales
+             |
+             |2024-01-02 'refund
+             | Income:Sales  30
+             | Assets:Bank
+             |"
+        )
+        .strip_margin();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let bal = Balance::from(
+            "",
+            &txn_set,
+            &PriceLookupCtx::default(),
+            &BalanceAllSelector {},
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert!(bal.verify_normal_balances(&settings).is_ok());
+
+        // normal balance is checked at the end of period, not by txns
+        let txn_set = txn_data
+            .filter(&FilterDefinition::from_expr("ts >= 2024-01-02", TimeZone::UTC).unwrap(/*:test:*/))
+            .unwrap(/*:test:*/);
+        let bal = Balance::from(
+            "",
+            &txn_set,
+            &PriceLookupCtx::default(),
+            &BalanceAllSelector {},
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(
+            bal.verify_normal_balances(&settings)
+                .err()
+                .unwrap(/*:test:*/)
+                .to_string(),
+            "Normal balance error: income account 'Income:Sales' has debit balance 30"
+        );
+    }
 }
@@ -0,0 +1,372 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Income statement and balance sheet
+//!
+//! Statements are made with typed accounts (see account types of Chart of Accounts),
+//! and account balances are shown with natural signs (e.g. income is positive).
+//!
+//! Income statement is made with txns of the period, and it has income and expenses
+//! sections, and net income (income minus expenses).
+//!
+//! Balance sheet is made with all txns up to and including the period, also with
+//! txns which are not selected by the txn filter, and it has assets,
+//! liabilities and equity sections, and net income (income minus expenses), which
+//! is not yet closed into equity. Total of liabilities, equity and net income
+//! is the same as total of assets.
+use crate::config::AccountType;
+use crate::kernel::Settings;
+use crate::kernel::accumulator::TxnGroupByOp;
+use crate::kernel::balance::{Balance, Deltas};
+use crate::kernel::price_lookup::PriceLookupCtx;
+use crate::kernel::report_item_selector::BalanceAllSelector;
+use crate::model::{BalanceTreeNode, Transaction, TxnSet};
+use crate::tackler;
+use itertools::Itertools;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatementType {
+    IncomeStatement,
+    BalanceSheet,
+}
+
+/// Statement of one period
+#[derive(Debug)]
+pub struct Statement {
+    /// Period of statement (group-by key), or none for the whole txn set
+    pub(crate) period: Option<String>,
+    /// Account sections, titles of sections are section names
+    pub(crate) sections: Vec<Balance>,
+    /// Summary lines, e.g. net income
+    pub(crate) summary: Vec<(String, Deltas)>,
+}
+
+fn section(title: &str, bal: &[BalanceTreeNode], acc_type: AccountType, cfg: &Settings) -> Balance {
+    let bal: Vec<_> = bal
+        .iter()
+        .filter(|btn| cfg.get_account_type(&btn.acctn.atn.account) == Some(acc_type))
+        .cloned()
+        .collect();
+    Balance {
+        title: title.to_string(),
+        deltas: Balance::deltas(&bal),
+        bal,
+    }
+}
+
+/// Sum of deltas, with sign of each deltas
+fn sum_deltas(items: &[(&Deltas, i64)]) -> Deltas {
+    let mut sum = Deltas::new();
+    for (deltas, sign) in items {
+        for (comm, d) in deltas.iter() {
+            *sum.entry(comm.clone()).or_default() += *d * Decimal::from(*sign);
+        }
+    }
+    sum
+}
+
+impl Statement {
+    fn from<'a, I>(
+        stmt_type: StatementType,
+        period: Option<String>,
+        txns: I,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        cfg: &Settings,
+    ) -> Result<Statement, tackler::Error>
+    where
+        I: IntoIterator<Item = &'a &'a Transaction>,
+    {
+        let bal = Balance::from_iter("", txns, price_lookup_ctx, &BalanceAllSelector {}, cfg)?;
+        bal.verify_normal_balances(cfg)?;
+        let bal = bal.natural_signs(cfg).bal;
+
+        let income = section("Income", &bal, AccountType::Income, cfg);
+        let expenses = section("Expenses", &bal, AccountType::Expense, cfg);
+        let net_income = sum_deltas(&[(&income.deltas, 1), (&expenses.deltas, -1)]);
+
+        let statement = match stmt_type {
+            StatementType::IncomeStatement => Statement {
+                period,
+                sections: vec![income, expenses],
+                summary: vec![("Net income".to_string(), net_income)],
+            },
+            StatementType::BalanceSheet => {
+                let assets = section("Assets", &bal, AccountType::Asset, cfg);
+                let liabilities = section("Liabilities", &bal, AccountType::Liability, cfg);
+                let equity = section("Equity", &bal, AccountType::Equity, cfg);
+                let total = sum_deltas(&[
+                    (&liabilities.deltas, 1),
+                    (&equity.deltas, 1),
+                    (&net_income, 1),
+                ]);
+                Statement {
+                    period,
+                    sections: vec![assets, liabilities, equity],
+                    summary: vec![
+                        ("Net income".to_string(), net_income),
+                        ("Liabilities, equity and net income".to_string(), total),
+                    ],
+                }
+            }
+        };
+        Ok(statement)
+    }
+
+    /// Make statements for each period, or for the whole txn set
+    ///
+    /// Periods are by selected txns. Income statement is made with selected txns
+    /// of the period, and balance sheet with all txns up to the end of the period.
+    /// Without periods, the end of period is the timestamp of the last selected txn.
+    ///
+    /// * `txn_set` selected txns and all txns
+    /// * `group_by_op` period of txn, if there are statements by period
+    pub(crate) fn statements(
+        stmt_type: StatementType,
+        txn_set: &TxnSet<'_>,
+        group_by_op: Option<TxnGroupByOp<'_>>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        cfg: &Settings,
+    ) -> Result<Vec<Statement>, tackler::Error> {
+        let txns = &txn_set.txns;
+        let all_txns = &txn_set.all_txns;
+
+        let Some(group_by_op) = group_by_op else {
+            let txns = match stmt_type {
+                StatementType::IncomeStatement => &txns[..],
+                StatementType::BalanceSheet => {
+                    let end = txns.last().map_or(0, |last| {
+                        all_txns
+                            .partition_point(|txn| txn.header.timestamp <= last.header.timestamp)
+                    });
+                    &all_txns[..end]
+                }
+            };
+            let stmt = Statement::from(stmt_type, None, txns, price_lookup_ctx, cfg)?;
+            return Ok(vec![stmt]);
+        };
+
+        // txns are sorted, so each period is a continuous range of txns
+        let mut periods = Vec::new();
+        let mut start = 0;
+        for (period, group) in &txns.iter().chunk_by(|txn| group_by_op(txn)) {
+            let end = start + group.count();
+            periods.push((period, start, end));
+            start = end;
+        }
+
+        periods
+            .into_iter()
+            .map(|(period, start, end)| {
+                let txns = match stmt_type {
+                    StatementType::IncomeStatement => &txns[start..end],
+                    StatementType::BalanceSheet => {
+                        // all txns are sorted, so periods are in order
+                        let end = all_txns.partition_point(|txn| group_by_op(txn) <= period);
+                        &all_txns[..end]
+                    }
+                };
+                Statement::from(stmt_type, Some(period), txns, price_lookup_ctx, cfg)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::accumulator;
+    use crate::model::Commodity;
+    use crate::parser;
+    use indoc::indoc;
+    use jiff::tz::TimeZone;
+    use std::sync::Arc;
+    use tackler_api::filters::FilterDefinition;
+    use tackler_api::txn_ts::GroupBy;
+    use tackler_rs::IndocUtils;
+
+    fn amount(deltas: &Deltas, comm: &str) -> Decimal {
+        let comm = Some(Arc::new(
+            Commodity::from(comm.to_string()).unwrap(/*:test:*/),
+        ));
+        deltas.get(&comm).copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn income_statement_and_balance_sheet() {
+        let journal = indoc!(
+            "|2024-01-01 'opening
+             | Assets:Bank  1000 EUR
+             | Equity:Opening
+             |
+             |2024-01-15 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-01-20 'rent
+             | Expenses:Rent  1200 EUR
+             | Liabilities:Card
+             |
+             |2024-02-15 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-02-20 'food
+             | Expenses:Food  300 EUR
+             | Assets:Bank
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        settings.set_account_types(&[
+            ("Assets", AccountType::Asset),
+            ("Liabilities", AccountType::Liability),
+            ("Equity", AccountType::Equity),
+            ("Income", AccountType::Income),
+            ("Expenses", AccountType::Expense),
+        ]);
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+        let price_lookup_ctx = settings
+                .get_price_lookup()
+                .make_ctx(&txn_set.txns, None, &settings.price.price_db).unwrap(/*:test:*/);
+        let by_month = || {
+            Some(accumulator::group_by_op(
+                GroupBy::Month,
+                jiff::tz::TimeZone::UTC,
+            ))
+        };
+
+        let stmts = Statement::statements(
+            StatementType::IncomeStatement,
+            &txn_set,
+            by_month(),
+            &price_lookup_ctx,
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(stmts[0].period.as_deref(), Some("2024-01"));
+        assert_eq!(
+            amount(&stmts[0].sections[0].deltas, "EUR"),
+            Decimal::from(3000)
+        );
+        assert_eq!(
+            amount(&stmts[0].sections[1].deltas, "EUR"),
+            Decimal::from(1200)
+        );
+        assert_eq!(amount(&stmts[0].summary[0].1, "EUR"), Decimal::from(1800));
+        assert_eq!(amount(&stmts[1].summary[0].1, "EUR"), Decimal::from(2700));
+
+        let stmts = Statement::statements(
+            StatementType::BalanceSheet,
+            &txn_set,
+            by_month(),
+            &price_lookup_ctx,
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(stmts.len(), 2);
+        let feb = &stmts[1];
+        assert_eq!(feb.period.as_deref(), Some("2024-02"));
+        // assets
+        assert_eq!(amount(&feb.sections[0].deltas, "EUR"), Decimal::from(6700));
+        // liabilities
+        assert_eq!(amount(&feb.sections[1].deltas, "EUR"), Decimal::from(1200));
+        // equity
+        assert_eq!(amount(&feb.sections[2].deltas, "EUR"), Decimal::from(1000));
+        // net income
+        assert_eq!(amount(&feb.summary[0].1, "EUR"), Decimal::from(4500));
+        // liabilities, equity and net income
+        assert_eq!(amount(&feb.summary[1].1, "EUR"), Decimal::from(6700));
+    }
+
+    #[test]
+    fn balance_sheet_with_filtered_txns() {
+        let journal = indoc!(
+            "|2024-01-01 'opening
+             | Assets:Bank  1000 EUR
+             | Equity:Opening
+             |
+             |2024-01-15 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-02-15 'salary
+             | Assets:Bank  3000 EUR
+             | Income:Salary
+             |
+             |2024-02-20 'food
+             | Expenses:Food  300 EUR
+             | Assets:Bank
+             |
+             |2024-03-01 'food
+             | Expenses:Food  100 EUR
+             | Assets:Bank
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        settings.set_account_types(&[
+            ("Assets", AccountType::Asset),
+            ("Equity", AccountType::Equity),
+            ("Income", AccountType::Income),
+            ("Expenses", AccountType::Expense),
+        ]);
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let filter = FilterDefinition::from_expr(
+            "ts >= 2024-02-01 and ts < 2024-03-01",
+            TimeZone::UTC,
+        )
+        .unwrap(/*:test:*/);
+        let txn_set = txn_data.filter(&filter).unwrap(/*:test:*/);
+        let price_lookup_ctx = PriceLookupCtx::default();
+
+        let stmts = Statement::statements(
+            StatementType::IncomeStatement,
+            &txn_set,
+            None,
+            &price_lookup_ctx,
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        // net income of selected txns
+        assert_eq!(amount(&stmts[0].summary[0].1, "EUR"), Decimal::from(2700));
+
+        let stmts = Statement::statements(
+            StatementType::BalanceSheet,
+            &txn_set,
+            None,
+            &price_lookup_ctx,
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        let bs = &stmts[0];
+        // assets of all txns up to the last selected txn
+        assert_eq!(amount(&bs.sections[0].deltas, "EUR"), Decimal::from(6700));
+        // equity
+        assert_eq!(amount(&bs.sections[2].deltas, "EUR"), Decimal::from(1000));
+        // net income
+        assert_eq!(amount(&bs.summary[0].1, "EUR"), Decimal::from(5700));
+
+        let by_month = accumulator::group_by_op(GroupBy::Month, TimeZone::UTC);
+        let stmts = Statement::statements(
+            StatementType::BalanceSheet,
+            &txn_set,
+            Some(by_month),
+            &price_lookup_ctx,
+            &settings,
+        )
+        .unwrap(/*:test:*/);
+        assert_eq!(stmts.len(), 1);
+        assert_eq!(stmts[0].period.as_deref(), Some("2024-02"));
+        assert_eq!(
+            amount(&stmts[0].sections[0].deltas, "EUR"),
+            Decimal::from(6700)
+        );
+    }
+}
@@ -5,8 +5,10 @@
 #![forbid(unsafe_code)]
 
 pub mod config;
+pub mod convert;
 pub mod export;
 pub mod filter;
+pub mod import;
 pub mod kernel;
 pub mod math;
 pub mod model;
@@ -6,8 +6,10 @@
 pub(crate) use account_tree_node::AccountTreeNode;
 pub(crate) use account_tree_node::Commodity;
 pub(crate) use account_tree_node::TxnAccount;
+pub use balance_assertion::BalanceAssertion;
 pub(crate) use balance_tree_node::BalanceTreeNode;
 pub use posting::Posting;
+pub use posting::PostingMeta;
 pub(crate) use register::RegisterEntry;
 pub(crate) use register::RegisterPosting;
 pub use transaction::Transaction;
@@ -15,6 +17,7 @@ pub use txn_data::TxnData;
 pub use txn_data::TxnSet;
 
 pub(crate) mod account_tree_node;
+pub mod balance_assertion;
 pub(crate) mod balance_tree_node;
 pub mod posting;
 pub mod price_entry;
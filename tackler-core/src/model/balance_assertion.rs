@@ -0,0 +1,63 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::model::TxnAccount;
+use crate::tackler;
+use jiff::{ToSpan, Zoned};
+use rust_decimal::Decimal;
+use std::fmt::{Display, Formatter};
+use tackler_api::txn_ts;
+
+/// Balance assertion directive, e.g. `2024-01-31 balance Assets:Bank 1000.00 EUR`
+///
+/// Asserted balance of the account and commodity is the balance after all
+/// transactions up to and including the timestamp of the assertion.
+/// Assertion with date only covers the whole day.
+#[derive(Debug, Clone)]
+pub struct BalanceAssertion {
+    pub timestamp: Zoned,
+    /// Assertion is given with date only (without time)
+    pub is_date: bool,
+    pub acctn: TxnAccount,
+    pub amount: Decimal,
+}
+
+impl BalanceAssertion {
+    /// Last instant which is covered by the assertion
+    ///
+    /// This is the end of day for date only assertion, otherwise the timestamp.
+    pub fn end(&self) -> Result<Zoned, tackler::Error> {
+        if self.is_date {
+            Ok(self.timestamp.tomorrow()?.checked_sub(1.nanosecond())?)
+        } else {
+            Ok(self.timestamp.clone())
+        }
+    }
+
+    pub(crate) fn location(&self) -> String {
+        if self.is_date {
+            self.timestamp.date().to_string()
+        } else {
+            txn_ts::rfc_3339(&self.timestamp)
+        }
+    }
+}
+
+impl Display for BalanceAssertion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let comm = &self.acctn.comm;
+        write!(
+            f,
+            "{} balance {} {}{}",
+            self.location(),
+            self.acctn.atn,
+            self.amount,
+            match comm.is_any() {
+                true => format!(" {}", comm.name),
+                false => String::new(),
+            },
+        )
+    }
+}
@@ -8,8 +8,54 @@ use crate::model::Posts;
 use crate::model::TxnAccount;
 use crate::tackler;
 use rust_decimal::Decimal;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Display, Formatter, Write};
 use std::sync::Arc;
+use tackler_api::txn_header::{Metadata, Tags};
+
+/// Cost basis of posting, e.g. opening position `{ 4.56 € }`
+///
+/// This is the unit cost of the posting commodity,
+/// and it is used to open and to identify the lots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostBasis {
+    pub unit_cost: Decimal,
+    pub commodity: Arc<Commodity>,
+}
+
+impl Display for CostBasis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{} {}}}", self.unit_cost, self.commodity.name)
+    }
+}
+
+/// Posting metadata, e.g. `# tags: vat` line under the posting
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostingMeta {
+    /// Posting tags, if any
+    pub tags: Option<Tags>,
+    /// Posting date (e.g. value date of bank statement), if any
+    pub date: Option<jiff::civil::Date>,
+    /// Free-form `key: value` metadata, in the order of journal
+    pub values: Metadata,
+}
+
+impl PostingMeta {
+    /// Metadata lines of posting, each line is prefixed with `indent`
+    pub fn to_string_with_indent(&self, indent: &str) -> String {
+        let mut output = String::new();
+        if let Some(tags) = &self.tags {
+            let tags = tags.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+            let _ = writeln!(output, "{indent}# tags: {}", tags.join(", "));
+        }
+        if let Some(date) = &self.date {
+            let _ = writeln!(output, "{indent}# date: {date}");
+        }
+        for (key, value) in &self.values {
+            let _ = writeln!(output, "{indent}# {key}: {value}");
+        }
+        output
+    }
+}
 
 #[derive(Debug, Clone)]
 #[non_exhaustive]
@@ -20,7 +66,14 @@ pub struct Posting {
     pub txn_amount: Decimal,
     pub is_total_amount: bool,
     pub txn_commodity: Arc<Commodity>, // todo: check / fix this
+    pub cost_basis: Option<CostBasis>,
+    /// Asserted balance of the account after this posting (`== amount`)
+    pub balance_assertion: Option<Decimal>,
+    /// Amount is not given, it's inferred from other postings of txn
+    pub is_inferred: bool,
     pub comment: Option<String>,
+    /// Posting metadata, if any
+    pub meta: Option<PostingMeta>,
 }
 
 impl Posting {
@@ -43,7 +96,11 @@ impl Posting {
             txn_amount,
             is_total_amount,
             txn_commodity,
+            cost_basis: None,
+            balance_assertion: None,
+            is_inferred: false,
             comment,
+            meta: None,
         })
     }
 }
@@ -51,44 +108,47 @@ pub fn txn_sum(posts: &Posts) -> Decimal {
     posts.iter().map(|p| p.txn_amount).sum()
 }
 
-impl Display for Posting {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let sign_space = if self.amount.is_sign_negative() {
-            ""
+impl Posting {
+    /// Price of closing position (`@` unit price or `=` total price)
+    /// in txn commodity, if posting has one
+    pub(crate) fn closing_price(&self) -> Option<Decimal> {
+        // todo: old-scala comment: fix this
+        if !self.txn_commodity.is_any() || self.txn_commodity.name == self.acctn.comm.name {
+            None
+        } else if self.is_total_amount {
+            Some(self.txn_amount)
         } else {
-            " "
-        };
+            Some(self.txn_amount / self.amount)
+        }
+    }
 
+    /// Posting value without the amount: commodity, positions,
+    /// balance assertion and comment
+    pub(crate) fn value_suffix(&self) -> String {
         let comm = &self.acctn.comm;
-        write!(
-            f,
-            "{}  {}{}{}{}{}",
-            self.acctn.atn,
-            sign_space,
-            self.amount,
+        format!(
+            "{}{}{}{}{}",
             match comm.is_any() {
                 true => format!(" {}", comm.name),
                 false => String::new(),
             },
-            if self.txn_commodity.is_any() {
-                #[allow(clippy::collapsible_else_if)]
-                // todo: old-scala comment: fix this
-                if self.txn_commodity.name == self.acctn.comm.name {
-                    String::default()
-                } else {
-                    if self.is_total_amount {
-                        format!(" = {} {}", self.txn_amount, self.txn_commodity.name)
-                    } else {
-                        format!(
-                            " @ {} {}",
-                            (self.txn_amount / self.amount),
-                            self.txn_commodity.name
-                        )
-                    }
+            self.cost_basis
+                .as_ref()
+                .map(|cb| format!(" {cb}"))
+                .unwrap_or_default(),
+            match self.closing_price() {
+                Some(price) if self.is_total_amount => {
+                    format!(" = {} {}", price, self.txn_commodity.name)
                 }
-            } else {
-                String::default()
+                Some(price) => format!(" @ {} {}", price, self.txn_commodity.name),
+                None => String::default(),
             },
+            self.balance_assertion
+                .map(|ba| match comm.is_any() {
+                    true => format!(" == {} {}", ba, comm.name),
+                    false => format!(" == {}", ba),
+                })
+                .unwrap_or_default(),
             self.comment
                 .as_ref()
                 .map(|c| format!(" ; {c}"))
@@ -97,6 +157,25 @@ impl Display for Posting {
     }
 }
 
+impl Display for Posting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign_space = if self.amount.is_sign_negative() {
+            ""
+        } else {
+            " "
+        };
+
+        write!(
+            f,
+            "{}  {}{}{}",
+            self.acctn.atn,
+            sign_space,
+            self.amount,
+            self.value_suffix()
+        )
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
@@ -308,4 +387,54 @@ mod tests {
 
         assert_eq!(p.to_string(), "a:b   123.00 = 246.00 € ; comment");
     }
+
+    #[test]
+    fn display_cost_basis() {
+        let pv = Decimal::new(10, 0);
+        let tv = Decimal::new(6000, 2);
+        let acctn = Arc::new(AccountTreeNode::from("a:b").unwrap(/*:test:*/));
+        let txntn = TxnAccount {
+            atn: acctn,
+            comm: Arc::new(Commodity {
+                name: "ACME".to_string(),
+            }),
+        };
+        let mut p = Posting::from(
+            txntn,
+            pv,
+            tv,
+            false,
+            Arc::new(Commodity {
+                name: "€".to_string(),
+            }),
+            None,
+        )
+        .unwrap(/*:test:*/);
+        p.cost_basis = Some(CostBasis {
+            unit_cost: Decimal::new(456, 2),
+            commodity: Arc::new(Commodity {
+                name: "€".to_string(),
+            }),
+        });
+
+        assert_eq!(p.to_string(), "a:b   10 ACME {4.56 €} @ 6.00 €");
+    }
+
+    #[test]
+    fn display_balance_assertion() {
+        let v = Decimal::new(-10, 0);
+        let acctn = Arc::new(AccountTreeNode::from("a:b").unwrap(/*:test:*/));
+        let comm = Arc::new(Commodity {
+            name: "€".to_string(),
+        });
+        let txntn = TxnAccount {
+            atn: acctn,
+            comm: comm.clone(),
+        };
+        let mut p = Posting::from(txntn, v, v, false, comm, Some("comment".to_string()))
+            .unwrap(/*:test:*/);
+        p.balance_assertion = Some(Decimal::new(9000, 2));
+
+        assert_eq!(p.to_string(), "a:b  -10 € == 90.00 € ; comment");
+    }
 }
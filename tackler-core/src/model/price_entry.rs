@@ -5,12 +5,13 @@
 
 use std::sync::Arc;
 
+use itertools::Itertools;
 use rust_decimal::Decimal;
 
-use super::Commodity;
+use super::{Commodity, Posting, Transaction};
 
 /// Entry in the price database
-#[derive(Debug, Eq)]
+#[derive(Debug, Clone, Eq)]
 pub struct PriceEntry {
     /// Timestamp with Zone information
     pub timestamp: jiff::Zoned,
@@ -48,3 +49,43 @@ impl PartialOrd for PriceEntry {
 }
 
 pub type PriceDb = Vec<PriceEntry>;
+
+impl PriceEntry {
+    /// Implicit price entry of closing position (`@` or `=`)
+    ///
+    /// Price is the unit price of posting commodity in txn commodity,
+    /// and its timestamp is the timestamp of txn.
+    pub(crate) fn from_posting(txn: &Transaction, post: &Posting) -> Option<PriceEntry> {
+        if !post.acctn.comm.is_any() {
+            return None;
+        }
+        post.closing_price()?;
+
+        Some(PriceEntry {
+            timestamp: txn.header.timestamp.clone(),
+            base_commodity: post.acctn.comm.clone(),
+            eq_amount: (post.txn_amount / post.amount).normalize(),
+            eq_commodity: post.txn_commodity.clone(),
+            comments: txn.header.uuid.map(|uuid| format!("txn: {uuid}")),
+        })
+    }
+}
+
+/// Implicit price entries of all closing positions of txns
+///
+/// If txn has multiple prices for the same commodity pair,
+/// then the first one is used.
+pub(crate) fn implicit_prices<'a, I>(txns: I) -> PriceDb
+where
+    I: IntoIterator<Item = &'a Transaction>,
+{
+    txns.into_iter()
+        .flat_map(|txn| {
+            txn.posts
+                .iter()
+                .filter_map(|p| PriceEntry::from_posting(txn, p))
+        })
+        .sorted()
+        .dedup()
+        .collect()
+}
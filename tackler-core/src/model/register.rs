@@ -3,13 +3,12 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use crate::config::Scale;
 use crate::kernel::RegisterSettings;
 use crate::kernel::price_lookup::PriceLookup;
 use crate::model::{Commodity, Posting, Transaction};
 use jiff::Zoned;
 use jiff::tz::TimeZone;
-use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal::Decimal;
 use std::cmp::{Ordering, max};
 use std::fmt::Write;
 use std::fmt::{Display, Formatter};
@@ -24,7 +23,7 @@ pub struct RegisterPosting<'a> {
 }
 
 impl RegisterPosting<'_> {
-    fn is_commodity_conv(&self) -> bool {
+    pub(crate) fn is_commodity_conv(&self) -> bool {
         self.target_commodity != self.post.acctn.comm
     }
 }
@@ -72,20 +71,6 @@ impl RegisterEntry<'_> {
         tz: TimeZone,
         reg_cfg: &RegisterSettings,
     ) -> String {
-        fn amount_to_string(amount: &Decimal, scale: &Scale, width: usize) -> String {
-            let prec = scale.get_precision(amount);
-            let amount_txt = format!(
-                "{:.prec$}",
-                amount.round_dp_with_strategy(prec as u32, RoundingStrategy::MidpointAwayFromZero)
-            );
-
-            if amount.is_sign_positive() && amount_txt.chars().count() >= width {
-                format!(" {}", amount_txt)
-            } else {
-                amount_txt
-            }
-        }
-
         let indent = " ".repeat(12);
         let mut line_len = 0;
         let mut reg_entry_txt = self.txn.header.to_string_with_indent(&indent, ts_fmtr, tz);
@@ -98,10 +83,12 @@ impl RegisterEntry<'_> {
         };
 
         for p in &self.posts {
+            let amount_fmt = &reg_cfg.amount_fmt;
             let (comm, base_comm, width) = if p.is_commodity_conv() {
+                let post_comm = amount_fmt.txt_commodity(&p.post.acctn.comm.name);
                 let (base_comm, width) = match p.rate {
-                    Some(r) => (format!(" {} @ {}", p.post.acctn.comm, r), 20),
-                    None => (format!(" {}", p.post.acctn.comm), 8),
+                    Some(r) => (format!(" {} @ {}", post_comm, amount_fmt.txt_rate(&r)), 20),
+                    None => (format!(" {}", post_comm), 8),
                 };
                 (&p.target_commodity, base_comm, width)
             } else {
@@ -109,15 +96,15 @@ impl RegisterEntry<'_> {
             };
 
             let line = format!(
-                "{}{:<33}{:>18}{:<w$} {:>18}{}",
+                "{}{:<33}{}{:<w$} {}{}",
                 indent,
                 p.post.acctn.atn.account,
-                amount_to_string(&p.post.amount, &reg_cfg.scale, 18),
+                amount_fmt.txt_column(&p.post.amount, &p.post.acctn.comm.name, 18),
                 base_comm,
-                amount_to_string(&p.amount, &reg_cfg.scale, 18),
-                match &comm.is_any() {
-                    true => format!(" {}", comm.name),
-                    false => String::new(),
+                amount_fmt.txt_column(&p.amount, &comm.name, 18),
+                match amount_fmt.txt_commodity(&comm.name) {
+                    "" => String::new(),
+                    c => format!(" {}", c),
                 },
                 w = width,
             );
@@ -69,6 +69,9 @@ impl Display for Transaction {
                 .iter()
                 .fold(String::with_capacity(256), |mut output, p| {
                     let _ = writeln!(output, "{indent}{p}");
+                    if let Some(meta) = &p.meta {
+                        output.push_str(&meta.to_string_with_indent(&indent.repeat(2)));
+                    }
                     output
                 })
         )
@@ -106,6 +109,7 @@ mod tests {
             uuid: None,
             location: None,
             tags: None,
+            meta: None,
             comments: None,
         };
 
@@ -4,6 +4,7 @@
  */
 
 use itertools::Itertools;
+use std::collections::HashMap;
 
 use crate::kernel::Predicate;
 use crate::kernel::hash::Hash;
@@ -13,15 +14,24 @@ use tackler_api::filters::FilterDefinition;
 use tackler_api::metadata::items::{MetadataItem, TxnFilterDescription, TxnSetChecksum};
 use tackler_api::metadata::{Checksum, Metadata};
 
+/// Blob ids of txn attachments by txn uuid (in metadata order)
+pub(crate) type AttachmentIds = HashMap<String, Vec<String>>;
+
 pub struct TxnData {
     metadata: Option<Metadata>,
     txns: Txns,
     hash: Option<Hash>,
+    attachments: Option<AttachmentIds>,
 }
 
 pub struct TxnSet<'a> {
     pub(crate) metadata: Option<Metadata>,
     pub(crate) txns: TxnRefs<'a>,
+    /// All txns of txn data (sorted), without filtering
+    ///
+    /// This is for reports which need the history before
+    /// (or outside of) the selected txns, e.g. lots of PnL.
+    pub(crate) all_txns: TxnRefs<'a>,
 }
 
 impl TxnSet<'_> {
@@ -55,9 +65,18 @@ impl TxnData {
             metadata,
             txns: t,
             hash: hash.clone(),
+            attachments: None,
         })
     }
 
+    /// Include blob ids of attachments into txn set checksum
+    pub(crate) fn with_attachments(self, attachments: Option<AttachmentIds>) -> TxnData {
+        TxnData {
+            attachments,
+            ..self
+        }
+    }
+
     fn make_metadata(&self, txns: &TxnRefs<'_>) -> Result<Metadata, tackler::Error> {
         let mut metadata = match &self.metadata {
             Some(md) => Metadata::from_metadata(md),
@@ -67,7 +86,7 @@ impl TxnData {
         if let Some(hash) = &self.hash {
             let new_tsc_mdi = MetadataItem::TxnSetChecksum(TxnSetChecksum {
                 size: txns.len(),
-                hash: calc_txn_checksum(txns, hash)?,
+                hash: calc_txn_checksum(txns, self.attachments.as_ref(), hash)?,
             });
 
             metadata.push(new_tsc_mdi);
@@ -76,6 +95,11 @@ impl TxnData {
         Ok(metadata)
     }
 
+    /// All txns (sorted), without txn set metadata
+    pub(crate) fn txn_refs(&self) -> TxnRefs<'_> {
+        self.txns.iter().collect()
+    }
+
     pub fn filter<'a>(&'a self, tf: &FilterDefinition) -> Result<TxnSet<'a>, tackler::Error> {
         let refvec: TxnRefs<'_> = self.txns.iter().filter(|txn| tf.eval(txn)).collect();
 
@@ -86,6 +110,7 @@ impl TxnData {
         Ok(TxnSet {
             metadata: Some(metadata),
             txns: refvec,
+            all_txns: self.txn_refs(),
             //hash: &self.hash,
         })
     }
@@ -99,11 +124,23 @@ impl TxnData {
             None
         };
 
-        Ok(TxnSet { metadata, txns })
+        Ok(TxnSet {
+            metadata,
+            all_txns: txns.clone(),
+            txns,
+        })
     }
 }
 
-fn calc_txn_checksum(txns: &TxnRefs<'_>, hasher: &Hash) -> Result<Checksum, tackler::Error> {
+/// Calculate txn set checksum
+///
+/// Checksum is calculated over sorted txn uuids. If txn has attachments,
+/// then their blob ids are included on the same line with txn uuid.
+pub(crate) fn calc_txn_checksum(
+    txns: &TxnRefs<'_>,
+    attachments: Option<&AttachmentIds>,
+    hasher: &Hash,
+) -> Result<Checksum, tackler::Error> {
     let uuids: Result<Vec<String>, tackler::Error> = txns
         .iter()
         .map(|txn| match txn.header.uuid {
@@ -137,14 +174,75 @@ fn calc_txn_checksum(txns: &TxnRefs<'_>, hasher: &Hash) -> Result<Checksum, tack
         return Err(msg.into());
     }
 
-    let cs = hasher.checksum(&u, "\n".as_bytes())?;
+    let items: Vec<String> = match attachments {
+        Some(att_ids) => u
+            .into_iter()
+            .map(|uuid| match att_ids.get(&uuid) {
+                Some(ids) if !ids.is_empty() => format!("{} {}", uuid, ids.join(" ")),
+                _ => uuid,
+            })
+            .collect(),
+        None => u,
+    };
+
+    let cs = hasher.checksum(&items, "\n".as_bytes())?;
     Ok(cs)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
     use uuid::Uuid;
 
+    #[test]
+    // desc: attachment blob ids are included into txn set checksum
+    fn txn_checksum_with_attachments() {
+        let uuid_1 = "506a2d55-2375-4d51-af3a-cf5021f04de9";
+        let uuid_2 = "e274c99e-1ebb-45e8-832d-58caf54ed95f";
+        let journal = indoc!(
+            "|2024-01-02 'receipt
+             | # uuid: e274c99e-1ebb-45e8-832d-58caf54ed95f
+             | # receipt: receipts/2024/117.pdf
+             | e:office  10
+             | a:cash
+             |
+             |2024-01-01 'no attachments
+             | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+             | e:office  10
+             | a:cash
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let txn_data =
+            parser::string_to_txns(&mut journal.as_str(), &mut settings).unwrap(/*:test:*/);
+        let txns = txn_data.get_all().unwrap(/*:test:*/).txns;
+        let hash = Hash::default();
+
+        let plain = calc_txn_checksum(&txns, None, &hash).unwrap(/*:test:*/);
+        let plain_ref = hash
+            .checksum(&[uuid_1.to_string(), uuid_2.to_string()], b"\n")
+            .unwrap(/*:test:*/);
+        assert_eq!(plain.value, plain_ref.value);
+
+        let empty = AttachmentIds::new();
+        let cs = calc_txn_checksum(&txns, Some(&empty), &hash).unwrap(/*:test:*/);
+        assert_eq!(cs.value, plain.value);
+
+        let blob_id = "2f2fdb8a7bdad0d62a8e7b2dbcf2b6b2a7b1dbb4";
+        let att_ids = AttachmentIds::from([(uuid_2.to_string(), vec![blob_id.to_string()])]);
+        let cs = calc_txn_checksum(&txns, Some(&att_ids), &hash).unwrap(/*:test:*/);
+        let cs_ref = hash
+            .checksum(&[uuid_1.to_string(), format!("{uuid_2} {blob_id}")], b"\n")
+            .unwrap(/*:test:*/);
+        assert_eq!(cs.value, cs_ref.value);
+        assert_ne!(cs.value, plain.value);
+    }
+
     #[test]
     // desc: check that uuid::to_string returns normalized lower-case UUID
     fn uuid_as_lower_case() {
@@ -2,14 +2,19 @@
  * Tackler-NG 2022-2025
  * SPDX-License-Identifier: Apache-2.0
  */
+pub use crate::parser::diagnostic::{DiagnosticSource, ParseDiagnostic, ParseErrors};
+pub use crate::parser::journal_formatter::format_journal;
 pub use crate::parser::pricedb_parser::{pricedb_from_file, pricedb_from_str};
 pub use crate::parser::tackler_txns::GitInputSelector;
 pub use crate::parser::tackler_txns::git_to_txns;
+pub use crate::parser::tackler_txns::input_to_txns;
 pub use crate::parser::tackler_txns::paths_to_txns;
 pub use crate::parser::tackler_txns::string_to_txns;
 use winnow::error::{ErrMode, FromExternalError};
 
+mod diagnostic;
 mod error;
+mod journal_formatter;
 mod pricedb_parser;
 mod tackler_parser;
 mod tackler_txns;
@@ -0,0 +1,368 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::parser::Stream;
+use crate::tackler;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use winnow::error::{ContextError, ParseError, StrContext};
+
+/// Source of txn data
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticSource {
+    /// Txn file
+    File(PathBuf),
+    /// Git object
+    Git {
+        /// Commit id
+        commit: String,
+        /// Object id (blob)
+        object: String,
+        /// Path of object inside repository
+        path: String,
+    },
+}
+
+impl Display for DiagnosticSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticSource::File(path) => write!(f, "{}", path.display()),
+            DiagnosticSource::Git { path, .. } => write!(f, "{path}"),
+        }
+    }
+}
+
+/// Parse error of txn data
+///
+/// Diagnostic has the position of error, the offending source line and
+/// the parser context (what was parsed and what was expected).
+/// This is returned as [`tackler::Error`](crate::tackler::Error),
+/// and it could be accessed with `downcast_ref::<ParseDiagnostic>()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDiagnostic {
+    /// Source of txn data, `None` with string input
+    pub source: Option<DiagnosticSource>,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Column number (1-based, in characters)
+    pub column: usize,
+    /// Content of the offending line
+    pub source_line: String,
+    /// Parser context labels, innermost first, e.g. "Txn Header"
+    pub labels: Vec<String>,
+    /// Expected tokens or formats
+    pub expected: Vec<String>,
+    /// Error message, e.g. semantic error of txn
+    pub message: Option<String>,
+}
+
+impl ParseDiagnostic {
+    pub(crate) fn from_parse_error(err: &ParseError<Stream<'_>, ContextError>) -> Self {
+        Self::from_context_error(err.input().input, err.offset(), err.inner())
+    }
+
+    /// Diagnostic of error at `offset` (in bytes) of `input`
+    pub(crate) fn from_context_error(input: &str, offset: usize, ctx: &ContextError) -> Self {
+        let offset = offset.min(input.len());
+        let prefix = input.get(..offset).unwrap_or(input);
+
+        let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+        let line = prefix.matches('\n').count() + 1;
+        let column = prefix[line_start..].chars().count() + 1;
+        let source_line = input[line_start..]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        let labels = ctx
+            .context()
+            .filter_map(|c| match c {
+                StrContext::Label(l) => Some(l.to_string()),
+                _ => None,
+            })
+            .collect();
+        let expected = ctx
+            .context()
+            .filter_map(|c| match c {
+                StrContext::Expected(e) => Some(e.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        ParseDiagnostic {
+            source: None,
+            line,
+            column,
+            source_line,
+            labels,
+            expected,
+            message: ctx.cause().map(|c| c.to_string()),
+        }
+    }
+
+    pub(crate) fn with_source(self, source: DiagnosticSource) -> Self {
+        ParseDiagnostic {
+            source: Some(source),
+            ..self
+        }
+    }
+}
+
+impl StdError for ParseDiagnostic {}
+
+/// All parse errors of txn data (error recovery mode)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseErrors {
+    /// Parse errors in the order of input
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl StdError for ParseErrors {}
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for diag in &self.diagnostics {
+            writeln!(f, "{diag}\n")?;
+        }
+        write!(f, "Found {} parse errors", self.diagnostics.len())
+    }
+}
+
+/// Set source of parse error(s)
+///
+/// Other errors are returned as they are.
+pub(crate) fn with_source(err: tackler::Error, source: DiagnosticSource) -> tackler::Error {
+    let err = match err.downcast::<ParseDiagnostic>() {
+        Ok(diag) => return diag.with_source(source).into(),
+        Err(err) => err,
+    };
+    match err.downcast::<ParseErrors>() {
+        Ok(errs) => ParseErrors {
+            diagnostics: errs
+                .diagnostics
+                .into_iter()
+                .map(|d| d.with_source(source.clone()))
+                .collect(),
+        }
+        .into(),
+        Err(err) => err,
+    }
+}
+
+/// Diagnostics of parse error(s), or the original error if it's not a parse error
+pub(crate) fn into_diagnostics(
+    err: tackler::Error,
+) -> Result<Vec<ParseDiagnostic>, tackler::Error> {
+    let err = match err.downcast::<ParseDiagnostic>() {
+        Ok(diag) => return Ok(vec![*diag]),
+        Err(err) => err,
+    };
+    match err.downcast::<ParseErrors>() {
+        Ok(errs) => Ok(errs.diagnostics),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parse error, rendered for console
+///
+/// ```text
+/// parse error: invalid Txn Header
+///   --> txns/2024/01/02.txn:12:3
+///    |
+/// 12 | 2024-01-32
+///    |   ^ expected ISO 8601 timestamp
+/// ```
+impl Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        match (self.labels.first(), &self.message) {
+            (_, Some(msg)) => writeln!(f, "parse error: {msg}")?,
+            (Some(label), None) => writeln!(f, "parse error: invalid {label}")?,
+            (None, None) => writeln!(f, "parse error: invalid input")?,
+        }
+        match &self.source {
+            Some(src) => writeln!(f, "{gutter}--> {src}:{}:{}", self.line, self.column)?,
+            None => writeln!(f, "{gutter}--> line {}, column {}", self.line, self.column)?,
+        }
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{gutter} | {}^", " ".repeat(self.column - 1))?;
+        if !self.expected.is_empty() {
+            write!(f, " expected {}", self.expected.join(", ").trim_end())?;
+        }
+
+        if self.message.is_some() {
+            if let Some(label) = self.labels.first() {
+                write!(f, "\n{gutter} = while parsing {label}")?;
+            }
+        }
+        if let Some(DiagnosticSource::Git { commit, object, .. }) = &self.source {
+            write!(f, "\n{gutter} = commit id: {commit}, object id: {object}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn test_parse_diagnostic() {
+        let journal = indoc!(
+            "|2024-01-01 'ok
+             | e:office  10
+             | a:cash
+             |
+             |2024-01-02 'ö
+             |e:office  10
+             | a:cash
+             |"
+        )
+        .strip_margin();
+        let res = parser::string_to_txns(&mut journal.as_str(), &mut Settings::default());
+        let err = res.err().unwrap(/*:test:*/);
+        let diag = err.downcast_ref::<ParseDiagnostic>().unwrap(/*:test:*/);
+
+        assert_eq!(diag.source, None);
+        assert_eq!(diag.line, 6);
+        assert_eq!(diag.column, 1);
+        assert_eq!(diag.source_line, "e:office  10");
+        assert!(err.to_string().contains("--> line 6, column 1"));
+    }
+
+    #[test]
+    fn test_parse_diagnostic_column() {
+        let journal = indoc!(
+            "|2024-01-01 'äö
+             | e:öffice  1.0.0
+             | a:cash
+             |"
+        )
+        .strip_margin();
+        let res = parser::string_to_txns(&mut journal.as_str(), &mut Settings::default());
+        let err = res.err().unwrap(/*:test:*/);
+        let diag = err.downcast_ref::<ParseDiagnostic>().unwrap(/*:test:*/);
+
+        assert_eq!(diag.line, 2);
+        // column is counted in characters, not in bytes
+        assert_eq!(diag.column, 15);
+        assert_eq!(diag.source_line, " e:öffice  1.0.0");
+        assert_eq!(
+            diag.labels.first().map(String::as_str),
+            Some("Txn Postings")
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostic_semantic() {
+        let journal = indoc!(
+            "|2017-05-05
+             | e  1000 USD
+             | a -1000 EUR
+             |"
+        )
+        .strip_margin();
+        let res = parser::string_to_txns(&mut journal.as_str(), &mut Settings::default());
+        let err = res.err().unwrap(/*:test:*/);
+        let diag = err.downcast_ref::<ParseDiagnostic>().unwrap(/*:test:*/);
+
+        assert!(
+            diag.message
+                .as_ref()
+                .is_some_and(|m| m.starts_with("Semantic error:"))
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostic_display() {
+        let diag = ParseDiagnostic {
+            source: Some(DiagnosticSource::File(PathBuf::from("txns/a.txn"))),
+            line: 12,
+            column: 3,
+            source_line: "2024-01-32".to_string(),
+            labels: vec!["Txn Header".to_string()],
+            expected: vec!["ISO 8601 timestamp".to_string()],
+            message: None,
+        };
+        let reference = indoc!(
+            "|parse error: invalid Txn Header
+             |  --> txns/a.txn:12:3
+             |   |
+             |12 | 2024-01-32
+             |   |   ^ expected ISO 8601 timestamp"
+        )
+        .strip_margin();
+        assert_eq!(diag.to_string(), reference);
+    }
+
+    #[test]
+    fn test_parse_diagnostic_display_git() {
+        let diag = ParseDiagnostic {
+            source: Some(DiagnosticSource::Git {
+                commit: "4926337e".to_string(),
+                object: "fe2264bd".to_string(),
+                path: "txns/a.txn".to_string(),
+            }),
+            line: 3,
+            column: 1,
+            source_line: " a:cash".to_string(),
+            labels: vec!["posting".to_string()],
+            expected: vec![],
+            message: Some("Semantic error: TXN postings do not zero: 2".to_string()),
+        };
+        let reference = indoc!(
+            "|parse error: Semantic error: TXN postings do not zero: 2
+             | --> txns/a.txn:3:1
+             |  |
+             |3 |  a:cash
+             |  | ^
+             |  = while parsing posting
+             |  = commit id: 4926337e, object id: fe2264bd"
+        )
+        .strip_margin();
+        assert_eq!(diag.to_string(), reference);
+    }
+
+    #[test]
+    fn test_parse_errors_display() {
+        let diag = ParseDiagnostic {
+            source: None,
+            line: 1,
+            column: 1,
+            source_line: "; comment".to_string(),
+            labels: vec![],
+            expected: vec![],
+            message: None,
+        };
+        let errs = ParseErrors {
+            diagnostics: vec![diag.clone(), diag],
+        };
+        let reference = indoc!(
+            "|parse error: invalid input
+             | --> line 1, column 1
+             |  |
+             |1 | ; comment
+             |  | ^
+             |
+             |parse error: invalid input
+             | --> line 1, column 1
+             |  |
+             |1 | ; comment
+             |  | ^
+             |
+             |Found 2 parse errors"
+        )
+        .strip_margin();
+        assert_eq!(errs.to_string(), reference);
+    }
+}
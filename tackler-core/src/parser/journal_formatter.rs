@@ -0,0 +1,256 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Journal formatter
+//!
+//! Formats txn journal into canonical layout:
+//! - timestamps are normalized: date only, if the timestamp is
+//!   the same as the date with default time and timezone, otherwise
+//!   RFC-3339 timestamp with offset
+//! - txn metadata is in fixed order (uuid, location, tags, key-values),
+//!   and it is followed by txn comments
+//! - postings are indented with three spaces, and their amounts are aligned
+//!   by decimal point
+//! - posting metadata is in fixed order (tags, date, key-values), and it is
+//!   indented with six spaces
+//! - transactions are separated by single empty line, consecutive balance
+//!   directives are kept together
+use crate::kernel::Settings;
+use crate::model::{Posting, Transaction};
+use crate::parser::parts::txns::JournalItem;
+use crate::parser::tackler_parser;
+use crate::tackler;
+use jiff::Zoned;
+use jiff::tz::TimeZone;
+use std::fmt::Write;
+use tackler_api::txn_ts;
+
+const INDENT: &str = "   ";
+
+fn format_ts(ts: &Zoned, settings: &Settings) -> Result<String, tackler::Error> {
+    let date_ts = settings.get_offset_date(ts.date())?;
+    if date_ts.timestamp() == ts.timestamp() && date_ts.offset() == ts.offset() {
+        Ok(ts.date().to_string())
+    } else {
+        Ok(txn_ts::rfc_3339(ts))
+    }
+}
+
+/// Split amount into integer (with sign) and fractional (with decimal point) parts
+fn split_amount(p: &Posting) -> (String, String) {
+    let amount = p.amount.to_string();
+    match amount.split_once('.') {
+        Some((int, frac)) => (int.to_string(), format!(".{frac}")),
+        None => (amount, String::new()),
+    }
+}
+
+fn format_txn(txn: &Transaction, settings: &Settings) -> Result<String, tackler::Error> {
+    let mut output = format_ts(&txn.header.timestamp, settings)?;
+    output.push_str(&txn.header.to_string_with_indent(
+        INDENT,
+        |_ts, _tz| String::new(),
+        TimeZone::UTC,
+    ));
+
+    let acc_width = txn
+        .posts
+        .iter()
+        .map(|p| p.acctn.atn.to_string().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let amounts: Vec<_> = txn.posts.iter().map(split_amount).collect();
+    let (int_width, frac_width) = txn
+        .posts
+        .iter()
+        .zip(amounts.iter())
+        .filter(|(p, _)| !p.is_inferred)
+        .fold((0, 0), |(iw, fw), (_, (int, frac))| {
+            (iw.max(int.len()), fw.max(frac.len()))
+        });
+
+    for (p, (int, frac)) in txn.posts.iter().zip(amounts.iter()) {
+        let line = if p.is_inferred {
+            format!(
+                "{INDENT}{}{}",
+                p.acctn.atn,
+                p.comment
+                    .as_ref()
+                    .map(|c| format!(" ; {c}"))
+                    .unwrap_or_default()
+            )
+        } else {
+            format!(
+                "{INDENT}{:<acc_width$}  {:>int_width$}{:<frac_width$}{}",
+                p.acctn.atn.to_string(),
+                int,
+                frac,
+                p.value_suffix()
+            )
+        };
+        let _ = writeln!(output, "{}", line.trim_end());
+        if let Some(meta) = &p.meta {
+            output.push_str(&meta.to_string_with_indent(&INDENT.repeat(2)));
+        }
+    }
+    Ok(output)
+}
+
+/// Format txn journal into canonical layout
+///
+/// Journal is parsed with given settings, so the result will be
+/// parsed into the same transactions as the original journal.
+///
+/// # Errors
+/// Returns an error if the journal can't be parsed
+pub fn format_journal(input: &str, settings: &mut Settings) -> Result<String, tackler::Error> {
+    let items = tackler_parser::journal_text(&mut &*input, settings)?;
+
+    let mut output = String::with_capacity(input.len());
+    let mut prev_is_balance = None;
+    for item in &items {
+        let is_balance = matches!(item, JournalItem::Balance(_));
+        match prev_is_balance {
+            Some(true) if is_balance => {}
+            Some(_) => output.push('\n'),
+            None => {}
+        }
+        match item {
+            JournalItem::Txn(txn) => output.push_str(&format_txn(txn, settings)?),
+            JournalItem::Balance(ba) => output.push_str(&format!("{ba}\n")),
+        }
+        prev_is_balance = Some(is_balance);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn format_postings() {
+        let input = indoc!(
+            "|2024-01-01 (#001) 'txn
+             |  Expenses:Food 1.5 EUR ; lunch
+             | Assets:Cash:Wallet  -10.25 EUR
+             |     Assets:Bank   8.75 EUR
+             |"
+        )
+        .strip_margin();
+        let reference = indoc!(
+            "|2024-01-01 (#001) 'txn
+             |   Expenses:Food         1.5  EUR ; lunch
+             |   Assets:Cash:Wallet  -10.25 EUR
+             |   Assets:Bank           8.75 EUR
+             |"
+        )
+        .strip_margin();
+
+        let mut settings = Settings::default();
+        let res = format_journal(&input, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(res, reference);
+    }
+
+    #[test]
+    fn format_metadata_and_inferred_amount() {
+        let input = indoc!(
+            "|2024-01-01T00:00:00Z 'txn
+             | # invoice: 2024-117
+             | # tags: a, b
+             | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+             | ; comment
+             | Expenses:Food 1
+             |  # vat: 24
+             |  # tags: vat:food
+             | Assets:Cash ; inferred
+             |
+             |
+             |2024-01-02T10:11:12+02:00 'txn
+             | Expenses:Food 2 ACME @ 1.5 EUR
+             | Assets:Cash
+             |"
+        )
+        .strip_margin();
+        let reference = indoc!(
+            "|2024-01-01 'txn
+             |   # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+             |   # tags: a, b
+             |   # invoice: 2024-117
+             |   ; comment
+             |   Expenses:Food  1
+             |      # tags: vat:food
+             |      # vat: 24
+             |   Assets:Cash ; inferred
+             |
+             |2024-01-02T10:11:12+02:00 'txn
+             |   Expenses:Food  2 ACME @ 1.5 EUR
+             |   Assets:Cash
+             |"
+        )
+        .strip_margin();
+
+        let mut settings = Settings::default();
+        let res = format_journal(&input, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(res, reference);
+    }
+
+    #[test]
+    fn format_balance_directives() {
+        let input = indoc!(
+            "|2024-01-01 'txn
+             | Assets:Bank 10 EUR
+             | Equity
+             |
+             |2024-01-31 balance Assets:Bank 10 EUR
+             |2024-01-31 balance Equity -10 EUR
+             |2024-02-01 'txn
+             | Assets:Bank 1 EUR
+             | Equity
+             |"
+        )
+        .strip_margin();
+        let reference = indoc!(
+            "|2024-01-01 'txn
+             |   Assets:Bank  10 EUR
+             |   Equity
+             |
+             |2024-01-31 balance Assets:Bank 10 EUR
+             |2024-01-31 balance Equity -10 EUR
+             |
+             |2024-02-01 'txn
+             |   Assets:Bank  1 EUR
+             |   Equity
+             |"
+        )
+        .strip_margin();
+
+        let mut settings = Settings::default();
+        let res = format_journal(&input, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(res, reference);
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        let input = indoc!(
+            "|2024-01-01T12:00:00+03:00 (c) 'txn
+             | # location: geo:60.17,24.95
+             | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+             | Assets:Invest  10 ACME {10 EUR} @ 10 EUR
+             | Assets:Cash  -100 EUR == -100 EUR
+             |"
+        )
+        .strip_margin();
+
+        let mut settings = Settings::default();
+        let first = format_journal(&input, &mut settings).unwrap(/*:test:*/);
+        let mut settings = Settings::default();
+        let second = format_journal(&first, &mut settings).unwrap(/*:test:*/);
+        assert_eq!(first, second);
+    }
+}
@@ -3,19 +3,22 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+mod balance_assertion;
 mod comment;
 pub(crate) mod identifier;
 pub(crate) mod number;
+mod posting_meta;
 mod posting_value;
 pub(super) mod pricedb;
 pub(crate) mod timestamp;
 mod txn_comment;
 mod txn_header;
-mod txn_header_code;
+pub(crate) mod txn_header_code;
 mod txn_header_desc;
 mod txn_meta_location;
 mod txn_meta_tags;
 mod txn_meta_uuid;
+mod txn_meta_value;
 mod txn_metadata;
 mod txn_posting;
 mod txn_postings;
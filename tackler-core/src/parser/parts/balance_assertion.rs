@@ -0,0 +1,168 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+use crate::kernel::Settings;
+use crate::model::BalanceAssertion;
+use crate::parser::parts::identifier::{p_identifier, p_multi_part_id};
+use crate::parser::parts::number::p_number;
+use crate::parser::parts::timestamp::parse_timestamp;
+use crate::parser::{Stream, from_error};
+use crate::tackler;
+use rust_decimal::Decimal;
+use winnow::ascii::{line_ending, space0, space1};
+use winnow::combinator::{alt, cut_err, eof, opt, preceded};
+use winnow::error::{StrContext, StrContextValue};
+use winnow::{ModalResult, Parser, seq};
+
+/*
+posting_assertion: sp '==' sp amount (sp unit)?;
+
+balance_directive: timestamp sp 'balance' sp account sp amount (sp unit)? opt_sp NL;
+ */
+
+/// Posting level balance assertion: `== amount [commodity]`
+pub(crate) fn p_posting_assertion<'s>(
+    is: &mut Stream<'s>,
+) -> ModalResult<(Decimal, Option<&'s str>)> {
+    const CTX_LABEL: &str = "balance assertion";
+    let m = seq!(
+        _: space1,
+        _: "==",
+        _: cut_err(space1)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("space"))),
+        cut_err(p_number)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("number"))),
+        opt(preceded(space1, p_identifier)),
+    )
+    .parse_next(is)?;
+
+    Ok(m)
+}
+
+fn handle_balance_directive(
+    (timestamp, ts_text): (jiff::Zoned, &str),
+    account: &str,
+    amount: Decimal,
+    commodity: Option<&str>,
+    settings: &mut Settings,
+) -> Result<BalanceAssertion, tackler::Error> {
+    let comm = settings.get_or_create_commodity(commodity)?;
+    let acctn = settings.get_or_create_txn_account(account, comm)?;
+
+    Ok(BalanceAssertion {
+        timestamp,
+        is_date: !ts_text.contains('T'),
+        acctn,
+        amount,
+    })
+}
+
+/// Standalone balance assertion directive
+///
+/// `YYYY-MM-DD balance account amount [commodity]`
+pub(crate) fn parse_balance_directive(is: &mut Stream<'_>) -> ModalResult<BalanceAssertion> {
+    const CTX_LABEL: &str = "balance directive";
+    let m = seq!(
+        parse_timestamp.with_taken(),
+        _: space1,
+        _: "balance",
+        _: cut_err(space1)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("space"))),
+        cut_err(p_multi_part_id)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("account name"))),
+        _: cut_err(space1)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("space"))),
+        cut_err(p_number)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("number"))),
+        opt(preceded(space1, p_identifier)),
+        _: space0,
+        _: cut_err(alt((line_ending, eof)))
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "format: timestamp balance account amount [commodity]"
+            ))),
+    )
+    .parse_next(is)?;
+
+    match handle_balance_directive(m.0, m.1, m.2, m.3, is.state) {
+        Ok(ba) => Ok(ba),
+        Err(err) => Err(from_error(is, err.as_ref())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p_posting_assertion() {
+        let tests = [
+            (" == 123\n", None),
+            (" == -1.23 €\n", Some("€")),
+            (" == 10 ACME·INC ; comment\n", Some("ACME·INC")),
+        ];
+        for t in tests {
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input: t.0,
+                state: &mut settings,
+            };
+            let res = p_posting_assertion(&mut is);
+            assert!(res.is_ok(), "input: {}", t.0);
+            assert_eq!(res.unwrap(/*:test:*/).1, t.1);
+        }
+    }
+
+    #[test]
+    fn test_parse_balance_directive() {
+        let mut settings = Settings::default();
+        let mut is = Stream {
+            input: "2024-01-31 balance a:b:c -1.23 €\n",
+            state: &mut settings,
+        };
+        let res = parse_balance_directive(&mut is);
+        assert!(res.is_ok());
+
+        let ba = res.unwrap(/*:test:*/);
+        assert_eq!(ba.acctn.atn.account, "a:b:c");
+        assert_eq!(ba.acctn.comm.name, "€");
+        assert_eq!(ba.amount.to_string(), "-1.23");
+        assert!(ba.is_date);
+    }
+
+    #[test]
+    fn test_parse_balance_directive_ts() {
+        let mut settings = Settings::default();
+        let mut is = Stream {
+            input: "2024-01-31T12:00:00Z balance a:b:c 1\n",
+            state: &mut settings,
+        };
+        let ba = parse_balance_directive(&mut is).unwrap(/*:test:*/);
+        assert!(!ba.is_date);
+        assert_eq!(ba.to_string(), "2024-01-31T12:00:00+00:00 balance a:b:c 1");
+    }
+
+    #[test]
+    fn test_parse_balance_directive_err() {
+        let tests = [
+            "2024-01-31 balance a:b:c\n",
+            "2024-01-31 balance a:b:c 12 € foo\n",
+        ];
+        for t in tests {
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input: t,
+                state: &mut settings,
+            };
+            let res = parse_balance_directive(&mut is);
+            assert!(res.is_err(), "input: {}", t);
+        }
+    }
+}
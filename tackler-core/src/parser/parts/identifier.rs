@@ -48,7 +48,7 @@ NameStartChar
 
 const CTX_LABEL: &str = "name";
 
-fn id_char(c: char) -> bool {
+pub(crate) fn id_char(c: char) -> bool {
     id_start_char(c)
         | matches!(
             c,
@@ -59,7 +59,7 @@ fn id_char(c: char) -> bool {
         )
 }
 
-fn id_start_char(c: char) -> bool {
+pub(crate) fn id_start_char(c: char) -> bool {
     matches!(c,
         'a'..='z'
         | 'A'..='Z'
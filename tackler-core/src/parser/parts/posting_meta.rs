@@ -0,0 +1,197 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::model::PostingMeta;
+use crate::parser::parts::timestamp::p_date;
+use crate::parser::parts::txn_meta_tags::p_tags;
+use crate::parser::parts::txn_meta_value::p_meta_key_value;
+use crate::parser::{Stream, from_error};
+use crate::tackler;
+use tackler_api::txn_header::Tags;
+use winnow::ascii::{line_ending, space0, space1};
+use winnow::combinator::{alt, cut_err, preceded, repeat};
+use winnow::error::{StrContext, StrContextValue};
+use winnow::{ModalResult, Parser, seq};
+
+const CTX_LABEL: &str = "posting metadata";
+
+enum PostingMetaItem<'s> {
+    Tags(Tags),
+    Date(jiff::civil::Date),
+    Value(&'s str, &'s str),
+}
+
+fn p_meta_tags<'s>(is: &mut Stream<'s>) -> ModalResult<PostingMetaItem<'s>> {
+    preceded(
+        ("tags:", space1),
+        cut_err(p_tags)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "valid tags",
+            ))),
+    )
+    .map(PostingMetaItem::Tags)
+    .parse_next(is)
+}
+
+fn p_meta_date<'s>(is: &mut Stream<'s>) -> ModalResult<PostingMetaItem<'s>> {
+    preceded(
+        ("date:", space1),
+        cut_err(p_date)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "date as 'YYYY-MM-DD'",
+            ))),
+    )
+    .map(PostingMetaItem::Date)
+    .parse_next(is)
+}
+
+fn p_meta_value<'s>(is: &mut Stream<'s>) -> ModalResult<PostingMetaItem<'s>> {
+    p_meta_key_value
+        .map(|(key, value)| PostingMetaItem::Value(key, value))
+        .parse_next(is)
+}
+
+fn p_meta_item<'s>(is: &mut Stream<'s>) -> ModalResult<PostingMetaItem<'s>> {
+    let m = seq!(
+        _: space1,
+        _: '#',
+        _: cut_err(space1)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("space after '#'"))),
+        cut_err(alt((p_meta_tags, p_meta_date, p_meta_value)))
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "'tags: <tags>', 'date: <date>' or '<key>: <value>'"
+            ))),
+        _: space0,
+        _: cut_err(line_ending)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("line ending"))),
+    )
+    .parse_next(is)?;
+
+    Ok(m.0)
+}
+
+fn handle_meta(items: Vec<PostingMetaItem<'_>>) -> Result<PostingMeta, tackler::Error> {
+    let mut meta = PostingMeta::default();
+    for item in items {
+        match item {
+            PostingMetaItem::Tags(tags) => {
+                if meta.tags.is_some() {
+                    return Err("posting metadata contains duplicate 'tags'".into());
+                }
+                meta.tags = Some(tags);
+            }
+            PostingMetaItem::Date(date) => {
+                if meta.date.is_some() {
+                    return Err("posting metadata contains duplicate 'date'".into());
+                }
+                meta.date = Some(date);
+            }
+            PostingMetaItem::Value(key, value) => {
+                if matches!(key, "uuid" | "location" | "tags" | "date") {
+                    let msg = format!("posting metadata: '{key}' is not supported with postings");
+                    return Err(msg.into());
+                }
+                if meta.values.iter().any(|(k, _)| k == key) {
+                    let msg = format!("posting metadata contains duplicate '{key}'");
+                    return Err(msg.into());
+                }
+                meta.values.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+    Ok(meta)
+}
+
+/// Metadata lines of posting, e.g. `# tags: vat`, `# date: 2025-01-31` or `# key: value`
+pub(crate) fn parse_posting_meta(is: &mut Stream<'_>) -> ModalResult<Option<PostingMeta>> {
+    let items: Vec<_> = repeat(0.., p_meta_item).parse_next(is)?;
+    if items.is_empty() {
+        return Ok(None);
+    }
+    match handle_meta(items) {
+        Ok(meta) => Ok(Some(meta)),
+        Err(err) => Err(from_error(is, err.as_ref())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn test_parse_posting_meta() {
+        let input = indoc!(
+            "| # tags: vat:deductible, receipt
+             | # date: 2025-01-31
+             | # share: 24 %
+             | # receipt-id: 1234\u{20}
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let mut is = Stream {
+            input: &input,
+            state: &mut settings,
+        };
+
+        let meta = parse_posting_meta(&mut is).unwrap(/*:test:*/).unwrap(/*:test:*/);
+        assert_eq!(meta.tags.map(|t| t.len()), Some(2));
+        assert_eq!(meta.date, Some(jiff::civil::date(2025, 1, 31)));
+        assert_eq!(
+            meta.values,
+            vec![
+                ("share".to_string(), "24 %".to_string()),
+                ("receipt-id".to_string(), "1234".to_string())
+            ]
+        );
+        assert!(is.input.is_empty());
+    }
+
+    #[test]
+    fn test_parse_posting_meta_none() {
+        let mut settings = Settings::default();
+        let mut is = Stream {
+            input: " a:b 1\n",
+            state: &mut settings,
+        };
+
+        let res = parse_posting_meta(&mut is);
+        assert!(matches!(res, Ok(None)));
+        assert_eq!(is.input, " a:b 1\n");
+    }
+
+    #[test]
+    fn test_parse_posting_meta_err() {
+        let tests = [
+            " # tags: a\n # tags: b\n",
+            " # date: 2025-01-01\n # date: 2025-01-02\n",
+            " # date: 2025-13-01\n",
+            " # key: a\n # key: b\n",
+            " # key:\n",
+            " # key:value\n",
+            " # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9\n",
+            " # location: geo:60.170,24.935\n",
+            " #tags: a\n",
+        ];
+        for input in tests {
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input,
+                state: &mut settings,
+            };
+
+            let res = parse_posting_meta(&mut is);
+            assert!(res.is_err(), "{input}");
+        }
+    }
+}
@@ -4,6 +4,7 @@
  */
 use crate::kernel::Settings;
 use crate::model::Commodity;
+use crate::model::posting::CostBasis;
 use crate::parser::parts::identifier::p_identifier;
 use crate::parser::parts::number::p_number;
 use crate::parser::{Stream, from_error};
@@ -11,7 +12,7 @@ use crate::tackler;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use winnow::ascii::{space0, space1};
-use winnow::combinator::{alt, opt};
+use winnow::combinator::{alt, not, opt, terminated};
 use winnow::{ModalResult, Parser, seq};
 /*
 // The old ANTLR Grammar
@@ -73,7 +74,7 @@ fn p_opening_pos<'s>(is: &mut Stream<'s>) -> ModalResult<Value<'s>> {
     Ok(Value {
         value: m.0,
         commodity: m.1,
-    }) // opening position is recognized but ignored at the moment
+    })
 }
 
 enum PriceType {
@@ -85,7 +86,8 @@ fn p_closing_pos<'s>(is: &mut Stream<'s>) -> ModalResult<(PriceType, Value<'s>)>
     const CTX_LABEL: &str = "closing position";
     let m = seq!(
         _:space1,
-        alt(('@', '=')),
+        // '==' is balance assertion, not total price
+        alt(('@', terminated('=', not('=')))),
         _:cut_err(space1)
             .context(StrContext::Label(CTX_LABEL))
             .context(StrContext::Expected(StrContextValue::Description("space"))),
@@ -160,6 +162,7 @@ pub(crate) struct ValuePosition {
     pub(crate) total_amount: bool,
     pub(crate) post_commodity: Arc<Commodity>,
     pub(crate) txn_commodity: Arc<Commodity>,
+    pub(crate) cost_basis: Option<CostBasis>,
 }
 
 fn handle_posting_value(
@@ -205,6 +208,26 @@ fn handle_posting_value(
         None => settings.get_or_create_commodity(None)?,
     };
 
+    let cost_basis = match opt_unit.as_ref().and_then(|u| u.1.as_ref()) {
+        Some(Positions {
+            opening: Some(op), ..
+        }) => {
+            let cost_commodity = settings.get_or_create_commodity(Some(op.commodity))?;
+            if post_commodity.name == cost_commodity.name {
+                let msg = format!(
+                    "Both commodities are same for opening position [{}]",
+                    cost_commodity.name
+                );
+                return Err(msg.into());
+            }
+            Some(CostBasis {
+                unit_cost: op.value,
+                commodity: cost_commodity,
+            })
+        }
+        _ => None,
+    };
+
     let post_amount = amount;
 
     let txn_amount: (Decimal, bool) = match &opt_unit {
@@ -273,6 +296,7 @@ fn handle_posting_value(
         total_amount: txn_amount.1,
         post_commodity,
         txn_commodity,
+        cost_basis,
     })
 }
 
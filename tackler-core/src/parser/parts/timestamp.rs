@@ -14,7 +14,7 @@ use winnow::token::take_while;
 
 const CTX_LABEL: &str = "ISO 8601 timestamp";
 
-fn p_date(is: &mut Stream<'_>) -> ModalResult<jiff::civil::Date> {
+pub(crate) fn p_date(is: &mut Stream<'_>) -> ModalResult<jiff::civil::Date> {
     let (y, m, d) = seq!(
         take_while(4, AsChar::is_dec_digit).try_map(i16::from_str)
             .context(StrContext::Label(CTX_LABEL))
@@ -64,7 +64,8 @@ ISO 8601 Timestamp:
         description: desc.map(String::from),
         uuid: meta.as_ref().and_then(|t| t.uuid),
         location: meta.as_ref().and_then(|t| t.location.clone()),
-        tags: meta.and_then(|t| t.tags.clone()),
+        tags: meta.as_ref().and_then(|t| t.tags.clone()),
+        meta: meta.and_then(|t| t.meta),
         comments: comments.map(|v| v.into_iter().map(String::from).collect()),
     })
 }
@@ -10,7 +10,7 @@ use winnow::error::StrContextValue;
 use winnow::token::take_while;
 use winnow::{ModalResult, Parser, seq};
 
-fn valid_code_char(c: char) -> bool {
+pub(crate) fn valid_code_char(c: char) -> bool {
     !matches!(
         c,
         ')' | '\'' | '(' | '[' | ']' | '{' | '}' | '<' | '>' | '\r' | '\n'
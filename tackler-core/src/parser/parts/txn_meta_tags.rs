@@ -38,7 +38,7 @@ fn handle_tags(v: Vec<&str>, settings: &mut Settings) -> Result<Tags, tackler::E
     Ok(tags)
 }
 
-fn p_tags(is: &mut Stream<'_>) -> ModalResult<Tags> {
+pub(crate) fn p_tags(is: &mut Stream<'_>) -> ModalResult<Tags> {
     let mut tags = (
         cut_err(p_multi_part_id)
             .context(StrContext::Label(CTX_LABEL))
@@ -0,0 +1,116 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::parser::parts::identifier::p_identifier;
+use crate::parser::{Stream, from_error};
+use winnow::ascii::{line_ending, space0, space1, till_line_ending};
+use winnow::combinator::cut_err;
+use winnow::error::{StrContext, StrContextValue};
+use winnow::{ModalResult, Parser, seq};
+
+const CTX_LABEL: &str = "txn metadata key-value";
+
+/// Key-value metadata `key: value`
+///
+/// Value is the rest of the line without trailing spaces, and it is checked
+/// against Chart of Metadata.
+pub(crate) fn p_meta_key_value<'s>(is: &mut Stream<'s>) -> ModalResult<(&'s str, &'s str)> {
+    let (key, value) = seq!(
+        p_identifier,
+        _: cut_err((':', space1))
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("': ' after key"))),
+        cut_err(till_line_ending.map(str::trim_end).verify(|v: &str| !v.is_empty()))
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("value after 'key: '"))),
+    )
+    .parse_next(is)?;
+
+    match is.state.check_meta_value(key, value) {
+        Ok(()) => Ok((key, value)),
+        Err(err) => Err(from_error(is, err.as_ref())),
+    }
+}
+
+pub(crate) fn parse_meta_value<'s>(is: &mut Stream<'s>) -> ModalResult<(&'s str, &'s str)> {
+    let kv = seq!(
+        _: space1,
+        _: '#',
+        _: cut_err(space1)
+            .context(StrContext::Label("txn metadata"))
+            .context(StrContext::Expected(StrContextValue::Description("space after '#'"))),
+        p_meta_key_value,
+        _: space0,
+        _: cut_err(line_ending)
+            .context(StrContext::Label(CTX_LABEL))
+            .context(StrContext::Expected(StrContextValue::Description("line ending"))),
+    )
+    .parse_next(is)?;
+
+    Ok(kv.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::Settings;
+
+    #[test]
+    fn test_parse_meta_value() {
+        let tests = [
+            (" # invoice: 2024-117\n", ("invoice", "2024-117")),
+            (
+                " # receipt: receipts/2024/117.pdf  \n",
+                ("receipt", "receipts/2024/117.pdf"),
+            ),
+            (" # note: a: b # c\n", ("note", "a: b # c")),
+        ];
+        for (input, reference) in tests {
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input,
+                state: &mut settings,
+            };
+
+            let res = parse_meta_value(&mut is);
+            assert_eq!(res.unwrap(/*:test:*/), reference);
+        }
+    }
+
+    #[test]
+    fn test_parse_meta_value_err() {
+        let tests = [" # invoice:\n", " # invoice: \n", " # invoice:2024\n"];
+        for input in tests {
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input,
+                state: &mut settings,
+            };
+
+            assert!(parse_meta_value(&mut is).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_meta_value_strict() {
+        let mut settings = Settings::default();
+        settings.set_strict_meta_keys(&[("invoice", Some(r"\d{4}-\d+")), ("receipt", None)]);
+
+        let tests = [
+            (" # invoice: 2024-117\n", true),
+            (" # receipt: anything\n", true),
+            (" # invoice: 117\n", false),
+            (" # unknown: 117\n", false),
+        ];
+        for (input, ok) in tests {
+            let mut is = Stream {
+                input,
+                state: &mut settings,
+            };
+
+            assert_eq!(parse_meta_value(&mut is).is_ok(), ok, "{input}");
+        }
+    }
+}
@@ -3,95 +3,81 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use crate::parser::Stream;
 use crate::parser::parts::txn_meta_location::parse_meta_location;
 use crate::parser::parts::txn_meta_tags::parse_meta_tags;
 use crate::parser::parts::txn_meta_uuid::parse_meta_uuid;
+use crate::parser::parts::txn_meta_value::parse_meta_value;
+use crate::parser::{Stream, from_error};
+use crate::tackler;
 use tackler_api::location::GeoPoint;
-use tackler_api::txn_header::Tags;
+use tackler_api::txn_header::{Metadata, Tags};
 use uuid::Uuid;
-use winnow::combinator::{alt, opt};
-use winnow::{ModalResult, Parser, seq};
+use winnow::combinator::{alt, repeat};
+use winnow::{ModalResult, Parser};
 
 pub(crate) struct TxnMeta {
     pub(crate) uuid: Option<Uuid>,
     pub(crate) tags: Option<Tags>,
     pub(crate) location: Option<GeoPoint>,
+    pub(crate) meta: Option<Metadata>,
 }
 
-fn permutation_uuid(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = parse_meta_uuid.parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: Some(m),
-        tags: None,
-        location: None,
-    })
+enum TxnMetaItem<'s> {
+    Uuid(Uuid),
+    Location(GeoPoint),
+    Tags(Tags),
+    Value(&'s str, &'s str),
 }
 
-fn permutation_uuid_tags_o_location(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_uuid, parse_meta_tags, opt(parse_meta_location)).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: Some(m.0),
-        tags: Some(m.1),
-        location: m.2,
-    })
-}
-fn permutation_uuid_location_o_tags(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_uuid, parse_meta_location, opt(parse_meta_tags),).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: Some(m.0),
-        tags: m.2,
-        location: Some(m.1),
-    })
-}
-fn permutation_tags(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = parse_meta_tags.parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: None,
-        tags: Some(m),
-        location: None,
-    })
-}
-fn permutation_tags_uuid_o_location(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_tags, parse_meta_uuid, opt(parse_meta_location)).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: Some(m.1),
-        tags: Some(m.0),
-        location: m.2,
-    })
+fn p_meta_item<'s>(is: &mut Stream<'s>) -> ModalResult<TxnMetaItem<'s>> {
+    alt((
+        parse_meta_uuid.map(TxnMetaItem::Uuid),
+        parse_meta_location.map(TxnMetaItem::Location),
+        parse_meta_tags.map(TxnMetaItem::Tags),
+        parse_meta_value.map(|(key, value)| TxnMetaItem::Value(key, value)),
+    ))
+    .parse_next(is)
 }
-fn permutation_tags_location_o_uuid(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_tags, parse_meta_location, opt(parse_meta_uuid),).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: m.2,
-        tags: Some(m.0),
-        location: Some(m.1),
-    })
+
+fn duplicate(key: &str) -> tackler::Error {
+    let msg = format!("txn metadata contains duplicate '{key}'");
+    msg.into()
 }
 
-fn permutation_location(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = parse_meta_location.parse_next(is)?;
-    Ok(TxnMeta {
+fn handle_meta(items: Vec<TxnMetaItem<'_>>) -> Result<TxnMeta, tackler::Error> {
+    let mut txn_meta = TxnMeta {
         uuid: None,
         tags: None,
-        location: Some(m),
-    })
-}
-fn permutation_location_uuid_o_tags(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_location, parse_meta_uuid, opt(parse_meta_tags)).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: Some(m.1),
-        tags: m.2,
-        location: Some(m.0),
-    })
-}
-fn permutation_location_tags_o_uuid(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
-    let m = seq!(parse_meta_location, parse_meta_tags, opt(parse_meta_uuid),).parse_next(is)?;
-    Ok(TxnMeta {
-        uuid: m.2,
-        tags: Some(m.1),
-        location: Some(m.0),
-    })
+        location: None,
+        meta: None,
+    };
+    for item in items {
+        match item {
+            TxnMetaItem::Uuid(uuid) => {
+                if txn_meta.uuid.replace(uuid).is_some() {
+                    return Err(duplicate("uuid"));
+                }
+            }
+            TxnMetaItem::Location(geo) => {
+                if txn_meta.location.replace(geo).is_some() {
+                    return Err(duplicate("location"));
+                }
+            }
+            TxnMetaItem::Tags(tags) => {
+                if txn_meta.tags.replace(tags).is_some() {
+                    return Err(duplicate("tags"));
+                }
+            }
+            TxnMetaItem::Value(key, value) => {
+                let meta = txn_meta.meta.get_or_insert_with(Vec::new);
+                if meta.iter().any(|(k, _)| k == key) {
+                    return Err(duplicate(key));
+                }
+                meta.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+    Ok(txn_meta)
 }
 
 pub(crate) fn parse_txn_meta(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
@@ -103,31 +89,16 @@ pub(crate) fn parse_txn_meta(is: &mut Stream<'_>) -> ModalResult<TxnMeta> {
      *      |  {$l < 1}? txn_meta_location NL  { let tmp = $l; $l = (tmp+1); }
      *      |  {$t < 1}? txn_meta_tags NL      { let tmp = $t; $t = (tmp+1); }
      *      )+;
+     *
+     * Metadata lines could be in any order, and each of uuid, location, tags
+     * and key of key-value metadata could be used only once.
      */
+    let items: Vec<_> = repeat(1.., p_meta_item).parse_next(is)?;
 
-    // todo: meta permutation: is there better way?
-    //
-    // "The Winner Takes It All"
-    //
-    // Alt: Pick the first successful parser, so try
-    // the combinations in descending order of (common, length)
-    let meta = alt((
-        // uuid
-        permutation_uuid_tags_o_location,
-        permutation_uuid_location_o_tags,
-        permutation_uuid,
-        // tags
-        permutation_tags_uuid_o_location,
-        permutation_tags_location_o_uuid,
-        permutation_tags,
-        // location
-        permutation_location_uuid_o_tags,
-        permutation_location_tags_o_uuid,
-        permutation_location,
-    ))
-    .parse_next(is)?;
-
-    Ok(meta)
+    match handle_meta(items) {
+        Ok(meta) => Ok(meta),
+        Err(err) => Err(from_error(is, err.as_ref())),
+    }
 }
 
 #[cfg(test)]
@@ -286,4 +257,73 @@ mod tests {
         }
         assert_eq!(count, 15);
     }
+
+    #[test]
+    fn test_parse_txn_meta_key_values() {
+        let input = indoc!(
+            "| # invoice: 2024-117
+             | # tags: cef, first
+             | # receipt: receipts/2024/117.pdf
+             | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+             | ; comment
+             |"
+        )
+        .strip_margin();
+        let mut settings = Settings::default();
+        let mut is = Stream {
+            input: input.as_str(),
+            state: &mut settings,
+        };
+
+        let meta = parse_txn_meta(&mut is).unwrap(/*:test:*/);
+        assert!(meta.uuid.is_some());
+        assert!(meta.tags.is_some());
+        assert!(meta.location.is_none());
+        assert_eq!(
+            meta.meta,
+            Some(vec![
+                ("invoice".to_string(), "2024-117".to_string()),
+                ("receipt".to_string(), "receipts/2024/117.pdf".to_string()),
+            ])
+        );
+        assert_eq!(is.input, " ; comment\n");
+    }
+
+    #[test]
+    fn test_parse_txn_meta_duplicates() {
+        let tests = [
+            indoc!(
+                "| # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+                 | # tags: cef
+                 | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+                 |"
+            ),
+            indoc!(
+                "| # location: geo:1.111,2.222
+                 | # location: geo:1.111,2.222
+                 |"
+            ),
+            indoc!(
+                "| # tags: cef
+                 | # tags: first
+                 |"
+            ),
+            indoc!(
+                "| # invoice: 2024-117
+                 | # tags: cef
+                 | # invoice: 2024-118
+                 |"
+            ),
+        ];
+        for input in tests {
+            let input = input.strip_margin();
+            let mut settings = Settings::default();
+            let mut is = Stream {
+                input: input.as_str(),
+                state: &mut settings,
+            };
+
+            assert!(parse_txn_meta(&mut is).is_err(), "{input}");
+        }
+    }
 }
@@ -4,11 +4,13 @@
  */
 use crate::kernel::Settings;
 use crate::model::Posting;
+use crate::parser::parts::balance_assertion::p_posting_assertion;
 use crate::parser::parts::comment::p_comment;
 use crate::parser::parts::identifier::p_multi_part_id;
 use crate::parser::parts::posting_value::{ValuePosition, parse_posting_value};
 use crate::parser::{Stream, from_error};
 use crate::tackler;
+use rust_decimal::Decimal;
 use winnow::ascii::{line_ending, space0, space1};
 use winnow::combinator::opt;
 use winnow::{ModalResult, Parser, seq};
@@ -59,20 +61,34 @@ pub(crate) fn parse_txn_last_posting<'s>(
 fn handle_posting(
     acc_id: &str,
     vp: ValuePosition,
+    assertion: Option<(Decimal, Option<&str>)>,
     comment: Option<&str>,
     settings: &mut Settings,
 ) -> Result<Posting, tackler::Error> {
     let comm = vp.post_commodity;
+    if let Some((_, Some(ba_comm))) = assertion {
+        if ba_comm != comm.name {
+            let msg = format!(
+                "Balance assertion commodity [{}] is different than posting commodity [{}]",
+                ba_comm, comm.name
+            );
+            return Err(msg.into());
+        }
+    }
     let acctn = settings.get_or_create_txn_account(acc_id, comm.clone())?;
 
-    Posting::from(
+    let mut posting = Posting::from(
         acctn,
         vp.post_amount,
         vp.txn_amount,
         vp.total_amount,
         vp.txn_commodity,
         comment.map(String::from),
-    )
+    )?;
+    posting.cost_basis = vp.cost_basis;
+    posting.balance_assertion = assertion.map(|a| a.0);
+
+    Ok(posting)
 }
 
 pub(crate) fn parse_txn_posting(is: &mut Stream<'_>) -> ModalResult<Posting> {
@@ -81,13 +97,14 @@ pub(crate) fn parse_txn_posting(is: &mut Stream<'_>) -> ModalResult<Posting> {
         p_multi_part_id,
         _: space1,
         parse_posting_value,
+        opt(p_posting_assertion),
         _: space0,
         opt(p_comment),
         _: line_ending
     )
     .parse_next(is)?;
 
-    match handle_posting(m.0, m.1, m.2, is.state) {
+    match handle_posting(m.0, m.1, m.2, m.3, is.state) {
         Ok(posting) => Ok(posting),
         Err(err) => Err(from_error(is, err.as_ref())),
     }
@@ -140,6 +157,10 @@ mod tests {
             " a:b:c -1 ACME·INC {120 EUR} @ 123 EUR\n",
             " a:b:c -1 ACME·INC {120 EUR}\n",
             " a:b:c  1 Au·µg {1 EUR}\n",
+            " abc 123 == 246\n",
+            " abc 123 € == 246 €\n",
+            " abc 26 bar·He_50L = 32.50 EUR == 52 bar·He_50L ; comment\n",
+            " a:b:c 1 ACME·INC {120 EUR} @ 123 EUR == 3 ACME·INC\n",
         ];
 
         for s in tests {
@@ -4,6 +4,7 @@
  */
 use crate::model::posting::txn_sum;
 use crate::model::{Posting, Posts};
+use crate::parser::parts::posting_meta::parse_posting_meta;
 use crate::parser::parts::txn_posting::{parse_txn_last_posting, parse_txn_posting};
 use crate::parser::{Stream, from_error};
 use std::ops::Neg;
@@ -12,15 +13,19 @@ use winnow::{ModalResult, Parser, seq};
 
 pub(crate) fn parse_txn_postings(is: &mut Stream<'_>) -> ModalResult<Posts> {
     let mut postings = seq!(
-        repeat(1.., parse_txn_posting).fold(Vec::new, |mut acc: Vec<_>, item| {
-            acc.push(item);
-            acc
-        }),
-        opt(parse_txn_last_posting),
+        repeat(1.., (parse_txn_posting, parse_posting_meta)).fold(
+            Vec::new,
+            |mut acc: Vec<_>, (mut posting, meta)| {
+                posting.meta = meta;
+                acc.push(posting);
+                acc
+            }
+        ),
+        opt((parse_txn_last_posting, parse_posting_meta)),
     )
     .parse_next(is)?;
 
-    if let Some(p) = postings.1 {
+    if let Some((p, meta)) = postings.1 {
         let amount = txn_sum(&postings.0).neg();
         let comm = postings.0[0].txn_commodity.clone();
 
@@ -34,7 +39,11 @@ pub(crate) fn parse_txn_postings(is: &mut Stream<'_>) -> ModalResult<Posts> {
             txn_amount: amount,
             is_total_amount: false,
             txn_commodity: comm,
+            cost_basis: None,
+            balance_assertion: None,
+            is_inferred: true,
             comment: p.1.map(String::from),
+            meta,
         };
         postings.0.push(lp);
     }
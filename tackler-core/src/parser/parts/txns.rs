@@ -5,13 +5,14 @@
 use itertools::Itertools;
 use winnow::{ModalResult, Parser, seq};
 
-use crate::model::{Transaction, Txns};
+use crate::model::{BalanceAssertion, Transaction, Txns};
+use crate::parser::parts::balance_assertion::parse_balance_directive;
 use crate::parser::parts::txn_header::parse_txn_header;
 use crate::parser::parts::txn_postings::parse_txn_postings;
 use crate::parser::{Stream, from_error, make_semantic_error};
 use winnow::ascii::{line_ending, space0};
 use winnow::combinator::alt;
-use winnow::combinator::{cut_err, eof, opt, preceded, repeat, repeat_till};
+use winnow::combinator::{cut_err, eof, opt, preceded, repeat, repeat_till, terminated};
 use winnow::error::StrContext;
 
 pub(crate) fn multispace0_line_ending<'s>(is: &mut Stream<'s>) -> ModalResult<&'s str> {
@@ -50,14 +51,61 @@ fn parse_txn(is: &mut Stream<'_>) -> ModalResult<Transaction> {
     }
 }
 
-pub(crate) fn parse_txns(input: &mut Stream<'_>) -> ModalResult<Txns> {
-    let txns: (Vec<Transaction>, &str) = preceded(
+/// Item of journal, in the order of the journal
+pub(crate) enum JournalItem {
+    Txn(Transaction),
+    Balance(BalanceAssertion),
+}
+
+/// Balance directive or txn
+pub(crate) fn parse_journal_item(is: &mut Stream<'_>) -> ModalResult<JournalItem> {
+    alt((
+        terminated(parse_balance_directive, opt(multispace0_line_ending)).map(JournalItem::Balance),
+        parse_txn.map(JournalItem::Txn),
+    ))
+    .parse_next(is)
+}
+
+pub(crate) fn parse_journal(input: &mut Stream<'_>) -> ModalResult<Vec<JournalItem>> {
+    let items: (Vec<JournalItem>, &str) = preceded(
         opt(multispace0_line_ending),
-        repeat_till(1.., parse_txn, eof),
+        repeat_till(1.., parse_journal_item, eof),
     )
     .parse_next(input)?;
 
-    Ok(txns.0)
+    Ok(items.0)
+}
+
+/// Parsed txns and balance directives of journal
+#[derive(Debug, Default)]
+pub(crate) struct Journal {
+    pub(crate) txns: Txns,
+    /// Balance directives are verified later with the whole txn set
+    pub(crate) balance_assertions: Vec<BalanceAssertion>,
+}
+
+impl Journal {
+    /// Append txns and balance directives of other journal
+    pub(crate) fn extend(&mut self, other: Journal) {
+        self.txns.extend(other.txns);
+        self.balance_assertions.extend(other.balance_assertions);
+    }
+}
+
+pub(crate) fn parse_txns(input: &mut Stream<'_>) -> ModalResult<Journal> {
+    let items = parse_journal(input)?;
+
+    let mut journal = Journal {
+        txns: Txns::with_capacity(items.len()),
+        balance_assertions: Vec::new(),
+    };
+    for item in items {
+        match item {
+            JournalItem::Txn(txn) => journal.txns.push(txn),
+            JournalItem::Balance(ba) => journal.balance_assertions.push(ba),
+        }
+    }
+    Ok(journal)
 }
 
 #[cfg(test)]
@@ -156,7 +204,7 @@ mod tests {
                 count + 1
             );
 
-            let txns = res.unwrap(/*:test:*/);
+            let txns = res.unwrap(/*:test:*/).txns;
             assert_eq!(
                 txns.len(),
                 t.1,
@@ -4,25 +4,82 @@
  */
 
 use crate::parser::Stream;
-use crate::parser::parts::txns::parse_txns;
+use crate::parser::diagnostic::{DiagnosticSource, ParseDiagnostic, ParseErrors, with_source};
+use crate::parser::parts::txns::{
+    Journal, JournalItem, multispace0_line_ending, parse_journal, parse_journal_item, parse_txns,
+};
 
 use crate::kernel::Settings;
-use crate::model::Txns;
 use crate::tackler;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use winnow::Parser;
+use winnow::combinator::opt;
 
-pub(crate) fn txns_text(input: &mut &str, settings: &mut Settings) -> Result<Txns, tackler::Error> {
+pub(crate) fn txns_text(
+    input: &mut &str,
+    settings: &mut Settings,
+) -> Result<Journal, tackler::Error> {
+    let recovery_mode = settings.recovery_mode;
     let is = Stream {
         input,
         state: settings,
     };
-    parse_txns.parse(is).map_err(|err| err.to_string().into())
+    let diag = match parse_txns.parse(is) {
+        Ok(journal) => return Ok(journal),
+        Err(err) => ParseDiagnostic::from_parse_error(&err),
+    };
+    if !recovery_mode {
+        return Err(diag.into());
+    }
+
+    let mut diagnostics = txns_text_recover(input, settings);
+    if diagnostics.is_empty() {
+        // e.g. there is nothing else than whitespace
+        diagnostics.push(diag);
+    }
+    Err(ParseErrors { diagnostics }.into())
+}
+
+/// Parse txns in error recovery mode, and return all parse errors
+///
+/// After an error, parsing continues at the next txn header
+/// (timestamp at column 0) after the start of the failed txn.
+fn txns_text_recover(input: &str, settings: &mut Settings) -> Vec<ParseDiagnostic> {
+    let mut is = Stream {
+        input,
+        state: settings,
+    };
+    let mut diagnostics = Vec::new();
+
+    let _ = opt(multispace0_line_ending).parse_next(&mut is);
+    while !is.input.is_empty() {
+        let item_start = input.len() - is.input.len();
+        if let Err(err) = parse_journal_item.parse_next(&mut is) {
+            let offset = input.len() - is.input.len();
+            let ctx = err.into_inner().unwrap_or_default();
+            diagnostics.push(ParseDiagnostic::from_context_error(input, offset, &ctx));
+
+            is.input = next_txn_header(&input[item_start..]);
+        }
+    }
+    diagnostics
 }
 
-pub(crate) fn txns_file(path: &Path, settings: &mut Settings) -> Result<Txns, tackler::Error> {
+/// Skip to the next line which starts with a timestamp
+fn next_txn_header(input: &str) -> &str {
+    let mut pos = 0;
+    while let Some(nl) = input[pos..].find('\n') {
+        pos += nl + 1;
+        if input[pos..].starts_with(|c: char| c.is_ascii_digit()) {
+            return &input[pos..];
+        }
+    }
+    ""
+}
+
+pub(crate) fn txns_file(path: &Path, settings: &mut Settings) -> Result<Journal, tackler::Error> {
     let f = File::open(path);
 
     let mut txn_file = match f {
@@ -37,6 +94,19 @@ pub(crate) fn txns_file(path: &Path, settings: &mut Settings) -> Result<Txns, ta
 
     txn_file.read_to_string(&mut txns_str)?;
 
-    // todo: error log
     txns_text(&mut txns_str.as_str(), settings)
+        .map_err(|err| with_source(err, DiagnosticSource::File(path.to_path_buf())))
+}
+
+pub(crate) fn journal_text(
+    input: &mut &str,
+    settings: &mut Settings,
+) -> Result<Vec<JournalItem>, tackler::Error> {
+    let is = Stream {
+        input,
+        state: settings,
+    };
+    parse_journal
+        .parse(is)
+        .map_err(|err| ParseDiagnostic::from_parse_error(&err).into())
 }
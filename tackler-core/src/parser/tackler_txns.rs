@@ -2,19 +2,29 @@
  * Tackler-NG 2023-2025
  * SPDX-License-Identifier: Apache-2.0
  */
-use itertools::Itertools;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
 //use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::kernel::Settings;
+use crate::kernel::balance::Balance;
+use crate::kernel::lots::Lots;
+use crate::kernel::settings::InputSettings;
+use crate::model::txn_data::AttachmentIds;
 use crate::model::{TxnData, Txns};
+use crate::parser::diagnostic::{
+    DiagnosticSource, ParseDiagnostic, ParseErrors, into_diagnostics, with_source,
+};
+use crate::parser::parts::txns::Journal;
 use crate::parser::tackler_parser;
 use crate::tackler;
 use gix as git;
 use gix::hash as gix_hash;
 use gix::objs::tree::EntryKind;
+use rayon::prelude::*;
 use tackler_api::metadata::items::{GitInputReference, MetadataItem};
 
 pub enum GitInputSelector {
@@ -22,29 +32,169 @@ pub enum GitInputSelector {
     Reference(String),
 }
 
+/// Make txn data and validate lots and balance assertions of it
+///
+/// Lots and balance assertions are validated with the whole, sorted txn set,
+/// e.g. it is an error to close more units than there is held.
+fn to_txn_data(
+    metadata: Option<MetadataItem>,
+    journal: Journal,
+    attachments: Option<AttachmentIds>,
+    settings: &Settings,
+) -> Result<TxnData, tackler::Error> {
+    let txn_data =
+        TxnData::from(metadata, journal.txns, &settings.get_hash())?.with_attachments(attachments);
+
+    let txns = txn_data.txn_refs();
+    if txns
+        .iter()
+        .any(|txn| txn.posts.iter().any(|p| p.cost_basis.is_some()))
+    {
+        Lots::from(&txns, settings.get_lot_method())?;
+    }
+    Balance::verify_assertions(&txns, &journal.balance_assertions)?;
+
+    Ok(txn_data)
+}
+
 pub fn string_to_txns(
     input: &mut &str,
     settings: &mut Settings,
 ) -> Result<TxnData, tackler::Error> {
-    let txns = tackler_parser::txns_text(input, settings)?;
+    let journal = tackler_parser::txns_text(input, settings)?;
 
     // feature: a94d4a60-40dc-4ec0-97a3-eeb69399f01b
     // coverage: "sorted" tested by 200aad57-9275-4d16-bdad-2f1c484bcf17
 
-    TxnData::from(None, txns, &settings.get_hash())
+    to_txn_data(None, journal, None, settings)
+}
+
+/// Read and parse txns from input (single file, filesystem or git)
+pub fn input_to_txns(
+    input: InputSettings,
+    settings: &mut Settings,
+) -> Result<TxnData, tackler::Error> {
+    match input {
+        InputSettings::File(f) => paths_to_txns(&[f.path], settings),
+        InputSettings::Fs(fs) => {
+            let paths = tackler_rs::get_paths_by_ext(fs.dir.as_path(), fs.suffix.as_str())?;
+            paths_to_txns(&paths, settings)
+        }
+        InputSettings::Git(git) => git_to_txns(
+            git.repo.as_path(),
+            git.dir.as_str(),
+            git.ext.as_str(),
+            git.git_ref,
+            settings,
+        ),
+    }
 }
 
 pub fn paths_to_txns(
     paths: &[PathBuf],
     settings: &mut Settings,
 ) -> Result<TxnData, tackler::Error> {
-    let txns: Result<Txns, tackler::Error> = paths
-        .iter()
-        .map(|p| tackler_parser::txns_file(p, settings))
-        .flatten_ok()
+    if settings.get_attachment_keys().is_some() {
+        let msg = "Attachment check (`kernel.audit.attachments`) is supported only with git input";
+        return Err(msg.into());
+    }
+    let results = parse_parallel(
+        paths,
+        settings,
+        || (),
+        |p, _, s| tackler_parser::txns_file(p, s),
+    );
+    let journal = collect_txns(results.into_iter(), settings.recovery_mode);
+
+    to_txn_data(None, journal?, None, settings)
+}
+
+/// Parse inputs in parallel
+///
+/// Each worker thread has its own parser settings and parser state (made by `init`),
+/// and worker settings are merged back into `settings` at the end.
+/// Results are in the order of inputs. Without error recovery mode, inputs after
+/// the first failed input (by input order) are skipped, and their results are left out.
+/// All inputs before the first failed input are always parsed, so the first error
+/// of results is the same as with sequential parsing.
+fn parse_parallel<T, S, I, F>(
+    inputs: &[T],
+    settings: &mut Settings,
+    init: I,
+    parse: F,
+) -> Vec<Result<Journal, tackler::Error>>
+where
+    T: Sync,
+    S: Send,
+    I: Fn() -> S + Sync,
+    F: Fn(&T, &mut S, &mut Settings) -> Result<Journal, tackler::Error> + Sync,
+{
+    let base: &Settings = settings;
+    let stop_on_error = !base.recovery_mode;
+    // index of the first failed input
+    let first_failed = AtomicUsize::new(usize::MAX);
+    let workers: Vec<Mutex<Option<(Settings, S)>>> = (0..rayon::current_num_threads())
+        .map(|_| Mutex::new(None))
         .collect();
 
-    TxnData::from(None, txns?, &settings.get_hash())
+    let results: Vec<Option<Result<Journal, tackler::Error>>> = inputs
+        .par_iter()
+        .enumerate()
+        .map(|(i, input)| {
+            if stop_on_error && i > first_failed.load(Ordering::Relaxed) {
+                return None;
+            }
+            let idx = rayon::current_thread_index().unwrap_or_default() % workers.len();
+            let mut worker = workers[idx].lock().unwrap_or_else(PoisonError::into_inner);
+            let (worker_settings, state) =
+                worker.get_or_insert_with(|| (base.fork_parser(), init()));
+
+            let res = parse(input, state, worker_settings);
+            if stop_on_error && res.is_err() {
+                first_failed.fetch_min(i, Ordering::Relaxed);
+            }
+            Some(res)
+        })
+        .collect();
+
+    for worker in workers {
+        let worker = worker.into_inner().unwrap_or_else(PoisonError::into_inner);
+        if let Some((worker_settings, _)) = worker {
+            settings.merge_parser(worker_settings);
+        }
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// Collect txns and balance directives of all inputs
+///
+/// In error recovery mode, parse errors of all inputs are collected
+/// and reported together. Other errors are reported immediately.
+fn collect_txns(
+    results: impl Iterator<Item = Result<Journal, tackler::Error>>,
+    recovery_mode: bool,
+) -> Result<Journal, tackler::Error> {
+    let mut journal = Journal::default();
+    if !recovery_mode {
+        for res in results {
+            journal.extend(res?);
+        }
+        return Ok(journal);
+    }
+
+    let mut diagnostics = Vec::new();
+    for res in results {
+        match res {
+            Ok(j) => journal.extend(j),
+            Err(err) => diagnostics.extend(into_diagnostics(err)?),
+        }
+    }
+    if diagnostics.is_empty() {
+        Ok(journal)
+    } else {
+        Err(ParseErrors { diagnostics }.into())
+    }
 }
 
 pub fn git_to_txns(
@@ -104,74 +254,381 @@ pub fn git_to_txns(
     // fixme: Optimization
     //      In the future, this could be optimized with custom walker,
     //      which does the filtering in the first place.
-    let txns: Result<Txns, tackler::Error> = tree
+    let entries: Vec<_> = tree
         .traverse()
         .breadthfirst
         .files()?
-        .iter()
-        .map(|entry| {
-            use git::objs::tree::EntryKind::{Blob, Link};
-            match EntryKind::from(entry.mode) {
-                Blob => {
-                    if entry.filepath.starts_with(str::as_bytes(dir))
-                        && entry.filepath.ends_with(str::as_bytes(extension))
-                    {
-                        let obj = repo.find_object(entry.oid)?;
-                        // perf: let ts_par_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap(/*:test:*/);
-
-                        let par_res =
-                            tackler_parser::txns_text(&mut str::from_utf8(&obj.data)?, settings);
-
-                        // perf: let ts_par_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap(/*:test:*/);
-                        // perf: ts_par_total = ts_par_total + (ts_par_end.as_millis() - ts_par_start.as_millis());
-                        match par_res {
-                            Ok(txns) => Ok(txns),
-                            Err(err) => {
-                                let msg = format!(
-                                    "\
-                                    GIT: Error while processing git object\n\
-                                    \x20  commit id: {}\n\
-                                    \x20  object id: {}\n\
-                                    \x20  path: {}\n\
-                                    \x20  msg: {}\
-                                    ",
-                                    object.id, obj.id, entry.filepath, err
-                                );
-                                Err(msg.into())
-                            }
-                        }
-                    } else {
-                        // It's blob but outside of our file path filter
-                        Ok(Vec::default())
-                    }
-                }
-                Link => {
-                    let obj = repo.find_object(entry.oid)?;
-                    let msg = format!(
-                        "\
+        .into_iter()
+        .filter(|entry| match EntryKind::from(entry.mode) {
+            EntryKind::Blob => {
+                entry.filepath.starts_with(str::as_bytes(dir))
+                    && entry.filepath.ends_with(str::as_bytes(extension))
+            }
+            EntryKind::Link => true,
+            // It's not a blob
+            _ => false,
+        })
+        .collect();
+    let sync_repo = repo.clone().into_sync();
+    let results = parse_parallel(
+        &entries,
+        settings,
+        || sync_repo.to_thread_local(),
+        |entry, repo, settings| {
+            let obj = repo.find_object(entry.oid)?;
+            if EntryKind::from(entry.mode) == EntryKind::Link {
+                let msg = format!(
+                    "\
                         GIT: Error while processing git object\n\
                         \x20  commit id: {}\n\
                         \x20  object id: {}\n\
                         \x20  path: {}\n\
                         \x20  msg: {}\
                         ",
-                        object.id,
-                        obj.id,
-                        entry.filepath,
-                        "Links inside repository are not supported"
+                    object.id, obj.id, entry.filepath, "Links inside repository are not supported"
+                );
+                return Err(msg.into());
+            }
+            // perf: let ts_par_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap(/*:test:*/);
+
+            let par_res = tackler_parser::txns_text(&mut str::from_utf8(&obj.data)?, settings);
+
+            // perf: let ts_par_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap(/*:test:*/);
+            // perf: ts_par_total = ts_par_total + (ts_par_end.as_millis() - ts_par_start.as_millis());
+            match par_res {
+                Ok(journal) => Ok(journal),
+                Err(err) if err.is::<ParseDiagnostic>() || err.is::<ParseErrors>() => {
+                    let src = DiagnosticSource::Git {
+                        commit: object.id.to_string(),
+                        object: obj.id.to_string(),
+                        path: entry.filepath.to_string(),
+                    };
+                    Err(with_source(err, src))
+                }
+                Err(err) => {
+                    let msg = format!(
+                        "\
+                            GIT: Error while processing git object\n\
+                            \x20  commit id: {}\n\
+                            \x20  object id: {}\n\
+                            \x20  path: {}\n\
+                            \x20  msg: {}\
+                            ",
+                        object.id, obj.id, entry.filepath, err
                     );
                     Err(msg.into())
                 }
-                // It's not a blob
-                _ => Ok(Vec::default()),
             }
-        })
-        .flatten_ok()
-        .collect::<Result<Txns, tackler::Error>>();
+        },
+    );
+    let journal = collect_txns(results.into_iter(), settings.recovery_mode);
 
     // perf: let ts_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap(/*:test:*/);
     // perf: eprintln!("total time: {}ms, parse time: {}ms, git: {}ms", (ts_end.as_millis() - ts_start.as_millis()), ts_par_total, (ts_end.as_millis() - ts_start.as_millis())-ts_par_total);
 
-    let hash = &settings.get_hash();
-    TxnData::from(Some(MetadataItem::GitInputReference(gitmd)), txns?, hash)
+    let journal = journal?;
+    let attachments = match settings.get_attachment_keys() {
+        Some(keys) => Some(check_attachments(&object.id, &tree, &journal.txns, keys)?),
+        None => None,
+    };
+
+    to_txn_data(
+        Some(MetadataItem::GitInputReference(gitmd)),
+        journal,
+        attachments,
+        settings,
+    )
+}
+
+/// Verify that all attachments of txns exist in the same commit tree
+///
+/// Attachment paths are relative to the root of repository.
+/// Returns blob ids of attachments by txn uuid.
+fn check_attachments(
+    commit_id: &git::ObjectId,
+    tree: &git::Tree<'_>,
+    txns: &Txns,
+    keys: &[String],
+) -> Result<AttachmentIds, tackler::Error> {
+    let mut attachments = AttachmentIds::new();
+    for txn in txns {
+        let Some(meta) = &txn.header.meta else {
+            continue;
+        };
+        let mut blob_ids = Vec::new();
+        for (_, path) in meta.iter().filter(|(k, _)| keys.contains(k)) {
+            let entry = tree
+                .lookup_entry_by_path(path)?
+                .filter(|e| e.mode().is_blob());
+            match entry {
+                Some(e) => blob_ids.push(e.oid().to_string()),
+                None => {
+                    let msg = format!(
+                        "\
+                        GIT: Attachment not found\n\
+                        \x20  commit id: {}\n\
+                        \x20  txn: {}\n\
+                        \x20  path: {}\
+                        ",
+                        commit_id,
+                        txn.header
+                            .uuid
+                            .map_or_else(|| txn.header.timestamp.to_string(), |u| u.to_string()),
+                        path
+                    );
+                    return Err(msg.into());
+                }
+            }
+        }
+        if let (Some(uuid), false) = (txn.header.uuid, blob_ids.is_empty()) {
+            attachments.insert(uuid.to_string(), blob_ids);
+        }
+    }
+    Ok(attachments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Commodity;
+    use gix::objs::tree::Entry;
+    use indoc::indoc;
+    use std::sync::Arc;
+    use tackler_rs::IndocUtils;
+
+    const ATTACHMENT_TXNS: &str = indoc!(
+        "|2024-01-01 'receipt
+         | # uuid: e274c99e-1ebb-45e8-832d-58caf54ed95f
+         | # receipt: receipts/117.pdf
+         | e:office  10
+         | a:cash
+         |
+         |2024-01-02 'no attachments
+         | # uuid: 506a2d55-2375-4d51-af3a-cf5021f04de9
+         | e:office  10
+         | a:cash
+         |"
+    );
+
+    fn attachment_txns(input: &str) -> Txns {
+        let mut settings = Settings::default();
+        tackler_parser::txns_text(&mut input.strip_margin().as_str(), &mut settings)
+            .unwrap(/*:test:*/)
+            .txns
+    }
+
+    /// Bare repository with tree `receipts/117.pdf` and id of that blob
+    fn attachment_repo(name: &str) -> (git::Repository, git::ObjectId, git::ObjectId) {
+        let dir = std::env::temp_dir().join(format!("tackler-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let repo = git::init_bare(&dir).unwrap(/*:test:*/);
+
+        let blob_id = repo.write_blob(b"receipt").unwrap(/*:test:*/).detach();
+        let receipts = git::objs::Tree {
+            entries: vec![Entry {
+                mode: EntryKind::Blob.into(),
+                filename: "117.pdf".into(),
+                oid: blob_id,
+            }],
+        };
+        let receipts_id = repo.write_object(&receipts).unwrap(/*:test:*/).detach();
+        let root = git::objs::Tree {
+            entries: vec![Entry {
+                mode: EntryKind::Tree.into(),
+                filename: "receipts".into(),
+                oid: receipts_id,
+            }],
+        };
+        let tree_id = repo.write_object(&root).unwrap(/*:test:*/).detach();
+        (repo, tree_id, blob_id)
+    }
+
+    #[test]
+    // desc: blob ids of attachments are returned by txn uuid
+    fn test_check_attachments() {
+        let (repo, tree_id, blob_id) = attachment_repo("check-attachments");
+        let tree = repo.find_tree(tree_id).unwrap(/*:test:*/);
+        let txns = attachment_txns(ATTACHMENT_TXNS);
+
+        let keys = ["receipt".to_string()];
+        let attachments = check_attachments(&tree_id, &tree, &txns, &keys).unwrap(/*:test:*/);
+        assert_eq!(
+            attachments,
+            AttachmentIds::from([(
+                "e274c99e-1ebb-45e8-832d-58caf54ed95f".to_string(),
+                vec![blob_id.to_string()]
+            )])
+        );
+
+        // other metadata keys are not attachments
+        let keys = ["invoice".to_string()];
+        let attachments = check_attachments(&tree_id, &tree, &txns, &keys).unwrap(/*:test:*/);
+        assert!(attachments.is_empty());
+
+        let _ = std::fs::remove_dir_all(repo.path());
+    }
+
+    #[test]
+    // desc: missing attachment is an error
+    fn test_check_attachments_missing() {
+        let (repo, tree_id, _) = attachment_repo("check-attachments-missing");
+        let tree = repo.find_tree(tree_id).unwrap(/*:test:*/);
+
+        let keys = ["receipt".to_string()];
+        for path in ["receipts/118.pdf", "receipts"] {
+            let txns = attachment_txns(&ATTACHMENT_TXNS.replace("receipts/117.pdf", path));
+            let res = check_attachments(&tree_id, &tree, &txns, &keys);
+            let msg = res.err().unwrap(/*:test:*/).to_string();
+            assert!(msg.contains("GIT: Attachment not found"));
+            assert!(msg.contains("txn: e274c99e-1ebb-45e8-832d-58caf54ed95f"));
+            assert!(msg.contains(&format!("path: {path}")));
+        }
+
+        let _ = std::fs::remove_dir_all(repo.path());
+    }
+
+    #[test]
+    // desc: lots are validated when txns are loaded, without any report
+    fn test_string_to_txns_lots() {
+        let txns = indoc!(
+            "|2024-01-01 'buy
+             | Assets:Invest  10 ACME {10 €} @ 10 €
+             | Assets:Cash
+             |
+             |2024-03-01 'sell
+             | Assets:Invest  -11 ACME @ 15 €
+             | Assets:Cash
+             |"
+        );
+        let mut settings = Settings::default();
+        let res = string_to_txns(&mut txns.strip_margin().as_str(), &mut settings);
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .contains("closing 11 ACME, but there is only 10 in open lots")
+        );
+
+        let mut settings = Settings::default();
+        let valid = txns.replace("-11 ACME", "-10 ACME");
+        let res = string_to_txns(&mut valid.strip_margin().as_str(), &mut settings);
+        assert_eq!(res.unwrap(/*:test:*/).len(), 2);
+    }
+
+    #[test]
+    // desc: attachment check is rejected with filesystem input
+    fn test_paths_to_txns_with_attachments() {
+        let mut settings = Settings::default();
+        settings.set_attachment_keys(&["receipt"]);
+
+        let res = paths_to_txns(&[], &mut settings);
+        assert!(
+            res.err()
+                .unwrap(/*:test:*/)
+                .to_string()
+                .contains("supported only with git input")
+        );
+    }
+
+    #[test]
+    // desc: parallel parsing keeps the order of inputs and merges worker settings
+    fn test_parse_parallel() {
+        let inputs: Vec<String> = (0..64)
+            .map(|i| {
+                format!(
+                    "2024-01-01 balance a:acc{i} {i}\n\
+                     \n\
+                     2024-01-02 'txn {i}\n\
+                     \x20e:exp{i}  1{i}\n\
+                     \x20a:acc{i}\n"
+                )
+            })
+            .collect();
+        let mut settings = Settings::default();
+
+        let results = parse_parallel(
+            &inputs,
+            &mut settings,
+            || (),
+            |input, _, s| tackler_parser::txns_text(&mut input.as_str(), s),
+        );
+
+        assert_eq!(results.len(), inputs.len());
+        let mut accounts = Vec::new();
+        for (i, res) in results.into_iter().enumerate() {
+            let journal = res.unwrap(/*:test:*/);
+            let desc = journal.txns[0].header.description.as_deref();
+            assert_eq!(desc, Some(format!("txn {i}").as_str()));
+            accounts.extend(
+                journal
+                    .balance_assertions
+                    .iter()
+                    .map(|ba| ba.acctn.atn.account.clone()),
+            );
+        }
+        let accounts_ref: Vec<_> = (0..64).map(|i| format!("a:acc{i}")).collect();
+        assert_eq!(accounts, accounts_ref);
+
+        let comm = Arc::new(Commodity::default());
+        assert!(settings.get_txn_account("e:exp63", comm.clone()).is_ok());
+        assert!(settings.get_txn_account("e", comm).is_ok());
+    }
+
+    #[test]
+    // desc: parser state is made once per worker thread
+    fn test_parse_parallel_worker_state() {
+        let inputs: Vec<String> = (0..256)
+            .map(|i| format!("2024-01-01 'txn {i}\n e  1\n a\n"))
+            .collect();
+        let mut settings = Settings::default();
+        let inits = std::sync::atomic::AtomicUsize::new(0);
+
+        let results = parse_parallel(
+            &inputs,
+            &mut settings,
+            || inits.fetch_add(1, Ordering::Relaxed),
+            |input, _, s| tackler_parser::txns_text(&mut input.as_str(), s),
+        );
+        assert_eq!(results.len(), inputs.len());
+        assert!(inits.load(Ordering::Relaxed) <= rayon::current_num_threads());
+    }
+
+    #[test]
+    // desc: parsing is stopped at the first error without recovery mode,
+    // and the error of the first failed input is reported
+    fn test_parse_parallel_stop_on_error() {
+        let inputs: Vec<usize> = (0..1024).collect();
+        let failed = [100, 101, 500, 1000];
+        let parse = |i: &usize, _: &mut (), _: &mut Settings| {
+            if failed.contains(i) {
+                Err(format!("input {i}").into())
+            } else {
+                Ok(Journal::default())
+            }
+        };
+
+        for _ in 0..10 {
+            let mut settings = Settings::default();
+            let results = parse_parallel(&inputs, &mut settings, || (), parse);
+            assert!(results[..100].iter().all(Result::is_ok));
+            assert_eq!(
+                results[100].as_ref().err().unwrap(/*:test:*/).to_string(),
+                "input 100"
+            );
+
+            let res = collect_txns(results.into_iter(), settings.recovery_mode);
+            assert_eq!(res.err().unwrap(/*:test:*/).to_string(), "input 100");
+        }
+
+        let mut settings = Settings::default();
+        settings.recovery_mode = true;
+        let results = parse_parallel(&inputs, &mut settings, || (), parse);
+        assert_eq!(results.len(), inputs.len());
+        let errors: Vec<_> = results
+            .iter()
+            .filter_map(|r| r.as_ref().err())
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(errors, failed.map(|i| format!("input {i}")));
+    }
 }
@@ -27,6 +27,7 @@ fn txn_tags_to_string(txn: &Transaction) -> String {
 
 mod tackler_txns;
 mod txn_accounts;
+mod txn_balance_assertions;
 mod txn_commodities;
 mod txn_header_code;
 mod txn_header_desc;
@@ -32,7 +32,7 @@ fn txn_data_errors() {
                 | e
                 |"
             ).strip_margin(),
-            "at line 2, column 1",
+            "--> line 2, column 1",
         ),
         (
             // test: 399fb5f8-0f03-4aa7-8f2e-1ae8ab2d6645
@@ -46,7 +46,7 @@ fn txn_data_errors() {
                 | e -1
                 |"
             ).strip_margin(),
-            "at line 4, column 1",
+            "--> line 4, column 1",
         ),
         (
             // test: e23d8e3f-db93-425d-8f3d-690f6d8d84a6
@@ -60,7 +60,7 @@ fn txn_data_errors() {
                 | e -1
                 |"
             ).strip_margin(),
-            "at line 4, column 1",
+            "--> line 4, column 1",
         ),
         (
             // test: efa25d85-96e4-435b-88c1-0728551c2c2a
@@ -75,7 +75,7 @@ fn txn_data_errors() {
                 | e -1
                 |"
             ).strip_margin(),
-            "at line 5, column 1",
+            "--> line 5, column 1",
         ),
         (
             // test: fd2e49c0-bb60-4f5b-8ddd-f3745fcc6015
@@ -95,7 +95,7 @@ fn txn_data_errors() {
                 |  e -1
                 |"
             ).strip_margin(),
-            "at line 4, column 1",
+            "--> line 4, column 1",
         ),
         (
             // test: a9c742e8-9f9a-42dc-8e09-f1ffe5e6e728
@@ -108,7 +108,7 @@ fn txn_data_errors() {
                 |; comment
                 |"
             ).strip_margin(),
-            "at line 5, column 1",
+            "--> line 5, column 1",
         ),
         (
             // test: 73a17e9d-3a91-4c29-bbc7-bf8c7b1b347e
@@ -124,7 +124,7 @@ fn txn_data_errors() {
                 |  e -1
                 |"
             ).strip_margin(),
-            "at line 5, column 1",
+            "--> line 5, column 1",
         ),
         (
             // test: 132f11c4-facd-4fbc-9550-eafd751a2cd8
@@ -136,7 +136,7 @@ fn txn_data_errors() {
                 |  e -1
                 |"
             ).strip_margin(),
-            "at line 1, column 1",
+            "--> line 1, column 1",
         ),
         (
             // test: 0c1a7d18-90eb-4f2b-b8b6-9bc36cd5ff73
@@ -229,3 +229,90 @@ fn test_string_txns_are_sorted() {
     assert_eq!(txn_desc_to_string(txn_1), "txn-1 by str");
     assert_eq!(txn_desc_to_string(txn_3), "txn-3 by str");
 }
+
+#[test]
+// desc: recovery mode reports all parse errors
+fn txn_data_error_recovery() {
+    #[rustfmt::skip]
+    let txns_str = indoc!(
+         "|2017-01-01 'ok
+          | e  1
+          | a
+          |
+          |2017-01-02 'missing indentation
+          |a  1
+          | e
+          |
+          |2017-01-03 'ok
+          | e  1
+          | a
+          |
+          |2017-01-04 'mixed commodities
+          | e  1 USD
+          | a -1 EUR
+          |
+          |2017-13-05 'invalid timestamp
+          | e  1
+          | a
+          |2017-01-06 'missing blank line
+          | e  1
+          | a
+          |"
+    )
+    .strip_margin();
+
+    let mut settings = Settings::default();
+    settings.recovery_mode = true;
+    let res = parser::string_to_txns(&mut txns_str.as_ref(), &mut settings);
+
+    let err = res.err().unwrap(/*:test:*/);
+    let errs = err.downcast_ref::<parser::ParseErrors>().unwrap(/*:test:*/);
+    // semantic error of txn is reported at the end of txn
+    let positions: Vec<(usize, usize)> = errs
+        .diagnostics
+        .iter()
+        .map(|d| (d.line, d.column))
+        .collect();
+    assert_eq!(positions, vec![(6, 1), (17, 1), (17, 11)]);
+    assert!(
+        errs.diagnostics[1]
+            .message
+            .as_ref()
+            .is_some_and(|m| m.contains("Different commodities without"))
+    );
+}
+
+#[test]
+// desc: recovery mode with valid txns and with error at the end of input
+fn txn_data_error_recovery_edges() {
+    #[rustfmt::skip]
+    let txns_str = indoc!(
+         "|2017-01-01 'ok
+          | e  1
+          | a
+          |"
+    )
+    .strip_margin();
+
+    let mut settings = Settings::default();
+    settings.recovery_mode = true;
+    let res = parser::string_to_txns(&mut txns_str.as_ref(), &mut settings);
+    assert_eq!(res.unwrap(/*:test:*/).len(), 1);
+
+    #[rustfmt::skip]
+    let txns_str = indoc!(
+         "|2017-01-01 'ok
+          | e  1
+          | a
+          |
+          |; comment
+          |"
+    )
+    .strip_margin();
+    let res = parser::string_to_txns(&mut txns_str.as_ref(), &mut settings);
+    let err = res.err().unwrap(/*:test:*/);
+    let errs = err.downcast_ref::<parser::ParseErrors>().unwrap(/*:test:*/);
+    assert_eq!(errs.diagnostics.len(), 1);
+    assert_eq!(errs.diagnostics[0].line, 5);
+    assert!(err.to_string().ends_with("Found 1 parse errors"));
+}
@@ -0,0 +1,186 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+#![cfg_attr(rustfmt, rustfmt_skip)]
+use indoc::indoc;
+use crate::kernel::Settings;
+use crate::parser;
+use tackler_rs::IndocUtils;
+
+//
+// "Balance assertions"
+//
+    #[test]
+    //desc: "accept valid posting assertions and balance directives"
+    fn ok_balance_assertions() {
+      let txns_str =
+  indoc!("|
+          |2024-01-01
+          | Assets:Bank   100.00 € == 100 €
+          | Equity
+          |
+          |2024-01-15
+          | Expenses:Food   10 €
+          | Assets:Bank  -10 € == 90 € ; comment
+          |
+          |2024-01-31 balance Assets:Bank 90.00 €
+          |
+          |2024-02-01 balance Assets:Bank 90 €
+          |2024-02-01 balance Expenses:Food 10 €
+          |
+          |2024-02-02
+          | Expenses:Food   5 €
+          | Assets:Bank
+          |
+          |2024-02-03 balance Assets:Bank 85 €
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_ok(), "{}", res.err().unwrap(/*:test:*/));
+        assert_eq!(res.unwrap(/*:test:*/).len(), 3);
+      }
+
+    #[test]
+    //desc: "balance directive is verified with all txns up to and including its timestamp"
+    fn ok_balance_directive_in_order() {
+      let txns_str =
+  indoc!("|
+          |2024-01-31 balance Assets:Bank 100
+          |
+          |2024-03-01
+          | Expenses   10
+          | Assets:Bank
+          |
+          |2024-01-31
+          | Assets:Bank   100
+          | Equity
+          |
+          |2024-01-01 balance Assets:Bank 0
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_ok(), "{}", res.err().unwrap(/*:test:*/));
+      }
+
+    #[test]
+    //desc: "date only balance directive covers the whole day"
+    fn ok_balance_directive_date_end_of_day() {
+      let txns_str =
+  indoc!("|
+          |2024-01-31 balance Assets:Bank 100
+          |
+          |2024-01-31T10:00:00Z
+          | Assets:Bank   100
+          | Equity
+          |
+          |2024-02-01
+          | Expenses   10
+          | Assets:Bank
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_ok(), "{}", res.err().unwrap(/*:test:*/));
+      }
+
+    #[test]
+    //desc: "balance directive with time covers txns only up to its timestamp"
+    fn err_balance_directive_ts() {
+      let txns_str =
+  indoc!("|
+          |2024-01-31T09:00:00Z balance Assets:Bank 100
+          |
+          |2024-01-31T10:00:00Z
+          | Assets:Bank   100
+          | Equity
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_err());
+        let msg = res.err().unwrap(/*:test:*/).to_string();
+        assert!(msg.ends_with("at balance directive: 2024-01-31T09:00:00+00:00"), "{msg}");
+      }
+
+    #[test]
+    //desc: "reject posting assertion with wrong balance"
+    fn err_posting_assertion() {
+      let txns_str =
+  indoc!("|
+          |2024-01-01
+          | Assets:Bank   100.00 €
+          | Equity
+          |
+          |2024-01-15 (#001)
+          | # uuid: 4e0ffd10-1fcb-4d1a-8e3c-54ff1f4bc5c5
+          | Expenses:Food   10 €
+          | Assets:Bank  -10 € == 80 €
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_err());
+        let msg = res.err().unwrap(/*:test:*/).to_string();
+        assert_eq!(msg, "Balance assertion error: account 'Assets:Bank' balance is 90.00 €, \
+                         but asserted balance is 80 € (difference: 10.00 €) \
+                         at txn: 2024-01-15T00:00:00+00:00, txn uuid: 4e0ffd10-1fcb-4d1a-8e3c-54ff1f4bc5c5");
+      }
+
+    #[test]
+    //desc: "reject balance directive with duplicated txn"
+    fn err_balance_directive_duplicate_txn() {
+      let txns_str =
+  indoc!("|
+          |2024-01-01
+          | Assets:Bank   100
+          | Equity
+          |
+          |2024-01-15
+          | Expenses:Food   10
+          | Assets:Bank
+          |
+          |2024-01-15
+          | Expenses:Food   10
+          | Assets:Bank
+          |
+          |2024-01-31 balance Assets:Bank 90
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_err());
+        let msg = res.err().unwrap(/*:test:*/).to_string();
+        assert_eq!(msg, "Balance assertion error: account 'Assets:Bank' balance is 80, \
+                         but asserted balance is 90 (difference: -10) \
+                         at balance directive: 2024-01-31");
+      }
+
+    #[test]
+    //desc: "balance directive is by account and commodity"
+    fn err_balance_directive_commodity() {
+      let txns_str =
+  indoc!("|
+          |2024-01-01
+          | Assets:Bank   100 €
+          | Equity
+          |
+          |2024-01-31 balance Assets:Bank 100 USD
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_err());
+        assert!(res.err().unwrap(/*:test:*/).to_string().contains("balance is 0 USD"));
+      }
+
+    #[test]
+    //desc: "reject posting assertion with different commodity"
+    fn err_posting_assertion_commodity() {
+      let txns_str =
+  indoc!("|
+          |2024-01-01
+          | Assets:Bank   100 € == 100 USD
+          | Equity
+          |").strip_margin();
+
+        let res = parser::string_to_txns(&mut txns_str.as_str(), &mut Settings::default());
+        assert!(res.is_err());
+        assert!(res.err().unwrap(/*:test:*/).to_string().contains(
+            "Balance assertion commodity [USD] is different than posting commodity [€]"));
+      }
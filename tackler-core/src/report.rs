@@ -2,24 +2,33 @@
  * Tackler-NG 2023-2025
  * SPDX-License-Identifier: Apache-2.0
  */
-use crate::config::ReportType;
-use crate::kernel::price_lookup::PriceLookupCtx;
+use crate::config::{ReportFormat, ReportType};
+use crate::kernel::amount_format::AmountFormat;
+use crate::kernel::price_lookup::{PriceLookup, PriceLookupCtx};
 use crate::kernel::report_item_selector::ReportItemSelector;
-use crate::kernel::{BalanceGroupSettings, RegisterSettings, Settings};
-use crate::model::TxnSet;
+use crate::kernel::statement::StatementType;
+use crate::kernel::{BalanceGroupSettings, RegisterSettings, Settings, StatementSettings};
+use crate::model::{Commodity, TxnRefs, TxnSet};
 use crate::tackler;
 pub use balance_group_reporter::BalanceGroupReporter;
 pub use balance_reporter::BalanceReporter;
+pub use pnl_reporter::PnlReporter;
 pub use register_reporter::RegisterReporter;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+pub use statement_reporter::StatementReporter;
 use std::io;
 use std::io::Write;
 use std::path::PathBuf;
-use tackler_api::metadata::items::{AccountSelectorChecksum, ReportTimezone, Text};
+use std::sync::Arc;
+use tackler_api::metadata::items::{AccountSelectorChecksum, Json, ReportTimezone, Text};
 use tackler_rs::create_output_file;
 
 mod balance_group_reporter;
 mod balance_reporter;
+mod pnl_reporter;
 mod register_reporter;
+mod statement_reporter;
 
 pub trait Report {
     fn write_txt_report<W: io::Write + ?Sized>(
@@ -28,13 +37,58 @@ pub trait Report {
         w: &mut W,
         txns: &TxnSet<'_>,
     ) -> Result<(), tackler::Error>;
+
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        w: &mut W,
+        txns: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error>;
+
+    /// Write report as delimiter separated table (CSV, TSV)
+    ///
+    /// There is one header row, and then one row per report item.
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        w: &mut W,
+        txns: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error>;
 }
 
-fn write_report_timezone<W: io::Write + ?Sized>(
-    cfg: &Settings,
-    writer: &mut W,
-) -> Result<(), tackler::Error> {
-    let rtz = ReportTimezone {
+/// Report which converts amounts with prices
+trait PriceConversion {
+    fn price_lookup(&self) -> &PriceLookup;
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>>;
+
+    /// Txns which are used to select prices, by default the txns of the report
+    fn price_txns<'a, 't>(&self, txn_data: &'a TxnSet<'t>) -> &'a TxnRefs<'t> {
+        &txn_data.txns
+    }
+
+    /// Run `f` with the price lookup context of the report
+    ///
+    /// Price db of the context has also implicit prices of all txns, if those are activated.
+    fn with_price_lookup_ctx<T>(
+        &self,
+        cfg: &Settings,
+        txn_data: &TxnSet<'_>,
+        f: impl FnOnce(&PriceLookupCtx<'_>) -> Result<T, tackler::Error>,
+    ) -> Result<T, tackler::Error> {
+        let price_db = cfg.price.get_price_db(&txn_data.all_txns);
+        let price_lookup_ctx = self.price_lookup().make_ctx(
+            self.price_txns(txn_data),
+            self.report_commodity(),
+            &price_db,
+        )?;
+        f(&price_lookup_ctx)
+    }
+}
+
+fn get_report_timezone(cfg: &Settings) -> Result<ReportTimezone, tackler::Error> {
+    Ok(ReportTimezone {
         timezone: match cfg.report.report_tz.iana_name() {
             Some(tz) => tz.to_string(),
             None => {
@@ -42,7 +96,14 @@ fn write_report_timezone<W: io::Write + ?Sized>(
                 return Err(msg.into());
             }
         },
-    };
+    })
+}
+
+fn write_report_timezone<W: io::Write + ?Sized>(
+    cfg: &Settings,
+    writer: &mut W,
+) -> Result<(), tackler::Error> {
+    let rtz = get_report_timezone(cfg)?;
     for v in rtz.text(cfg.report.report_tz.clone()) {
         writeln!(writer, "{}", &v)?;
     }
@@ -54,7 +115,10 @@ fn write_price_metadata<W: Write + ?Sized>(
     writer: &mut W,
     p_ctx: &PriceLookupCtx<'_>,
 ) -> Result<(), tackler::Error> {
-    let pr_metadata = p_ctx.metadata().text(cfg.report.report_tz.clone());
+    let amount_fmt = cfg.get_amount_format();
+    let pr_metadata = p_ctx
+        .metadata(|r| amount_fmt.txt_rate(r))
+        .text(cfg.report.report_tz.clone());
 
     if !pr_metadata.is_empty() {
         writeln!(writer)?;
@@ -82,8 +146,170 @@ fn write_acc_sel_checksum<W: io::Write + ?Sized, R: ReportItemSelector + ?Sized>
     Ok(())
 }
 
+/// Format amount with the same precision as text reports
+fn fmt_amount(amount: &Decimal, comm: &str, amount_fmt: &AmountFormat) -> String {
+    amount_fmt.amount(amount, comm)
+}
+
+/// Format amount as JSON value, with the same precision as text reports
+fn json_amount(amount: &Decimal, comm: &str, amount_fmt: &AmountFormat) -> Value {
+    Value::String(fmt_amount(amount, comm, amount_fmt))
+}
+
+/// Collect all metadata items of the report as JSON
+///
+/// This is Txn Set metadata (txn set checksum, git input reference, txn filters),
+/// and report specific metadata (account selector checksum, report timezone, prices).
+fn json_metadata<R: ReportItemSelector + ?Sized>(
+    cfg: &Settings,
+    txns: &TxnSet<'_>,
+    acc_sel: &R,
+    p_ctx: &PriceLookupCtx<'_>,
+    with_report_tz: bool,
+) -> Result<Value, tackler::Error> {
+    let tz = cfg.report.report_tz.clone();
+
+    let mut items = match txns.metadata() {
+        Some(md) => md.json(tz.clone())?,
+        None => Vec::new(),
+    };
+
+    if let Some(hash) = cfg.get_hash() {
+        let asc = AccountSelectorChecksum {
+            hash: acc_sel.checksum(hash)?,
+        };
+        items.push(asc.json(tz.clone())?);
+    }
+    if with_report_tz {
+        items.push(get_report_timezone(cfg)?.json(tz.clone())?);
+    }
+    let amount_fmt = cfg.get_amount_format();
+    let price_records = p_ctx.metadata(|r| amount_fmt.rate(r));
+    if !price_records.rates.is_empty() {
+        items.push(price_records.json(tz)?);
+    }
+
+    Ok(json!({ "items": items }))
+}
+
+fn write_json<W: io::Write + ?Sized>(writer: &mut W, report: &Value) -> Result<(), tackler::Error> {
+    serde_json::to_writer_pretty(&mut *writer, report)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+const CSV_DELIMITER: char = ',';
+const TSV_DELIMITER: char = '\t';
+
+/// Write one delimiter separated row
+///
+/// With CSV, fields are quoted (RFC-4180) if they contain delimiter, quote or line breaks.
+/// With TSV, tabs and line breaks are replaced with space, as TSV doesn't have quoting.
+fn write_table_row<W: io::Write + ?Sized>(
+    writer: &mut W,
+    delimiter: char,
+    fields: &[&str],
+) -> Result<(), tackler::Error> {
+    let row = fields
+        .iter()
+        .map(|f| {
+            if delimiter == TSV_DELIMITER {
+                f.replace(['\t', '\n', '\r'], " ")
+            } else if f.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                (*f).to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(delimiter.encode_utf8(&mut [0; 4]));
+    writeln!(writer, "{}", row)?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_report<W: io::Write + ?Sized, R: Report>(
+    console_writer: &mut Option<Box<W>>,
+    output_dir: Option<&PathBuf>,
+    output_prefix: &Option<String>,
+    reporter: &R,
+    (report_name, report_ext): (&str, &str),
+    formats: &[ReportFormat],
+    metadata: &str,
+    txn_set: &TxnSet<'_>,
+    settings: &Settings,
+    prog_writer: &mut Option<Box<W>>,
+) -> Result<(), tackler::Error> {
+    let report_separator_len = 82;
+
+    for fmt in formats {
+        match (output_prefix, output_dir) {
+            (Some(output_name), Some(output_dir)) => {
+                let (mut out_writer, path) =
+                    create_output_file(output_dir, output_name, report_ext, fmt.ext())?;
+
+                match fmt {
+                    ReportFormat::Txt => {
+                        write!(out_writer, "{}", metadata)?;
+                        reporter.write_txt_report(settings, &mut out_writer, txn_set)?;
+                    }
+                    ReportFormat::Json => {
+                        reporter.write_json_report(settings, &mut out_writer, txn_set)?;
+                    }
+                    ReportFormat::Csv => {
+                        reporter.write_table_report(
+                            settings,
+                            &mut out_writer,
+                            txn_set,
+                            CSV_DELIMITER,
+                        )?;
+                    }
+                    ReportFormat::Tsv => {
+                        reporter.write_table_report(
+                            settings,
+                            &mut out_writer,
+                            txn_set,
+                            TSV_DELIMITER,
+                        )?;
+                    }
+                }
+
+                if let Some(p) = prog_writer.as_mut() {
+                    writeln!(p, "{:>21} : {}", report_name, path)?;
+                }
+            }
+            _ => {
+                let mut cw = console_writer
+                    .as_mut()
+                    .expect("IE: logic error with output");
+
+                // Only text reports have separators, so that JSON, CSV and TSV
+                // output on console could be used as it is
+                let with_separators = *fmt == ReportFormat::Txt;
+                if with_separators {
+                    writeln!(cw, "{}", "*".repeat(report_separator_len))?;
+                }
+                match fmt {
+                    ReportFormat::Txt => reporter.write_txt_report(settings, &mut cw, txn_set)?,
+                    ReportFormat::Json => reporter.write_json_report(settings, &mut cw, txn_set)?,
+                    ReportFormat::Csv => {
+                        reporter.write_table_report(settings, &mut cw, txn_set, CSV_DELIMITER)?
+                    }
+                    ReportFormat::Tsv => {
+                        reporter.write_table_report(settings, &mut cw, txn_set, TSV_DELIMITER)?
+                    }
+                }
+                if with_separators {
+                    writeln!(cw, "{}", "#".repeat(report_separator_len))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
-pub fn write_txt_reports<W: io::Write + ?Sized>(
+pub fn write_reports<W: io::Write + ?Sized>(
     console_writer: &mut Option<Box<W>>,
     output_dir: Option<&PathBuf>,
     output_prefix: &Option<String>,
@@ -98,101 +324,146 @@ pub fn write_txt_reports<W: io::Write + ?Sized>(
         return Err("IE: Logic error, console output is not supported with file ouput".into());
     }
 
-    let report_separator_len = 82;
-
     let metadata = &txn_set
         .metadata()
         .map(|md| format!("{}\n", md.text(settings.report.report_tz.clone())))
         .unwrap_or_default();
 
+    let is_txt_on_console = reports
+        .iter()
+        .any(|r| settings.get_report_formats(r).contains(&ReportFormat::Txt));
     if let Some(cw) = console_writer.as_mut() {
-        write!(cw, "{}", metadata)?;
+        if is_txt_on_console {
+            write!(cw, "{}", metadata)?;
+        }
     }
 
     for r in reports {
+        let formats = settings.get_report_formats(r);
         match r {
             ReportType::Balance => {
                 let bal_reporter = BalanceReporter::try_from(settings)?;
-
-                match (output_prefix, output_dir) {
-                    (Some(output_name), Some(output_dir)) => {
-                        let (mut out_writer, path) =
-                            create_output_file(output_dir, output_name, "bal", "txt")?;
-
-                        write!(out_writer, "{}", metadata)?;
-
-                        bal_reporter.write_txt_report(settings, &mut out_writer, txn_set)?;
-
-                        if let Some(p) = prog_writer.as_mut() {
-                            writeln!(p, "{:>21} : {}", "Balance Report", path)?;
-                        }
-                    }
-                    _ => {
-                        let mut cw = console_writer
-                            .as_mut()
-                            .expect("IE: logic error with output");
-
-                        writeln!(cw, "{}", "*".repeat(report_separator_len))?;
-                        bal_reporter.write_txt_report(settings, &mut cw, txn_set)?;
-                        writeln!(cw, "{}", "#".repeat(report_separator_len))?;
-                    }
-                }
+                write_report(
+                    console_writer,
+                    output_dir,
+                    output_prefix,
+                    &bal_reporter,
+                    ("Balance Report", "bal"),
+                    &formats,
+                    metadata,
+                    txn_set,
+                    settings,
+                    prog_writer,
+                )?;
             }
             ReportType::BalanceGroup => {
                 let bal_group_reporter = BalanceGroupReporter {
                     report_settings: BalanceGroupSettings::try_from(settings)?,
                 };
-                match (output_prefix, output_dir) {
-                    (Some(output_name), Some(output_dir)) => {
-                        let (mut out_writer, path) =
-                            create_output_file(output_dir, output_name, "balgrp", "txt")?;
-
-                        write!(out_writer, "{}", metadata)?;
-
-                        bal_group_reporter.write_txt_report(settings, &mut out_writer, txn_set)?;
-
-                        if let Some(p) = prog_writer.as_mut() {
-                            writeln!(p, "{:>21} : {}", "Balance Group Report", path)?;
-                        }
-                    }
-                    _ => {
-                        let mut cw = console_writer
-                            .as_mut()
-                            .expect("IE: logic error with output");
-
-                        writeln!(cw, "{}", "*".repeat(report_separator_len))?;
-                        bal_group_reporter.write_txt_report(settings, &mut cw, txn_set)?;
-                        writeln!(cw, "{}", "#".repeat(report_separator_len))?;
-                    }
-                }
+                write_report(
+                    console_writer,
+                    output_dir,
+                    output_prefix,
+                    &bal_group_reporter,
+                    ("Balance Group Report", "balgrp"),
+                    &formats,
+                    metadata,
+                    txn_set,
+                    settings,
+                    prog_writer,
+                )?;
             }
             ReportType::Register => {
                 let reg_reporter = RegisterReporter {
                     report_settings: RegisterSettings::try_from(settings)?,
                 };
-
-                match (output_prefix, output_dir) {
-                    (Some(output_name), Some(output_dir)) => {
-                        let (mut out_writer, path) =
-                            create_output_file(output_dir, output_name, "reg", "txt")?;
-                        write!(out_writer, "{}", metadata)?;
-                        reg_reporter.write_txt_report(settings, &mut out_writer, txn_set)?;
-                        if let Some(p) = prog_writer.as_mut() {
-                            writeln!(p, "{:>21} : {}", "Register Report", path)?;
-                        }
-                    }
-                    _ => {
-                        let mut cw = console_writer
-                            .as_mut()
-                            .expect("IE: logic error with output");
-
-                        writeln!(cw, "{}", "*".repeat(report_separator_len))?;
-                        reg_reporter.write_txt_report(settings, &mut cw, txn_set)?;
-                        writeln!(cw, "{}", "#".repeat(report_separator_len))?;
+                write_report(
+                    console_writer,
+                    output_dir,
+                    output_prefix,
+                    &reg_reporter,
+                    ("Register Report", "reg"),
+                    &formats,
+                    metadata,
+                    txn_set,
+                    settings,
+                    prog_writer,
+                )?;
+            }
+            ReportType::Pnl => {
+                let pnl_reporter = PnlReporter::try_from(settings)?;
+                write_report(
+                    console_writer,
+                    output_dir,
+                    output_prefix,
+                    &pnl_reporter,
+                    ("PnL Report", "pnl"),
+                    &formats,
+                    metadata,
+                    txn_set,
+                    settings,
+                    prog_writer,
+                )?;
+            }
+            ReportType::IncomeStatement | ReportType::BalanceSheet => {
+                let (stmt_type, report_name) = match r {
+                    ReportType::IncomeStatement => {
+                        (StatementType::IncomeStatement, ("Income Statement", "is"))
                     }
-                }
+                    _ => (StatementType::BalanceSheet, ("Balance Sheet", "bs")),
+                };
+                let stmt_reporter = StatementReporter {
+                    report_settings: StatementSettings::from(settings, stmt_type)?,
+                };
+                write_report(
+                    console_writer,
+                    output_dir,
+                    output_prefix,
+                    &stmt_reporter,
+                    report_name,
+                    &formats,
+                    metadata,
+                    txn_set,
+                    settings,
+                    prog_writer,
+                )?;
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_row(delimiter: char, fields: &[&str]) -> String {
+        let mut buf = Vec::new();
+        write_table_row(&mut buf, delimiter, fields).unwrap(/*:test:*/);
+        String::from_utf8(buf).unwrap(/*:test:*/)
+    }
+
+    #[test]
+    fn csv_row() {
+        assert_eq!(table_row(CSV_DELIMITER, &["a", "", "1.00"]), "a,,1.00\n");
+    }
+
+    #[test]
+    fn csv_row_quoting() {
+        assert_eq!(
+            table_row(CSV_DELIMITER, &["a,b", "say \"hi\"", "line\nbreak", "c"]),
+            "\"a,b\",\"say \"\"hi\"\"\",\"line\nbreak\",c\n"
+        );
+    }
+
+    #[test]
+    fn tsv_row() {
+        assert_eq!(
+            table_row(
+                TSV_DELIMITER,
+                &["a,b", "tab\there", "say \"hi\"", "line\nbreak"]
+            ),
+            "a,b\ttab here\tsay \"hi\"\tline break\n"
+        );
+    }
+}
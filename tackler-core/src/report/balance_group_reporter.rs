@@ -4,17 +4,21 @@
  */
 
 use crate::kernel::accumulator::TxnGroupByOp;
+use crate::kernel::balance::Balance;
+use crate::kernel::price_lookup::PriceLookup;
+use crate::kernel::price_lookup::PriceLookupCtx;
 use crate::kernel::report_item_selector::BalanceSelector;
 use crate::kernel::{BalanceGroupSettings, accumulator};
 use crate::kernel::{BalanceSettings, Settings};
-use crate::model::{Transaction, TxnSet};
-use crate::report::{BalanceReporter, write_price_metadata};
-use crate::report::{Report, write_acc_sel_checksum, write_report_timezone};
+use crate::model::{Commodity, TxnSet};
+use crate::report::{
+    BalanceReporter, json_metadata, write_json, write_price_metadata, write_table_row,
+};
+use crate::report::{PriceConversion, Report, write_acc_sel_checksum, write_report_timezone};
 use crate::tackler;
-use jiff::tz::TimeZone;
+use serde_json::json;
 use std::io;
-use tackler_api::txn_ts;
-use tackler_api::txn_ts::GroupBy;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct BalanceGroupReporter {
@@ -27,24 +31,57 @@ impl BalanceGroupReporter {
     }
 
     fn get_group_by_op(&self) -> TxnGroupByOp<'_> {
-        let tz: TimeZone = self.report_settings.report_tz.clone();
-        match self.report_settings.group_by {
-            GroupBy::IsoWeekDate => Box::new(move |txn: &Transaction| {
-                txn_ts::as_tz_iso_week_date(&txn.header.timestamp, tz.clone())
-            }),
-            GroupBy::IsoWeek => Box::new(move |txn: &Transaction| {
-                txn_ts::as_tz_iso_week(&txn.header.timestamp, tz.clone())
-            }),
-            GroupBy::Date => Box::new(move |txn: &Transaction| {
-                txn_ts::as_tz_date(&txn.header.timestamp, tz.clone())
-            }),
-            GroupBy::Month => Box::new(move |txn: &Transaction| {
-                txn_ts::as_tz_month(&txn.header.timestamp, tz.clone())
-            }),
-            GroupBy::Year => Box::new(move |txn: &Transaction| {
-                txn_ts::as_tz_year(&txn.header.timestamp, tz.clone())
-            }),
+        accumulator::group_by_op(
+            self.report_settings.group_by,
+            self.report_settings.report_tz.clone(),
+        )
+    }
+
+    fn get_balance_settings(&self) -> BalanceSettings {
+        BalanceSettings {
+            title: String::default(),
+            ras: vec![],
+            amount_fmt: self.report_settings.amount_fmt.clone(),
+            report_commodity: self.report_settings.report_commodity.clone(),
+            price_lookup: self.report_settings.price_lookup.clone(),
+            natural_signs: self.report_settings.natural_signs,
+        }
+    }
+
+    fn balance_groups(
+        &self,
+        cfg: &Settings,
+        txn_data: &TxnSet<'_>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        bal_acc_sel: &dyn BalanceSelector,
+    ) -> Result<Vec<Balance>, tackler::Error> {
+        let bal_groups = accumulator::balance_groups(
+            &txn_data.txns,
+            self.get_group_by_op(),
+            price_lookup_ctx,
+            bal_acc_sel,
+            cfg,
+        );
+        for bal in &bal_groups {
+            bal.verify_normal_balances(cfg)?;
         }
+        Ok(match self.report_settings.natural_signs {
+            true => bal_groups
+                .into_iter()
+                .map(|bal| bal.natural_signs(cfg))
+                .collect(),
+            false => bal_groups,
+        })
+    }
+}
+
+impl PriceConversion for BalanceGroupReporter {
+    fn price_lookup(&self) -> &PriceLookup {
+        &self.report_settings.price_lookup
+    }
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>> {
+        self.report_settings.report_commodity.clone()
     }
 }
 
@@ -57,44 +94,151 @@ impl Report for BalanceGroupReporter {
     ) -> Result<(), tackler::Error> {
         let bal_acc_sel = self.get_acc_selector()?;
 
-        let price_lookup_ctx = self.report_settings.price_lookup.make_ctx(
-            &txn_data.txns,
-            self.report_settings.report_commodity.clone(),
-            &cfg.price.price_db,
-        );
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let bal_groups =
+                self.balance_groups(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
 
-        let group_by_op = self.get_group_by_op();
-        let bal_groups = accumulator::balance_groups(
-            &txn_data.txns,
-            group_by_op,
-            &price_lookup_ctx,
-            bal_acc_sel.as_ref(),
-            cfg,
-        );
+            write_acc_sel_checksum(cfg, writer, bal_acc_sel.as_ref())?;
 
-        write_acc_sel_checksum(cfg, writer, bal_acc_sel.as_ref())?;
+            write_report_timezone(cfg, writer)?;
 
-        write_report_timezone(cfg, writer)?;
+            write_price_metadata(cfg, writer, price_lookup_ctx)?;
 
-        write_price_metadata(cfg, writer, &price_lookup_ctx)?;
+            writeln!(writer)?;
+            writeln!(writer)?;
 
-        writeln!(writer)?;
-        writeln!(writer)?;
+            let title = &self.report_settings.title;
+            writeln!(writer, "{}", title)?;
+            writeln!(writer, "{}", "-".repeat(title.chars().count()))?;
 
-        let title = &self.report_settings.title;
-        writeln!(writer, "{}", title)?;
-        writeln!(writer, "{}", "-".repeat(title.chars().count()))?;
+            let bal_settings = self.get_balance_settings();
+            for bal in &bal_groups {
+                BalanceReporter::txt_report(writer, bal, &bal_settings)?
+            }
+            Ok(())
+        })
+    }
 
-        let bal_settings = BalanceSettings {
-            title: String::default(),
-            ras: vec![],
-            scale: self.report_settings.scale.clone(),
-            report_commodity: self.report_settings.report_commodity.clone(),
-            price_lookup: self.report_settings.price_lookup.clone(),
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let bal_acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let bal_groups =
+                self.balance_groups(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
+
+            let metadata =
+                json_metadata(cfg, txn_data, bal_acc_sel.as_ref(), price_lookup_ctx, true)?;
+
+            let bal_settings = self.get_balance_settings();
+            let groups = bal_groups
+                .iter()
+                .map(|bal| BalanceReporter::json_report(bal, &bal_settings))
+                .collect::<Vec<_>>();
+
+            let report = json!({
+                "metadata": metadata,
+                "title": self.report_settings.title,
+                "groups": groups,
+            });
+
+            write_json(writer, &report)
+        })
+    }
+
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error> {
+        let bal_acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let bal_groups =
+                self.balance_groups(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
+
+            let mut header = vec!["group"];
+            header.extend(BalanceReporter::TABLE_HEADER);
+            write_table_row(writer, delimiter, &header)?;
+
+            let bal_settings = self.get_balance_settings();
+            for bal in &bal_groups {
+                for row in BalanceReporter::table_rows(bal, &bal_settings) {
+                    let mut fields = vec![bal.title.as_str()];
+                    fields.extend(row.each_ref().map(String::as_str));
+                    write_table_row(writer, delimiter, &fields)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use serde_json::Value;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn balance_group_json_report() {
+        let txns = indoc!(
+            "|2024-01-01 'jan
+             | e:food  10 EUR
+             | a:cash
+             |
+             |2024-02-01 'feb
+             | e:food  20 EUR
+             | a:cash
+             |"
+        );
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let reporter = BalanceGroupReporter {
+            report_settings: BalanceGroupSettings::try_from(&settings).unwrap(/*:test:*/),
         };
-        for bal in &bal_groups {
-            BalanceReporter::txt_report(writer, bal, &bal_settings)?
-        }
-        Ok(())
+        let mut buf = Vec::new();
+        reporter
+            .write_json_report(&settings, &mut buf, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let report: Value = serde_json::from_slice(&buf).unwrap(/*:test:*/);
+        let group = |title: &str, sum: &str, neg_sum: &str| {
+            json!({
+                "title": title,
+                "balances": [
+                    { "account": "a", "accountSum": "0.00", "accountTreeSum": neg_sum, "commodity": "EUR" },
+                    { "account": "a:cash", "accountSum": neg_sum, "accountTreeSum": neg_sum, "commodity": "EUR" },
+                    { "account": "e", "accountSum": "0.00", "accountTreeSum": sum, "commodity": "EUR" },
+                    { "account": "e:food", "accountSum": sum, "accountTreeSum": sum, "commodity": "EUR" },
+                ],
+                "deltas": [
+                    { "delta": "0.00", "commodity": "EUR" },
+                ],
+            })
+        };
+        let reference = json!({
+            "metadata": {
+                "items": [
+                    { "ReportTimezone": { "timezone": "UTC" } },
+                ],
+            },
+            "title": "",
+            "groups": [
+                group("2024-01", "10.00", "-10.00"),
+                group("2024-02", "20.00", "-20.00"),
+            ],
+        });
+        assert_eq!(report, reference);
     }
 }
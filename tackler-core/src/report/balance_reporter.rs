@@ -4,18 +4,25 @@
  */
 
 use crate::kernel::balance::{BTNs, Balance, Deltas};
+use crate::kernel::price_lookup::PriceLookup;
+use crate::kernel::price_lookup::PriceLookupCtx;
 use crate::kernel::report_item_selector::{
     BalanceAllSelector, BalanceByAccountSelector, BalanceSelector,
 };
 use crate::kernel::{BalanceSettings, Settings};
-use crate::model::{BalanceTreeNode, TxnSet};
-use crate::report::{Report, write_acc_sel_checksum, write_price_metadata, write_report_timezone};
+use crate::model::{BalanceTreeNode, Commodity, TxnSet};
+use crate::report::{
+    PriceConversion, Report, fmt_amount, json_amount, json_metadata, write_acc_sel_checksum,
+    write_json, write_price_metadata, write_report_timezone, write_table_row,
+};
 use crate::tackler;
 use itertools::Itertools;
+use rust_decimal::Decimal;
 use rust_decimal::prelude::Zero;
-use rust_decimal::{Decimal, RoundingStrategy};
+use serde_json::{Value, json};
 use std::cmp::max;
 use std::io;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct BalanceReporter {
@@ -46,6 +53,28 @@ impl BalanceReporter {
     fn get_acc_selector(&self) -> Result<Box<dyn BalanceSelector>, tackler::Error> {
         BalanceReporter::acc_selector(&self.report_settings.ras)
     }
+
+    fn balance(
+        &self,
+        cfg: &Settings,
+        txn_data: &TxnSet<'_>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        bal_acc_sel: &dyn BalanceSelector,
+    ) -> Result<Balance, tackler::Error> {
+        let bal = Balance::from(
+            &self.report_settings.title,
+            txn_data,
+            price_lookup_ctx,
+            bal_acc_sel,
+            cfg,
+        )?;
+        bal.verify_normal_balances(cfg)?;
+
+        Ok(match self.report_settings.natural_signs {
+            true => bal.natural_signs(cfg),
+            false => bal,
+        })
+    }
 }
 
 impl BalanceReporter {
@@ -54,35 +83,37 @@ impl BalanceReporter {
         bal_report: &Balance,
         bal_settings: &BalanceSettings,
     ) -> Result<(), tackler::Error> {
+        let amount_fmt = &bal_settings.amount_fmt;
         let get_max_sum_len = |bal: &BTNs, f: fn(&BalanceTreeNode) -> Decimal| -> usize {
             bal.iter()
                 .map(|btn| {
                     let d = f(btn);
                     // include space for '+-' to the length always
-                    format!("{:+.prec$}", d, prec = bal_settings.scale.get_precision(&d))
-                        .chars()
-                        .count()
+                    let sign_len = usize::from(!d.is_sign_negative());
+                    amount_fmt.txt_width(&d, &btn.acctn.comm.name) + sign_len
                 })
                 .fold(0, max)
         };
-        fn get_max_delta_len(deltas: &Deltas) -> usize {
+        let get_max_delta_len = |deltas: &Deltas| -> usize {
             deltas
                 .iter()
-                .map(|(_, d)| format!("{}", d).chars().count())
+                .map(|(comm, d)| {
+                    amount_fmt.txt_width(d, comm.as_ref().map_or("", |c| c.name.as_str()))
+                })
                 .fold(0, max)
-        }
-        /// Max used length of commodity could be calculated from deltas
-        /// because all balance account commodities are present in there
-        fn get_max_commodity_len(deltas: &Deltas) -> usize {
+        };
+        // Max used length of commodity could be calculated from deltas
+        // because all balance account commodities are present in there
+        let get_max_commodity_len = |deltas: &Deltas| -> usize {
             deltas
-                .iter()
-                .map(|(opt_comm, _)| {
-                    opt_comm
-                        .as_ref()
-                        .map_or(0, |comm| comm.name.chars().count())
+                .keys()
+                .map(|opt_comm| {
+                    opt_comm.as_ref().map_or(0, |comm| {
+                        amount_fmt.txt_commodity(&comm.name).chars().count()
+                    })
                 })
                 .fold(0, max)
-        }
+        };
 
         let delta_max_len = get_max_delta_len(&bal_report.deltas);
         let comm_max_len = get_max_commodity_len(&bal_report.deltas);
@@ -108,20 +139,15 @@ impl BalanceReporter {
         };
         let filler_field_len = filler_field.chars().count();
 
-        fn make_commodity_field(comm_max_len: usize, btn: &BalanceTreeNode) -> String {
+        let make_commodity_field = |comm_max_len: usize, btn: &BalanceTreeNode| -> String {
             if comm_max_len.is_zero() {
                 // always separate with two spaces
                 " ".repeat(2)
             } else {
-                let comm = &btn.acctn.comm;
-                match &comm.is_any() {
-                    true => {
-                        format!(" {: <cl$}  ", comm.name, cl = comm_max_len)
-                    }
-                    false => format!(" {}  ", " ".repeat(comm_max_len)),
-                }
+                let comm = amount_fmt.txt_commodity(&btn.acctn.comm.name);
+                format!(" {: <cl$}  ", comm, cl = comm_max_len)
             }
-        }
+        };
 
         let left_ruler = " ".repeat(9);
 
@@ -130,21 +156,13 @@ impl BalanceReporter {
 
         if !bal_report.is_empty() {
             for btn in &bal_report.bal {
-                let prec_1 = bal_settings.scale.get_precision(&btn.account_sum);
-                let prec_2 = bal_settings.scale.get_precision(&btn.sub_acc_tree_sum);
-
+                let comm = &btn.acctn.comm.name;
                 writeln!(
                     writer,
-                    "{left_ruler}{:>asl$.prec_1$}{:>width$}{:>satsl$.prec_2$}{}{}",
-                    btn.account_sum.round_dp_with_strategy(
-                        prec_1 as u32,
-                        RoundingStrategy::MidpointAwayFromZero
-                    ),
+                    "{left_ruler}{:>asl$}{:>width$}{:>satsl$}{}{}",
+                    amount_fmt.txt_amount(&btn.account_sum, comm),
                     "",
-                    btn.sub_acc_tree_sum.round_dp_with_strategy(
-                        prec_2 as u32,
-                        RoundingStrategy::MidpointAwayFromZero
-                    ),
+                    amount_fmt.txt_amount(&btn.sub_acc_tree_sum, comm),
                     make_commodity_field(comm_max_len, btn),
                     btn.acctn.atn,
                     asl = left_sum_len,
@@ -171,19 +189,16 @@ impl BalanceReporter {
                 i.0.as_ref()
                     .map_or(String::default(), |comm| comm.name.clone())
             });
-            for delta in deltas {
-                let prec = bal_settings.scale.get_precision(delta.1);
+            for (comm, delta) in deltas {
+                let comm = comm.as_ref().map_or("", |c| c.name.as_str());
                 writeln!(
                     writer,
-                    "{left_ruler}{:>width$.prec$}{}",
-                    delta.1.round_dp_with_strategy(
-                        prec as u32,
-                        RoundingStrategy::MidpointAwayFromZero
-                    ),
-                    delta
-                        .0
-                        .as_ref()
-                        .map_or(String::default(), |c| format!(" {}", &c.name)),
+                    "{left_ruler}{:>width$}{}",
+                    amount_fmt.txt_amount(delta, comm),
+                    match amount_fmt.txt_commodity(comm) {
+                        "" => String::default(),
+                        c => format!(" {}", c),
+                    },
                     width = left_sum_len,
                 )?;
             }
@@ -192,6 +207,81 @@ impl BalanceReporter {
     }
 }
 
+impl BalanceReporter {
+    pub(crate) fn json_report(bal_report: &Balance, bal_settings: &BalanceSettings) -> Value {
+        let amount_fmt = &bal_settings.amount_fmt;
+
+        let balances = bal_report
+            .bal
+            .iter()
+            .map(|btn| {
+                let comm = &btn.acctn.comm;
+                json!({
+                    "accountSum": json_amount(&btn.account_sum, &comm.name, amount_fmt),
+                    "accountTreeSum": json_amount(&btn.sub_acc_tree_sum, &comm.name, amount_fmt),
+                    "account": btn.acctn.atn.account,
+                    "commodity": comm.is_any().then(|| comm.name.clone()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let deltas = bal_report
+            .deltas
+            .iter()
+            .sorted_by_key(|i| {
+                i.0.as_ref()
+                    .map_or(String::default(), |comm| comm.name.clone())
+            })
+            .map(|(comm, delta)| {
+                json!({
+                    "delta": json_amount(delta, comm.as_ref().map_or("", |c| c.name.as_str()), amount_fmt),
+                    "commodity": comm.as_ref().filter(|c| c.is_any()).map(|c| c.name.clone()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "title": bal_report.title,
+            "balances": balances,
+            "deltas": deltas,
+        })
+    }
+}
+
+impl BalanceReporter {
+    pub(crate) const TABLE_HEADER: [&'static str; 4] =
+        ["account", "commodity", "account_sum", "account_tree_sum"];
+
+    pub(crate) fn table_rows(
+        bal_report: &Balance,
+        bal_settings: &BalanceSettings,
+    ) -> Vec<[String; 4]> {
+        let amount_fmt = &bal_settings.amount_fmt;
+        bal_report
+            .bal
+            .iter()
+            .map(|btn| {
+                [
+                    btn.acctn.atn.account.clone(),
+                    btn.acctn.comm.name.clone(),
+                    fmt_amount(&btn.account_sum, &btn.acctn.comm.name, amount_fmt),
+                    fmt_amount(&btn.sub_acc_tree_sum, &btn.acctn.comm.name, amount_fmt),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl PriceConversion for BalanceReporter {
+    fn price_lookup(&self) -> &PriceLookup {
+        &self.report_settings.price_lookup
+    }
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>> {
+        self.report_settings.report_commodity.clone()
+    }
+}
+
 impl Report for BalanceReporter {
     fn write_txt_report<W: io::Write + ?Sized>(
         &self,
@@ -201,31 +291,235 @@ impl Report for BalanceReporter {
     ) -> Result<(), tackler::Error> {
         let bal_acc_sel = self.get_acc_selector()?;
 
-        let price_lookup_ctx = self.report_settings.price_lookup.make_ctx(
-            &txn_data.txns,
-            self.report_settings.report_commodity.clone(),
-            &cfg.price.price_db,
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            write_acc_sel_checksum(cfg, writer, bal_acc_sel.as_ref())?;
+
+            if !price_lookup_ctx.is_empty() {
+                write_report_timezone(cfg, writer)?;
+            }
+
+            write_price_metadata(cfg, writer, price_lookup_ctx)?;
+
+            writeln!(writer)?;
+
+            let bal_report = self.balance(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
+
+            BalanceReporter::txt_report(writer, &bal_report, &self.report_settings)?;
+            Ok(())
+        })
+    }
+
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let bal_acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let metadata = json_metadata(
+                cfg,
+                txn_data,
+                bal_acc_sel.as_ref(),
+                price_lookup_ctx,
+                !price_lookup_ctx.is_empty(),
+            )?;
+
+            let bal_report = self.balance(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
+
+            let mut report = BalanceReporter::json_report(&bal_report, &self.report_settings);
+            report["metadata"] = metadata;
+
+            write_json(writer, &report)
+        })
+    }
+
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error> {
+        let bal_acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let bal_report = self.balance(cfg, txn_data, price_lookup_ctx, bal_acc_sel.as_ref())?;
+
+            write_table_row(writer, delimiter, &BalanceReporter::TABLE_HEADER)?;
+            for row in BalanceReporter::table_rows(&bal_report, &self.report_settings) {
+                write_table_row(writer, delimiter, &row.each_ref().map(String::as_str))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccountType, CommodityFormat, Rounding, SymbolPosition};
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn balance_txt_report_with_commodity_formats() {
+        let txns = indoc!(
+            "|2024-01-01 'btc
+             | a:btc   0.123456789 BTC
+             | e:fx
+             |
+             |2024-01-01 'eur
+             | e:fx    5000.5 EUR
+             | a:cash
+             |
+             |2024-01-02 'yen
+             | e:food  1234.5 JPY
+             | a:yen
+             |"
         );
+        let mut settings = Settings::default();
+        settings.set_commodity_formats(&[
+            CommodityFormat {
+                name: "BTC".to_string(),
+                decimals: Some(8),
+                rounding: Rounding::Down,
+                ..CommodityFormat::default()
+            },
+            CommodityFormat {
+                name: "EUR".to_string(),
+                symbol: Some("€".to_string()),
+                ..CommodityFormat::default()
+            },
+            CommodityFormat {
+                name: "JPY".to_string(),
+                decimals: Some(0),
+                symbol: Some("¥".to_string()),
+                symbol_position: SymbolPosition::Prefix,
+                rounding: Rounding::HalfEven,
+            },
+        ]);
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
 
-        write_acc_sel_checksum(cfg, writer, bal_acc_sel.as_ref())?;
+        let reporter = BalanceReporter::try_from(&settings).unwrap(/*:test:*/);
+        let bal = reporter
+            .balance(
+                &settings,
+                &txn_set,
+                &PriceLookupCtx::default(),
+                &BalanceAllSelector {},
+            )
+            .unwrap(/*:test:*/);
 
-        if !price_lookup_ctx.is_empty() {
-            write_report_timezone(cfg, writer)?;
-        }
+        let mut buf = Vec::new();
+        BalanceReporter::txt_report(&mut buf, &bal, &reporter.report_settings).unwrap(/*:test:*/);
+
+        let reference = indoc!(
+            "|
+             |
+             |           0.00000000        0.12345678 BTC  a
+             |           0.12345678        0.12345678 BTC  a:btc
+             |           0.00000000       -0.12345678 BTC  e
+             |          -0.12345678       -0.12345678 BTC  e:fx
+             |                 0.00          -5000.50 €    a
+             |             -5000.50          -5000.50 €    a:cash
+             |                 0.00           5000.50 €    e
+             |              5000.50           5000.50 €    e:fx
+             |                   ¥0            -¥1234      a
+             |               -¥1234            -¥1234      a:yen
+             |                   ¥0             ¥1234      e
+             |                ¥1234             ¥1234      e:food
+             |=========================
+             |           0.00000000 BTC
+             |                 0.00 €
+             |                   ¥0
+             |"
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(buf).unwrap(/*:test:*/), reference);
+    }
 
-        write_price_metadata(cfg, writer, &price_lookup_ctx)?;
+    #[test]
+    fn balance_txt_report_natural_signs() {
+        let txns = indoc!(
+            "|2024-01-01 'salary
+             | Assets:Bank    100
+             | Income:Salary
+             |"
+        );
+        let mut settings = Settings::default();
+        settings.report.natural_signs = true;
+        settings.set_account_types(&[
+            ("Assets", AccountType::Asset),
+            ("Income", AccountType::Income),
+        ]);
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
 
-        writeln!(writer)?;
+        let reporter = BalanceReporter::try_from(&settings).unwrap(/*:test:*/);
+        let bal = reporter
+            .balance(
+                &settings,
+                &txn_set,
+                &PriceLookupCtx::default(),
+                &BalanceAllSelector {},
+            )
+            .unwrap(/*:test:*/);
 
-        let bal_report = Balance::from(
-            &self.report_settings.title,
-            txn_data,
-            &price_lookup_ctx,
-            bal_acc_sel.as_ref(),
-            cfg,
-        )?;
+        let mut buf = Vec::new();
+        BalanceReporter::txt_report(&mut buf, &bal, &reporter.report_settings).unwrap(/*:test:*/);
 
-        BalanceReporter::txt_report(writer, &bal_report, &self.report_settings)?;
-        Ok(())
+        // signs are flipped only for display, deltas are sums of original account sums
+        let reference = indoc!(
+            "|
+             |
+             |                 0.00    100.00  Assets
+             |               100.00    100.00  Assets:Bank
+             |                 0.00    100.00  Income
+             |               100.00    100.00  Income:Salary
+             |=====================
+             |                 0.00
+             |"
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(buf).unwrap(/*:test:*/), reference);
+    }
+
+    #[test]
+    fn balance_json_report() {
+        let txns = indoc!(
+            "|2024-01-01 'eur
+             | e:fx    5000.5 EUR
+             | a:cash
+             |"
+        );
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let reporter = BalanceReporter::try_from(&settings).unwrap(/*:test:*/);
+        let mut buf = Vec::new();
+        reporter
+            .write_json_report(&settings, &mut buf, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let report: Value = serde_json::from_slice(&buf).unwrap(/*:test:*/);
+        let reference = json!({
+            "metadata": { "items": [] },
+            "title": "",
+            "balances": [
+                { "account": "a", "accountSum": "0.00", "accountTreeSum": "-5000.50", "commodity": "EUR" },
+                { "account": "a:cash", "accountSum": "-5000.50", "accountTreeSum": "-5000.50", "commodity": "EUR" },
+                { "account": "e", "accountSum": "0.00", "accountTreeSum": "5000.50", "commodity": "EUR" },
+                { "account": "e:fx", "accountSum": "5000.50", "accountTreeSum": "5000.50", "commodity": "EUR" },
+            ],
+            "deltas": [
+                { "delta": "0.00", "commodity": "EUR" },
+            ],
+        });
+        assert_eq!(report, reference);
     }
 }
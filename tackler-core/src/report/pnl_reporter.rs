@@ -0,0 +1,434 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::kernel::pnl::{Pnl, PnlSum};
+use crate::kernel::price_lookup::PriceLookup;
+use crate::kernel::price_lookup::PriceLookupCtx;
+use crate::kernel::report_item_selector::{PnlAllSelector, PnlByAccountSelector, PnlSelector};
+use crate::kernel::{PnlSettings, Settings};
+use crate::model::{Commodity, TxnRefs, TxnSet};
+use crate::report::{
+    PriceConversion, Report, fmt_amount, json_amount, json_metadata, write_acc_sel_checksum,
+    write_json, write_price_metadata, write_report_timezone, write_table_row,
+};
+use crate::tackler;
+use serde_json::{Value, json};
+use std::cmp::max;
+use std::io;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct PnlReporter {
+    pub report_settings: PnlSettings,
+}
+
+impl TryFrom<&Settings> for PnlReporter {
+    type Error = tackler::Error;
+
+    fn try_from(settings: &Settings) -> Result<Self, Self::Error> {
+        Ok(PnlReporter {
+            report_settings: PnlSettings::try_from(settings)?,
+        })
+    }
+}
+
+impl PnlReporter {
+    fn get_acc_selector(&self) -> Result<Box<dyn PnlSelector>, tackler::Error> {
+        let ras = &self.report_settings.ras;
+        if ras.is_empty() {
+            Ok(Box::<PnlAllSelector>::default())
+        } else {
+            let s: Vec<_> = ras.iter().map(|s| s.as_str()).collect();
+            let ras = PnlByAccountSelector::from(&s)?;
+            Ok(Box::new(ras))
+        }
+    }
+
+    fn get_pnl(
+        &self,
+        cfg: &Settings,
+        txn_data: &TxnSet<'_>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+        acc_sel: &dyn PnlSelector,
+    ) -> Result<Pnl, tackler::Error> {
+        Pnl::from(
+            &self.report_settings.title,
+            txn_data,
+            price_lookup_ctx,
+            acc_sel,
+            cfg,
+        )
+    }
+}
+
+/// One formatted row of txt report
+struct TxtRow {
+    realized: String,
+    unrealized: String,
+    gain_comm: String,
+    units: String,
+    comm: String,
+    account: String,
+}
+
+impl TxtRow {
+    fn from(
+        sum: &PnlSum,
+        comm: Option<&str>,
+        account: Option<&str>,
+        settings: &PnlSettings,
+    ) -> TxtRow {
+        let amount_fmt = &settings.amount_fmt;
+        let gain_comm = sum.gain_commodity.name.as_str();
+        TxtRow {
+            realized: amount_fmt.txt_number(&sum.realized, gain_comm),
+            unrealized: sum
+                .unrealized
+                .map_or("-".to_string(), |u| amount_fmt.txt_number(&u, gain_comm)),
+            gain_comm: gain_comm.to_string(),
+            units: comm.map_or(String::default(), |c| {
+                amount_fmt.txt_number(&sum.open_units, c)
+            }),
+            comm: comm.unwrap_or_default().to_string(),
+            account: account.unwrap_or_default().to_string(),
+        }
+    }
+}
+
+impl PnlReporter {
+    fn txt_report<W: io::Write + ?Sized>(
+        writer: &mut W,
+        pnl: &Pnl,
+        settings: &PnlSettings,
+    ) -> Result<(), tackler::Error> {
+        let acc_rows: Vec<_> = pnl
+            .items
+            .iter()
+            .map(|item| {
+                let sum = PnlSum {
+                    commodity: Some(item.acctn.comm.clone()),
+                    gain_commodity: item.gain_commodity.clone(),
+                    realized: item.realized,
+                    open_units: item.open_units,
+                    unrealized: item.unrealized(),
+                };
+                TxtRow::from(
+                    &sum,
+                    Some(item.acctn.comm.name.as_str()),
+                    Some(item.acctn.atn.account.as_str()),
+                    settings,
+                )
+            })
+            .collect();
+        let comm_rows: Vec<_> = pnl
+            .by_commodity()
+            .iter()
+            .map(|sum| {
+                let comm = sum.commodity.as_ref().map(|c| c.name.as_str());
+                TxtRow::from(sum, comm, None, settings)
+            })
+            .collect();
+        let total_rows: Vec<_> = pnl
+            .totals()
+            .iter()
+            .map(|sum| TxtRow::from(sum, None, None, settings))
+            .collect();
+
+        let all_rows = || acc_rows.iter().chain(&comm_rows).chain(&total_rows);
+        let width = |hdr: &str, f: fn(&TxtRow) -> &String| {
+            all_rows()
+                .map(|r| f(r).chars().count())
+                .fold(hdr.chars().count(), max)
+        };
+        let realized_len = max(12, width("realized", |r| &r.realized));
+        let unrealized_len = max(12, width("unrealized", |r| &r.unrealized));
+        let gain_comm_len = width("", |r| &r.gain_comm);
+        let units_len = width("", |r| &r.units);
+        let comm_len = width("", |r| &r.comm);
+
+        let left_ruler = " ".repeat(9);
+        let fmt_row = |r: &TxtRow| {
+            format!(
+                "{left_ruler}{:>rl$}  {:>ul$} {:<gcl$}  {:>nl$} {:<cl$}  {}",
+                r.realized,
+                r.unrealized,
+                r.gain_comm,
+                r.units,
+                r.comm,
+                r.account,
+                rl = realized_len,
+                ul = unrealized_len,
+                gcl = gain_comm_len,
+                nl = units_len,
+                cl = comm_len,
+            )
+            .trim_end()
+            .to_string()
+        };
+        let ruler = "=".repeat(
+            left_ruler.chars().count() + realized_len + 2 + unrealized_len + 1 + gain_comm_len,
+        );
+
+        writeln!(writer, "{}", pnl.title)?;
+        writeln!(writer, "{}", "-".repeat(pnl.title.chars().count()))?;
+
+        if !pnl.is_empty() {
+            writeln!(
+                writer,
+                "{left_ruler}{:>rl$}  {:>ul$}",
+                "realized",
+                "unrealized",
+                rl = realized_len,
+                ul = unrealized_len,
+            )?;
+            for r in &acc_rows {
+                writeln!(writer, "{}", fmt_row(r))?;
+            }
+            writeln!(writer, "{}", ruler)?;
+            for r in &comm_rows {
+                writeln!(writer, "{}", fmt_row(r))?;
+            }
+            writeln!(writer, "{}", ruler)?;
+            for r in &total_rows {
+                writeln!(writer, "{}", fmt_row(r))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PnlReporter {
+    fn json_sum(sum: &PnlSum, settings: &PnlSettings) -> Value {
+        let amount_fmt = &settings.amount_fmt;
+        let gain_comm = sum.gain_commodity.name.as_str();
+        json!({
+            "realized": json_amount(&sum.realized, gain_comm, amount_fmt),
+            "unrealized": sum.unrealized.map(|u| json_amount(&u, gain_comm, amount_fmt)),
+            "gainCommodity": sum.gain_commodity.name,
+        })
+    }
+
+    fn json_report(pnl: &Pnl, settings: &PnlSettings) -> Value {
+        let amount_fmt = &settings.amount_fmt;
+
+        let accounts = pnl
+            .items
+            .iter()
+            .map(|item| {
+                let comm = item.acctn.comm.name.as_str();
+                let gain_comm = item.gain_commodity.name.as_str();
+                json!({
+                    "account": item.acctn.atn.account,
+                    "commodity": item.acctn.comm.name,
+                    "openUnits": json_amount(&item.open_units, comm, amount_fmt),
+                    "realized": json_amount(&item.realized, gain_comm, amount_fmt),
+                    "unrealized": item.unrealized().map(|u| json_amount(&u, gain_comm, amount_fmt)),
+                    "gainCommodity": item.gain_commodity.name,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let commodities = pnl
+            .by_commodity()
+            .iter()
+            .map(|sum| {
+                let mut v = PnlReporter::json_sum(sum, settings);
+                v["commodity"] = json!(sum.commodity.as_ref().map(|c| c.name.clone()));
+                let comm = sum.commodity.as_ref().map_or("", |c| c.name.as_str());
+                v["openUnits"] = json_amount(&sum.open_units, comm, amount_fmt);
+                v
+            })
+            .collect::<Vec<_>>();
+
+        let totals = pnl
+            .totals()
+            .iter()
+            .map(|sum| PnlReporter::json_sum(sum, settings))
+            .collect::<Vec<_>>();
+
+        json!({
+            "title": pnl.title,
+            "accounts": accounts,
+            "commodities": commodities,
+            "totals": totals,
+        })
+    }
+}
+
+impl PnlReporter {
+    const TABLE_HEADER: [&'static str; 6] = [
+        "account",
+        "commodity",
+        "open_units",
+        "realized",
+        "unrealized",
+        "gain_commodity",
+    ];
+
+    fn table_rows(pnl: &Pnl, settings: &PnlSettings) -> Vec<[String; 6]> {
+        let amount_fmt = &settings.amount_fmt;
+        pnl.items
+            .iter()
+            .map(|item| {
+                let comm = item.acctn.comm.name.as_str();
+                let gain_comm = item.gain_commodity.name.as_str();
+                [
+                    item.acctn.atn.account.clone(),
+                    item.acctn.comm.name.clone(),
+                    fmt_amount(&item.open_units, comm, amount_fmt),
+                    fmt_amount(&item.realized, gain_comm, amount_fmt),
+                    item.unrealized()
+                        .map_or(String::default(), |u| fmt_amount(&u, gain_comm, amount_fmt)),
+                    item.gain_commodity.name.clone(),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl PriceConversion for PnlReporter {
+    fn price_lookup(&self) -> &PriceLookup {
+        &self.report_settings.price_lookup
+    }
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>> {
+        self.report_settings.report_commodity.clone()
+    }
+
+    /// PnL is made with all txns, as lots could be opened by
+    /// txns which are not selected.
+    fn price_txns<'a, 't>(&self, txn_data: &'a TxnSet<'t>) -> &'a TxnRefs<'t> {
+        &txn_data.all_txns
+    }
+}
+
+impl Report for PnlReporter {
+    fn write_txt_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            write_acc_sel_checksum(cfg, writer, acc_sel.as_ref())?;
+
+            if !price_lookup_ctx.is_empty() {
+                write_report_timezone(cfg, writer)?;
+            }
+
+            write_price_metadata(cfg, writer, price_lookup_ctx)?;
+
+            writeln!(writer)?;
+
+            let pnl = self.get_pnl(cfg, txn_data, price_lookup_ctx, acc_sel.as_ref())?;
+
+            PnlReporter::txt_report(writer, &pnl, &self.report_settings)
+        })
+    }
+
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let metadata = json_metadata(
+                cfg,
+                txn_data,
+                acc_sel.as_ref(),
+                price_lookup_ctx,
+                !price_lookup_ctx.is_empty(),
+            )?;
+
+            let pnl = self.get_pnl(cfg, txn_data, price_lookup_ctx, acc_sel.as_ref())?;
+
+            let mut report = PnlReporter::json_report(&pnl, &self.report_settings);
+            report["metadata"] = metadata;
+
+            write_json(writer, &report)
+        })
+    }
+
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error> {
+        let acc_sel = self.get_acc_selector()?;
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let pnl = self.get_pnl(cfg, txn_data, price_lookup_ctx, acc_sel.as_ref())?;
+
+            write_table_row(writer, delimiter, &PnlReporter::TABLE_HEADER)?;
+            for row in PnlReporter::table_rows(&pnl, &self.report_settings) {
+                write_table_row(writer, delimiter, &row.each_ref().map(String::as_str))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn pnl_txt_report() {
+        let txns = indoc!(
+            "|2014-05-01 'opening / buy
+             | inventory  1 USD {1.38 EUR} @ 1.38 EUR
+             | a:cash    -1.38 EUR
+             |
+             |2015-01-01 'buy more
+             | inventory  2 USD {1.20 EUR} @ 1.20 EUR
+             | a:cash    -2.40 EUR
+             |
+             |2017-01-01 'close / sell at loss
+             | inventory  -1 USD {1.38 EUR} @ 1.04 EUR
+             | a:cash      1.04 EUR
+             | PnL
+             |"
+        );
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let reporter = PnlReporter::try_from(&settings).unwrap(/*:test:*/);
+        let pnl = reporter
+            .get_pnl(
+                &settings,
+                &txn_set,
+                &PriceLookupCtx::default(),
+                &PnlAllSelector::default(),
+            )
+            .unwrap(/*:test:*/);
+
+        let mut buf = Vec::new();
+        PnlReporter::txt_report(&mut buf, &pnl, &reporter.report_settings).unwrap(/*:test:*/);
+
+        let reference = indoc!(
+            "|PnL Report
+             |----------
+             |             realized    unrealized
+             |                -0.34             - EUR  2.00 USD  inventory
+             |=======================================
+             |                -0.34             - EUR  2.00 USD
+             |=======================================
+             |                -0.34             - EUR
+             |"
+        )
+        .strip_margin();
+        assert_eq!(String::from_utf8(buf).unwrap(/*:test:*/), reference);
+    }
+}
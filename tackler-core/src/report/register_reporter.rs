@@ -5,16 +5,22 @@
 
 use crate::kernel::Settings;
 use crate::kernel::accumulator;
+use crate::kernel::price_lookup::PriceLookup;
 use crate::kernel::report_item_selector::{
     RegisterAllSelector, RegisterByAccountSelector, RegisterSelector,
 };
 use crate::kernel::report_settings::RegisterSettings;
-use crate::model::{RegisterEntry, TxnSet};
-use crate::report::{Report, write_acc_sel_checksum, write_price_metadata, write_report_timezone};
+use crate::model::{Commodity, RegisterEntry, TxnSet};
+use crate::report::{
+    PriceConversion, Report, fmt_amount, json_amount, json_metadata, write_acc_sel_checksum,
+    write_json, write_price_metadata, write_report_timezone, write_table_row,
+};
 use crate::tackler;
 use jiff::Zoned;
 use jiff::tz::TimeZone;
+use serde_json::{Value, json};
 use std::io;
+use std::sync::Arc;
 use tackler_api::txn_ts;
 use tackler_api::txn_ts::TimestampStyle;
 
@@ -37,19 +43,21 @@ impl RegisterReporter {
     }
 }
 
+fn get_ts_formatter(register_settings: &RegisterSettings) -> fn(&Zoned, TimeZone) -> String {
+    match register_settings.timestamp_style {
+        TimestampStyle::Date => txn_ts::as_tz_date,
+        TimestampStyle::Secodns => txn_ts::as_tz_seconds,
+        TimestampStyle::Full => txn_ts::as_tz_full,
+    }
+}
+
 fn reg_entry_txt_writer<W: io::Write + ?Sized>(
     f: &mut W,
     re: &RegisterEntry<'_>,
     register_settings: &RegisterSettings,
 ) -> Result<(), tackler::Error> {
-    let ts_style = register_settings.timestamp_style;
     let report_tz = register_settings.report_tz.clone();
-
-    let fmt: fn(&Zoned, TimeZone) -> String = match ts_style {
-        TimestampStyle::Date => txn_ts::as_tz_date,
-        TimestampStyle::Secodns => txn_ts::as_tz_seconds,
-        TimestampStyle::Full => txn_ts::as_tz_full,
-    };
+    let fmt = get_ts_formatter(register_settings);
 
     if !re.posts.is_empty() {
         write!(f, "{}", re.fmt_with_cfg(fmt, report_tz, register_settings))?;
@@ -57,6 +65,112 @@ fn reg_entry_txt_writer<W: io::Write + ?Sized>(
     Ok(())
 }
 
+fn reg_entry_json(re: &RegisterEntry<'_>, register_settings: &RegisterSettings) -> Value {
+    let fmt = get_ts_formatter(register_settings);
+    let amount_fmt = &register_settings.amount_fmt;
+    let hdr = &re.txn.header;
+
+    let txn = json!({
+        "timestamp": fmt(&hdr.timestamp, register_settings.report_tz.clone()),
+        "code": hdr.code,
+        "description": hdr.description,
+        "uuid": hdr.uuid.map(|u| u.to_string()),
+        "location": hdr.location.as_ref().map(|geo| json!({
+            "lat": geo.lat.to_string(),
+            "lon": geo.lon.to_string(),
+            "alt": geo.alt.map(|alt| alt.to_string()),
+        })),
+        "tags": hdr.tags.as_ref().map(|tags| {
+            tags.iter().map(|t| t.to_string()).collect::<Vec<_>>()
+        }),
+        "comments": hdr.comments,
+    });
+
+    let postings = re
+        .posts
+        .iter()
+        .map(|p| {
+            let comm = &p.post.acctn.comm;
+            let rt_comm = &p.target_commodity;
+            json!({
+                "account": p.post.acctn.atn.account,
+                "amount": json_amount(&p.post.amount, &comm.name, amount_fmt),
+                "commodity": comm.is_any().then(|| comm.name.clone()),
+                "rate": p.rate.filter(|_| p.is_commodity_conv()).map(|r| amount_fmt.rate(&r)),
+                "runningTotal": json_amount(&p.amount, &rt_comm.name, amount_fmt),
+                "runningTotalCommodity": rt_comm.is_any().then(|| rt_comm.name.clone()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "txn": txn,
+        "postings": postings,
+    })
+}
+
+const REG_TABLE_HEADER: [&str; 10] = [
+    "timestamp",
+    "code",
+    "description",
+    "uuid",
+    "account",
+    "amount",
+    "commodity",
+    "rate",
+    "running_total",
+    "running_total_commodity",
+];
+
+fn reg_entry_table_writer<W: io::Write + ?Sized>(
+    writer: &mut W,
+    delimiter: char,
+    re: &RegisterEntry<'_>,
+    register_settings: &RegisterSettings,
+) -> Result<(), tackler::Error> {
+    let fmt = get_ts_formatter(register_settings);
+    let amount_fmt = &register_settings.amount_fmt;
+    let hdr = &re.txn.header;
+
+    let ts = fmt(&hdr.timestamp, register_settings.report_tz.clone());
+    let uuid = hdr.uuid.map(|u| u.to_string()).unwrap_or_default();
+
+    for p in &re.posts {
+        let rate = p
+            .rate
+            .filter(|_| p.is_commodity_conv())
+            .map(|r| amount_fmt.rate(&r))
+            .unwrap_or_default();
+        write_table_row(
+            writer,
+            delimiter,
+            &[
+                ts.as_str(),
+                hdr.code.as_deref().unwrap_or_default(),
+                hdr.description.as_deref().unwrap_or_default(),
+                uuid.as_str(),
+                p.post.acctn.atn.account.as_str(),
+                fmt_amount(&p.post.amount, &p.post.acctn.comm.name, amount_fmt).as_str(),
+                p.post.acctn.comm.name.as_str(),
+                rate.as_str(),
+                fmt_amount(&p.amount, &p.target_commodity.name, amount_fmt).as_str(),
+                p.target_commodity.name.as_str(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+impl PriceConversion for RegisterReporter {
+    fn price_lookup(&self) -> &PriceLookup {
+        &self.report_settings.price_lookup
+    }
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>> {
+        self.report_settings.report_commodity.clone()
+    }
+}
+
 impl Report for RegisterReporter {
     fn write_txt_report<W: io::Write + ?Sized>(
         &self,
@@ -66,36 +180,165 @@ impl Report for RegisterReporter {
     ) -> Result<(), tackler::Error> {
         let acc_sel = self.get_acc_selector()?;
 
-        let report_commodity = self.report_settings.report_commodity.clone();
-        let price_lookup_ctx = self.report_settings.price_lookup.make_ctx(
-            &txn_data.txns,
-            report_commodity,
-            &cfg.price.price_db,
-        );
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            write_acc_sel_checksum(cfg, writer, acc_sel.as_ref())?;
 
-        write_acc_sel_checksum(cfg, writer, acc_sel.as_ref())?;
+            write_report_timezone(cfg, writer)?;
 
-        write_report_timezone(cfg, writer)?;
+            write_price_metadata(cfg, writer, price_lookup_ctx)?;
 
-        write_price_metadata(cfg, writer, &price_lookup_ctx)?;
+            writeln!(writer)?;
+            writeln!(writer)?;
 
-        writeln!(writer)?;
-        writeln!(writer)?;
+            let title = &self.report_settings.title;
+            writeln!(writer, "{}", title)?;
+            writeln!(writer, "{}", "-".repeat(title.chars().count()))?;
 
-        let title = &self.report_settings.title;
-        writeln!(writer, "{}", title)?;
-        writeln!(writer, "{}", "-".repeat(title.chars().count()))?;
+            let ras = self.get_acc_selector()?;
 
-        let ras = self.get_acc_selector()?;
+            accumulator::register_engine(
+                &txn_data.txns,
+                price_lookup_ctx,
+                ras.as_ref(),
+                writer,
+                reg_entry_txt_writer,
+                &self.report_settings,
+            )?;
+            Ok(())
+        })
+    }
 
-        accumulator::register_engine(
-            &txn_data.txns,
-            &price_lookup_ctx,
-            ras.as_ref(),
-            writer,
-            reg_entry_txt_writer,
-            &self.report_settings,
-        )?;
-        Ok(())
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        let acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let metadata = json_metadata(cfg, txn_data, acc_sel.as_ref(), price_lookup_ctx, true)?;
+
+            let mut entries = Vec::new();
+            accumulator::register_engine(
+                &txn_data.txns,
+                price_lookup_ctx,
+                acc_sel.as_ref(),
+                writer,
+                |_, re, register_settings| {
+                    if !re.posts.is_empty() {
+                        entries.push(reg_entry_json(re, register_settings));
+                    }
+                    Ok(())
+                },
+                &self.report_settings,
+            )?;
+
+            let report = json!({
+                "metadata": metadata,
+                "title": self.report_settings.title,
+                "transactions": entries,
+            });
+
+            write_json(writer, &report)
+        })
+    }
+
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error> {
+        let acc_sel = self.get_acc_selector()?;
+
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            write_table_row(writer, delimiter, &REG_TABLE_HEADER)?;
+
+            accumulator::register_engine(
+                &txn_data.txns,
+                price_lookup_ctx,
+                acc_sel.as_ref(),
+                writer,
+                |w, re, register_settings| {
+                    reg_entry_table_writer(w, delimiter, re, register_settings)
+                },
+                &self.report_settings,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn register_json_report() {
+        let txns = indoc!(
+            "|2024-01-01T10:00:00Z (#1) 'lunch
+             | # uuid: 7e0b4f6a-1c8e-4c3a-9b0a-2a2c6f1e9d01
+             | # tags: food, work
+             | e:food   12.3 EUR
+             | a:cash
+             |"
+        );
+        let mut settings = Settings::default();
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let reporter = RegisterReporter {
+            report_settings: RegisterSettings::try_from(&settings).unwrap(/*:test:*/),
+        };
+        let mut buf = Vec::new();
+        reporter
+            .write_json_report(&settings, &mut buf, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let report: Value = serde_json::from_slice(&buf).unwrap(/*:test:*/);
+        let reference = json!({
+            "metadata": {
+                "items": [
+                    { "ReportTimezone": { "timezone": "UTC" } },
+                ],
+            },
+            "title": "",
+            "transactions": [
+                {
+                    "txn": {
+                        "timestamp": "2024-01-01 10:00:00",
+                        "code": "#1",
+                        "description": "lunch",
+                        "uuid": "7e0b4f6a-1c8e-4c3a-9b0a-2a2c6f1e9d01",
+                        "location": null,
+                        "tags": [ "food", "work" ],
+                        "comments": null,
+                    },
+                    "postings": [
+                        {
+                            "account": "a:cash",
+                            "amount": "-12.30",
+                            "commodity": "EUR",
+                            "rate": null,
+                            "runningTotal": "-12.30",
+                            "runningTotalCommodity": "EUR",
+                        },
+                        {
+                            "account": "e:food",
+                            "amount": "12.30",
+                            "commodity": "EUR",
+                            "rate": null,
+                            "runningTotal": "12.30",
+                            "runningTotalCommodity": "EUR",
+                        },
+                    ],
+                },
+            ],
+        });
+        assert_eq!(report, reference);
     }
 }
@@ -0,0 +1,355 @@
+/*
+ * Tackler-NG 2025
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::kernel::accumulator;
+use crate::kernel::amount_format::AmountFormat;
+use crate::kernel::balance::Deltas;
+use crate::kernel::price_lookup::PriceLookup;
+use crate::kernel::price_lookup::PriceLookupCtx;
+use crate::kernel::report_item_selector::BalanceAllSelector;
+use crate::kernel::statement::{Statement, StatementType};
+use crate::kernel::{BalanceSettings, Settings, StatementSettings};
+use crate::model::{Commodity, TxnRefs, TxnSet};
+use crate::report::{
+    BalanceReporter, PriceConversion, Report, fmt_amount, json_amount, json_metadata,
+    write_acc_sel_checksum, write_json, write_price_metadata, write_report_timezone,
+    write_table_row,
+};
+use crate::tackler;
+use itertools::Itertools;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use std::cmp::max;
+use std::io;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct StatementReporter {
+    pub report_settings: StatementSettings,
+}
+
+/// Deltas sorted by commodity
+fn sorted_deltas(deltas: &Deltas) -> Vec<(&Option<Arc<Commodity>>, &Decimal)> {
+    deltas
+        .iter()
+        .sorted_by_key(|i| {
+            i.0.as_ref()
+                .map_or(String::default(), |comm| comm.name.clone())
+        })
+        .collect()
+}
+
+/// Width of amount column of statement
+///
+/// This is the widest account sum or delta of all sections and summary lines,
+/// but at least 12 (the same as with balance report).
+fn amount_width(stmt: &Statement, amount_fmt: &AmountFormat) -> usize {
+    let delta_width = |deltas: &Deltas| {
+        deltas
+            .iter()
+            .map(|(comm, d)| amount_fmt.txt_width(d, comm.as_ref().map_or("", |c| c.name.as_str())))
+            .fold(0, max)
+    };
+    let section_width = stmt
+        .sections
+        .iter()
+        .map(|section| {
+            let sum_width = section
+                .bal
+                .iter()
+                .map(|btn| {
+                    // include space for '+-' to the length always
+                    let sign_len = usize::from(!btn.account_sum.is_sign_negative());
+                    amount_fmt.txt_width(&btn.account_sum, &btn.acctn.comm.name) + sign_len
+                })
+                .fold(0, max);
+            max(sum_width, delta_width(&section.deltas))
+        })
+        .fold(0, max);
+    let summary_width = stmt
+        .summary
+        .iter()
+        .map(|(_, deltas)| delta_width(deltas))
+        .fold(0, max);
+
+    max(12, max(section_width, summary_width))
+}
+
+impl StatementReporter {
+    fn get_balance_settings(&self) -> BalanceSettings {
+        BalanceSettings {
+            title: String::default(),
+            ras: vec![],
+            amount_fmt: self.report_settings.amount_fmt.clone(),
+            report_commodity: self.report_settings.report_commodity.clone(),
+            price_lookup: self.report_settings.price_lookup.clone(),
+            natural_signs: true,
+        }
+    }
+
+    fn get_title(&self, stmt: &Statement) -> String {
+        match &stmt.period {
+            Some(period) => format!("{}: {}", self.report_settings.title, period),
+            None => self.report_settings.title.clone(),
+        }
+    }
+
+    fn statements(
+        &self,
+        cfg: &Settings,
+        txn_data: &TxnSet<'_>,
+        price_lookup_ctx: &PriceLookupCtx<'_>,
+    ) -> Result<Vec<Statement>, tackler::Error> {
+        let group_by_op = self
+            .report_settings
+            .group_by
+            .map(|g| accumulator::group_by_op(g, self.report_settings.report_tz.clone()));
+
+        Statement::statements(
+            self.report_settings.stmt_type,
+            txn_data,
+            group_by_op,
+            price_lookup_ctx,
+            cfg,
+        )
+    }
+}
+
+impl PriceConversion for StatementReporter {
+    fn price_lookup(&self) -> &PriceLookup {
+        &self.report_settings.price_lookup
+    }
+
+    fn report_commodity(&self) -> Option<Arc<Commodity>> {
+        self.report_settings.report_commodity.clone()
+    }
+
+    /// Balance sheet is made also with txns which are not selected,
+    /// so prices are needed for commodities of all txns
+    fn price_txns<'a, 't>(&self, txn_data: &'a TxnSet<'t>) -> &'a TxnRefs<'t> {
+        match self.report_settings.stmt_type {
+            StatementType::IncomeStatement => &txn_data.txns,
+            StatementType::BalanceSheet => &txn_data.all_txns,
+        }
+    }
+}
+
+impl Report for StatementReporter {
+    fn write_txt_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let stmts = self.statements(cfg, txn_data, price_lookup_ctx)?;
+
+            write_acc_sel_checksum(cfg, writer, &BalanceAllSelector {})?;
+
+            if self.report_settings.group_by.is_some() || !price_lookup_ctx.is_empty() {
+                write_report_timezone(cfg, writer)?;
+            }
+
+            write_price_metadata(cfg, writer, price_lookup_ctx)?;
+
+            let amount_fmt = &self.report_settings.amount_fmt;
+            let bal_settings = self.get_balance_settings();
+            let left_ruler = " ".repeat(9);
+            for stmt in &stmts {
+                let width = amount_width(stmt, amount_fmt);
+                let title = self.get_title(stmt);
+                writeln!(writer)?;
+                writeln!(writer)?;
+                writeln!(writer, "{}", title)?;
+                writeln!(writer, "{}", "=".repeat(title.chars().count()))?;
+
+                for section in &stmt.sections {
+                    writeln!(writer)?;
+                    BalanceReporter::txt_report(writer, section, &bal_settings)?;
+                }
+                for (title, deltas) in &stmt.summary {
+                    writeln!(writer)?;
+                    writeln!(writer, "{}", title)?;
+                    writeln!(writer, "{}", "-".repeat(title.chars().count()))?;
+                    for (comm, delta) in sorted_deltas(deltas) {
+                        let comm = comm.as_ref().map_or("", |c| c.name.as_str());
+                        writeln!(
+                            writer,
+                            "{left_ruler}{:>width$}{}",
+                            amount_fmt.txt_amount(delta, comm),
+                            match amount_fmt.txt_commodity(comm) {
+                                "" => String::default(),
+                                c => format!(" {}", c),
+                            },
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn write_json_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+    ) -> Result<(), tackler::Error> {
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+
+        let stmts = self.statements(cfg, txn_data, price_lookup_ctx)?;
+
+        let metadata = json_metadata(
+            cfg,
+            txn_data,
+            &BalanceAllSelector {},
+            price_lookup_ctx,
+            self.report_settings.group_by.is_some() || !price_lookup_ctx.is_empty(),
+        )?;
+
+        let amount_fmt = &self.report_settings.amount_fmt;
+        let bal_settings = self.get_balance_settings();
+        let statements = stmts
+            .iter()
+            .map(|stmt| {
+                let sections = stmt
+                    .sections
+                    .iter()
+                    .map(|section| BalanceReporter::json_report(section, &bal_settings))
+                    .collect::<Vec<_>>();
+                let summary = stmt
+                    .summary
+                    .iter()
+                    .map(|(title, deltas)| {
+                        let deltas = sorted_deltas(deltas)
+                            .into_iter()
+                            .map(|(comm, delta)| {
+                                let comm_name = comm.as_ref().map_or("", |c| c.name.as_str());
+                                json!({
+                                    "delta": json_amount(delta, comm_name, amount_fmt),
+                                    "commodity": comm.as_ref().filter(|c| c.is_any()).map(|c| c.name.clone()),
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        json!({
+                            "title": title,
+                            "deltas": deltas,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({
+                    "period": stmt.period,
+                    "sections": sections,
+                    "summary": summary,
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        let report = json!({
+            "metadata": metadata,
+            "title": self.report_settings.title,
+            "statements": statements,
+        });
+
+        write_json(writer, &report)
+        })
+    }
+
+    fn write_table_report<W: io::Write + ?Sized>(
+        &self,
+        cfg: &Settings,
+        writer: &mut W,
+        txn_data: &TxnSet<'_>,
+        delimiter: char,
+    ) -> Result<(), tackler::Error> {
+        self.with_price_lookup_ctx(cfg, txn_data, |price_lookup_ctx| {
+            let stmts = self.statements(cfg, txn_data, price_lookup_ctx)?;
+
+            let mut header = vec!["period", "section"];
+            header.extend(BalanceReporter::TABLE_HEADER);
+            write_table_row(writer, delimiter, &header)?;
+
+            let amount_fmt = &self.report_settings.amount_fmt;
+            let bal_settings = self.get_balance_settings();
+            for stmt in &stmts {
+                let period = stmt.period.as_deref().unwrap_or_default();
+                for section in &stmt.sections {
+                    for row in BalanceReporter::table_rows(section, &bal_settings) {
+                        let mut fields = vec![period, section.title.as_str()];
+                        fields.extend(row.iter().map(String::as_str));
+                        write_table_row(writer, delimiter, &fields)?;
+                    }
+                }
+                // summary rows don't have account or account tree sum
+                for (title, deltas) in &stmt.summary {
+                    for (comm, delta) in sorted_deltas(deltas) {
+                        let comm = comm.as_ref().map_or("", |c| c.name.as_str());
+                        let amount = fmt_amount(delta, comm, amount_fmt);
+                        write_table_row(
+                            writer,
+                            delimiter,
+                            &[period, title, "", comm, amount.as_str(), ""],
+                        )?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AccountType;
+    use crate::parser;
+    use indoc::indoc;
+    use tackler_rs::IndocUtils;
+
+    #[test]
+    fn income_statement_txt_report_wide_amounts() {
+        let txns = indoc!(
+            "|2024-01-15 'salary
+             | Assets:Bank  123456789012345
+             | Income:Salary
+             |
+             |2024-01-20 'rent
+             | Expenses:Rent  1200
+             | Assets:Bank
+             |"
+        );
+        let mut settings = Settings::default();
+        settings.set_account_types(&[
+            ("Assets", AccountType::Asset),
+            ("Income", AccountType::Income),
+            ("Expenses", AccountType::Expense),
+        ]);
+        let txn_data = parser::string_to_txns(&mut txns.strip_margin().as_str(), &mut settings).unwrap(/*:test:*/);
+        let txn_set = txn_data.get_all().unwrap(/*:test:*/);
+
+        let reporter = StatementReporter {
+            report_settings: StatementSettings::from(&settings, StatementType::IncomeStatement)
+                .unwrap(/*:test:*/),
+        };
+        let mut buf = Vec::new();
+        reporter
+            .write_txt_report(&settings, &mut buf, &txn_set)
+            .unwrap(/*:test:*/);
+
+        let report = String::from_utf8(buf).unwrap(/*:test:*/);
+        let lines: Vec<_> = report.lines().collect();
+        let salary = lines
+            .iter()
+            .find(|l| l.ends_with("Income:Salary"))
+            .unwrap(/*:test:*/);
+        let net_income = lines.last().unwrap(/*:test:*/);
+        // summary amount is aligned with account sums
+        assert_eq!(net_income.trim(), "123456789011145.00");
+        assert_eq!(
+            salary.find("123456789012345.00"),
+            net_income.find("123456789011145.00")
+        );
+    }
+}
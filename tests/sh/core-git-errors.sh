@@ -53,7 +53,7 @@ echo "test: $module/$test_name: $mode"
 $TACKLER_SH \
     --config $SUITE_PATH/$module/git-ok.toml \
     --input.git.ref error-01 \
-    2>&1 | grep 'parse error at line 2, column 3'
+    2>&1 | grep -e '--> .*\.txn:2:3'
 
 echo "check: ok"
 
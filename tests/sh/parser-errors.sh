@@ -22,6 +22,6 @@ $TACKLER_SH \
     --config $SUITE_PATH/$module/ok.toml \
     --input.fs.dir $SUITE_PATH/$module/ex/perr-txns \
     --input.fs.ext txn \
-    2>&1 | grep 'Tackler error: Txn Data: parse error at line 2, column 3'
+    2>&1 | grep -e '--> .*\.txn:2:3'
 
 echo "check: ok"